@@ -2,7 +2,8 @@ use crate::*;
 
 /// Represents a data 'kind' (like int or float) for a shader variable. Together
 /// with a *DataShape*, a *DataKind* forms a *DataType*. You can't construct new
-/// *DataKind*s; you can only use the built-in data types *INT*, *FLOAT*, and *BOOL*.
+/// *DataKind*s; you can only use the built-in data types *INT*, *UINT*, *FLOAT*, and
+/// *BOOL*.
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub struct DataKind {
     long_name: &'static str,
@@ -11,13 +12,13 @@ pub struct DataKind {
 
 impl DataKind {
     /// Gets the 'long' or full name of this *DataKind*. The result is either
-    /// "int", "float", or "bool".
+    /// "int", "uint", "float", or "bool".
     pub fn get_long_name(&self) -> &'static str {
         self.long_name
     }
 
     /// Gets the 'short' or glsl name of this *DataKind*. The result is either
-    /// "i", "", or "b". This method is made to construct GLSL variable names
+    /// "i", "u", "", or "b". This method is made to construct GLSL variable names
     /// and should normally only be used by Griphin implementations.
     pub fn get_short_name(&self) -> &'static str {
         self.short_name
@@ -33,6 +34,8 @@ const fn data_kind(long_name: &'static str, short_name: &'static str) -> DataKin
 
 /// The *int* *DataKind*. This will be a 32-bit signed integer in GLSL.
 pub const INT: DataKind = data_kind("int", "i");
+/// The *uint* *DataKind*. This will be a 32-bit unsigned integer in GLSL.
+pub const UINT: DataKind = data_kind("uint", "u");
 /// The *float* *DataKind*. This will be a 32-bit (single precision) float in GLSL.
 pub const FLOAT: DataKind = data_kind("float", "");
 /// The *bool* *DataKind*.
@@ -46,6 +49,7 @@ pub const BOOL: DataKind = data_kind("bool", "b");
 pub struct DataShape {
     raw_name: &'static str,
     size: u8,
+    num_columns: u8,
 }
 
 impl DataShape {
@@ -59,26 +63,74 @@ impl DataShape {
     pub fn get_size(&self) -> u8 {
         self.size
     }
+
+    /// Gets the number of columns of this *DataShape*. This is 1 for *SINGLE* and
+    /// all vectors, and the number of columns of the matrix for all matrices.
+    pub fn get_num_columns(&self) -> u8 {
+        self.num_columns
+    }
+
+    /// Gets the number of rows of this *DataShape*. For vectors, this is the number
+    /// of components.
+    pub fn get_num_rows(&self) -> u8 {
+        self.size / self.num_columns
+    }
+
+    /// Gets the alignment (in bytes) that values of this *DataShape* must have in
+    /// buffers with the *std430* layout (like storage buffers). For matrices, this
+    /// is the alignment of each column.
+    pub fn get_std430_alignment(&self) -> usize {
+        match self.get_num_rows() {
+            1 => 4,
+            2 => 8,
+            _ => 16,
+        }
+    }
+
+    /// Gets the number of bytes that values of this *DataShape* occupy in buffers
+    /// with the *std430* layout. Note that each column of a matrix is padded to
+    /// its alignment, so a *MAT3* occupies 48 bytes rather than 36.
+    pub fn get_std430_size(&self) -> usize {
+        if self.num_columns == 1 {
+            4 * self.size as usize
+        } else {
+            self.num_columns as usize * self.get_std430_alignment()
+        }
+    }
+
+    /// Gets the distance (in bytes) between consecutive elements of an array of
+    /// this *DataShape* in buffers with the *std430* layout. This is the
+    /// *std430* size, rounded up to a multiple of the *std430* alignment (so an
+    /// array of *VEC3*s has a stride of 16 bytes).
+    pub fn get_std430_array_stride(&self) -> usize {
+        let alignment = self.get_std430_alignment();
+        let size = self.get_std430_size();
+        size.div_ceil(alignment) * alignment
+    }
 }
 
-const fn data_shape(raw_name: &'static str, size: u8) -> DataShape {
-    DataShape { raw_name, size }
+const fn data_shape(raw_name: &'static str, size: u8, num_columns: u8) -> DataShape {
+    DataShape {
+        raw_name,
+        size,
+        num_columns,
+    }
 }
 
 /// The *DataShape* to indicate that a variable has a single value.
-pub const SINGLE: DataShape = data_shape("", 1);
+pub const SINGLE: DataShape = data_shape("", 1, 1);
 /// The *DataShape* to indicate that a variable is a vector of 2 values.
-pub const VEC2: DataShape = data_shape("vec2", 2);
+pub const VEC2: DataShape = data_shape("vec2", 2, 1);
 /// The *DataShape* to indicate that a variable is a vector of 3 values.
-pub const VEC3: DataShape = data_shape("vec3", 3);
+pub const VEC3: DataShape = data_shape("vec3", 3, 1);
 /// The *DataShape* to indicate that a variable is a vector of 4 values.
-pub const VEC4: DataShape = data_shape("vec4", 4);
+pub const VEC4: DataShape = data_shape("vec4", 4, 1);
 /// The *DataShape* to indicate that a variable is a matrix of 2 x 2 values.
-pub const MAT2: DataShape = data_shape("mat2", 4);
+pub const MAT2: DataShape = data_shape("mat2", 4, 2);
 /// The *DataShape* to indicate that a variable is a matrix of 3 x 3 values.
-pub const MAT3: DataShape = data_shape("mat3", 9);
+pub const MAT3: DataShape = data_shape("mat3", 9, 3);
 /// The *DataShape* to indicate that a variable is a matrix of 4 x 4 values.
-pub const MAT4: DataShape = data_shape("mat4", 16);
+pub const MAT4: DataShape = data_shape("mat4", 16, 4);
 
 /// Represents the data type of a shader variable. This is a tuple of *DataKind*
 /// and *DataShape*. Unlike *DataKind* and *DataType*, the *new* function of this
@@ -0,0 +1,157 @@
+use crate::*;
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// This error indicates that a *FlowDefinition* couldn't be parsed or loaded.
+/// The *position* points to the part of the definition that caused the error.
+#[derive(Debug)]
+pub struct DefinitionError {
+    pub position: FilePosition,
+    pub kind: DefinitionErrorKind,
+}
+
+impl DefinitionError {
+    pub(super) fn new(position: FilePosition, kind: DefinitionErrorKind) -> Self {
+        Self { position, kind }
+    }
+}
+
+/// The possible reasons why a *FlowDefinition* couldn't be parsed or loaded.
+/// See *DefinitionError*.
+#[derive(Debug)]
+pub enum DefinitionErrorKind {
+    /// A line starts with a word that is not a known statement.
+    UnknownStatement(String),
+
+    /// A statement misses the argument or property with the given name.
+    MissingArgument(&'static str),
+
+    /// A statement has more arguments than it accepts, or a property it doesn't
+    /// know.
+    UnexpectedArgument(String),
+
+    /// A statement has the same property more than once.
+    DuplicateProperty(String),
+
+    /// The value of a property or argument is not one of the allowed values.
+    InvalidValue { property: String, value: String },
+
+    /// An *input* or *output* statement was found before the first *draw*
+    /// statement.
+    NoDrawingNode,
+
+    /// Multiple grids, grid nodes, or pipelines have the same name.
+    DuplicateName(String),
+
+    /// There is no color grid or depth stencil grid with the given name.
+    UnknownGrid(String),
+
+    /// There is no grid node with the given name.
+    UnknownGridNode(String),
+
+    /// There is no pipeline with the given name.
+    UnknownPipeline(String),
+
+    /// The *ShaderPairRegistry* doesn't have a *ShaderPair* with the given name.
+    UnknownShaders(String),
+
+    /// The *GridGroupIDs* that were given to *create_render_flow_builder* have
+    /// a different number of color grids or depth stencil grids than the
+    /// definition.
+    GridCountMismatch { num_grids: usize, num_ids: usize },
+
+    /// The sample counts of a drawing node or resolve operation don't match its
+    /// grids. See the *validate_sample_counts* method of *RenderFlowBuilder*.
+    SampleCount(SampleCountError),
+
+    /// A drawing node reads a grid that can't be read by shaders. See the
+    /// *validate_grid_inputs* method of *RenderFlowBuilder*.
+    GridInput(GridInputError),
+
+    /// An output of a drawing node doesn't match the grid it writes to. See the
+    /// *validate_color_formats* method of *RenderFlowBuilder*.
+    ColorOutput(ColorOutputError),
+
+    /// The pipeline of a drawing node doesn't have an external variable or
+    /// output variable with the given name.
+    UnknownVariable(String),
+
+    /// The shader variable with the given name has the wrong kind for the
+    /// input or output it is linked to. For instance, a *Uniform* input must
+    /// be linked to a *UniformInput* variable.
+    WrongVariableKind(String),
+
+    /// The external variable or output variable with the given name of the
+    /// pipeline of a drawing node is not linked to any of its inputs or
+    /// outputs.
+    UnlinkedVariable(String),
+
+    /// The grid nodes are not linked correctly. See the *validate_grid_nodes*
+    /// method of *RenderFlowBuilder*.
+    GridNode(GridNodeError),
+}
+
+impl Display for DefinitionError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "At {}: ", self.position)?;
+        match &self.kind {
+            DefinitionErrorKind::UnknownStatement(statement) => {
+                write!(f, "Unknown statement '{}'", statement)
+            }
+            DefinitionErrorKind::MissingArgument(argument) => {
+                write!(f, "Missing argument '{}'", argument)
+            }
+            DefinitionErrorKind::UnexpectedArgument(argument) => {
+                write!(f, "Unexpected argument '{}'", argument)
+            }
+            DefinitionErrorKind::DuplicateProperty(property) => {
+                write!(f, "Property '{}' is specified more than once", property)
+            }
+            DefinitionErrorKind::InvalidValue { property, value } => {
+                write!(f, "Invalid value '{}' for '{}'", value, property)
+            }
+            DefinitionErrorKind::NoDrawingNode => write!(
+                f,
+                "Inputs and outputs must be preceded by a 'draw' statement"
+            ),
+            DefinitionErrorKind::DuplicateName(name) => {
+                write!(f, "The name '{}' is used more than once", name)
+            }
+            DefinitionErrorKind::UnknownGrid(name) => write!(f, "Unknown grid '{}'", name),
+            DefinitionErrorKind::UnknownGridNode(name) => {
+                write!(f, "Unknown grid node '{}'", name)
+            }
+            DefinitionErrorKind::UnknownPipeline(name) => {
+                write!(f, "Unknown pipeline '{}'", name)
+            }
+            DefinitionErrorKind::UnknownShaders(name) => {
+                write!(f, "Unknown shader pair '{}'", name)
+            }
+            DefinitionErrorKind::GridCountMismatch { num_grids, num_ids } => write!(
+                f,
+                "The definition has {} grids, but {} grid ids were given",
+                num_grids, num_ids
+            ),
+            DefinitionErrorKind::SampleCount(error) => write!(f, "{}", error),
+            DefinitionErrorKind::GridInput(error) => write!(f, "{}", error),
+            DefinitionErrorKind::ColorOutput(error) => write!(f, "{}", error),
+            DefinitionErrorKind::UnknownVariable(name) => {
+                write!(f, "The pipeline doesn't have a shader variable '{}'", name)
+            }
+            DefinitionErrorKind::WrongVariableKind(name) => write!(
+                f,
+                "Shader variable '{}' can't be linked to this kind of input or output",
+                name
+            ),
+            DefinitionErrorKind::UnlinkedVariable(name) => write!(
+                f,
+                "Shader variable '{}' is not linked to any input or output",
+                name
+            ),
+            DefinitionErrorKind::GridNode(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for DefinitionError {}
@@ -0,0 +1,690 @@
+use crate::*;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A collection of *ShaderPair*s that can be referred to by name. The
+/// *shaders* of every *PipelineDefinition* must be the name of one of the
+/// pairs in the registry that is used to load its *FlowDefinition*.
+#[derive(Default)]
+pub struct ShaderPairRegistry {
+    pairs: HashMap<String, Arc<ShaderPair>>,
+}
+
+impl ShaderPairRegistry {
+    /// Constructs a new empty *ShaderPairRegistry*.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the given *ShaderPair* under the given *name*. If another pair
+    /// was already registered under that name, it will be replaced and returned.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        pair: Arc<ShaderPair>,
+    ) -> Option<Arc<ShaderPair>> {
+        self.pairs.insert(name.into(), pair)
+    }
+
+    /// Gets the *ShaderPair* that was registered under the given *name*, or
+    /// *None* if there is no such pair.
+    pub fn get(&self, name: &str) -> Option<&Arc<ShaderPair>> {
+        self.pairs.get(name)
+    }
+}
+
+fn find<'a, T>(
+    items: &'a [(&str, T)],
+    name: &str,
+    position: FilePosition,
+    error: fn(String) -> DefinitionErrorKind,
+) -> Result<&'a T, DefinitionError> {
+    items
+        .iter()
+        .find(|(candidate, _)| *candidate == name)
+        .map(|(_, item)| item)
+        .ok_or_else(|| DefinitionError::new(position, error(name.to_string())))
+}
+
+fn is_grid_source(source: &InputSourceDefinition) -> bool {
+    matches!(
+        source,
+        InputSourceDefinition::Cleared(_)
+            | InputSourceDefinition::Internal(_)
+            | InputSourceDefinition::External(_)
+    )
+}
+
+// Checks that every input and output of the node is linked to a shader
+// variable of the right kind, and that every external variable and output
+// variable of the pipeline is linked. The *Model* inputs are not checked, since
+// the vertex attributes are not part of the pipeline.
+fn check_variables(
+    pipeline: &dyn GraphicsPipeline,
+    node: &DrawingNodeDefinition,
+) -> Result<(), DefinitionError> {
+    let find_external = |name: &str| {
+        pipeline
+            .get_external_variables()
+            .iter()
+            .find(|variable| variable.get_name().to_str() == name)
+            .map(|variable| variable.get_variable_type())
+    };
+    let has_output = |name: &str| {
+        pipeline
+            .get_output_variables()
+            .iter()
+            .any(|variable| variable.get_name().to_str() == name)
+    };
+    let check = |name: &str, is_right_kind: bool, position| {
+        if find_external(name).is_none() && !has_output(name) {
+            Err(DefinitionError::new(
+                position,
+                DefinitionErrorKind::UnknownVariable(name.to_string()),
+            ))
+        } else if !is_right_kind {
+            Err(DefinitionError::new(
+                position,
+                DefinitionErrorKind::WrongVariableKind(name.to_string()),
+            ))
+        } else {
+            Ok(())
+        }
+    };
+
+    for input in &node.inputs {
+        let name = input.variable.as_str();
+        let expected = match &input.source {
+            InputSourceDefinition::Model => continue,
+            // The node either reads the pixels of the grid, or draws on it
+            source if is_grid_source(source) => {
+                let reads_pixels = matches!(
+                    find_external(name),
+                    Some(ExternalShaderVariableType::ColorGridInput)
+                        | Some(ExternalShaderVariableType::DepthStencilGridInput)
+                );
+                check(name, reads_pixels || has_output(name), input.position)?;
+                continue;
+            }
+            InputSourceDefinition::Sampled(_) | InputSourceDefinition::Texture => {
+                ExternalShaderVariableType::TextureInput
+            }
+            InputSourceDefinition::Uniform => ExternalShaderVariableType::UniformInput,
+            InputSourceDefinition::StorageBuffer => ExternalShaderVariableType::StorageBuffer,
+            InputSourceDefinition::PushConstant => ExternalShaderVariableType::PushConstant,
+            _ => unreachable!(),
+        };
+        check(name, find_external(name) == Some(expected), input.position)?;
+    }
+    for output in &node.outputs {
+        let name = output.variable.as_str();
+        check(name, has_output(name), output.position)?;
+    }
+
+    let external_names = pipeline
+        .get_external_variables()
+        .iter()
+        .map(|variable| variable.get_name());
+    for name in external_names {
+        let is_linked = node.inputs.iter().any(|input| {
+            input.source != InputSourceDefinition::Model && input.variable == name.to_str()
+        });
+        if !is_linked {
+            return Err(DefinitionError::new(
+                node.position,
+                DefinitionErrorKind::UnlinkedVariable(name.to_string()),
+            ));
+        }
+    }
+    let output_names = pipeline
+        .get_output_variables()
+        .iter()
+        .map(|variable| variable.get_name());
+    for name in output_names {
+        let is_output = node
+            .outputs
+            .iter()
+            .any(|output| output.variable == name.to_str());
+        let is_drawn_input = node
+            .inputs
+            .iter()
+            .any(|input| is_grid_source(&input.source) && input.variable == name.to_str());
+        if !is_output && !is_drawn_input {
+            return Err(DefinitionError::new(
+                node.position,
+                DefinitionErrorKind::UnlinkedVariable(name.to_string()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl FlowDefinition {
+    /// Creates the *AbstractGridGroupBuilder* with the grids of this definition.
+    /// The color grids and depth stencil grids are added in the order in which
+    /// they were defined.
+    pub fn create_grid_group_builder(&self) -> AbstractGridGroupBuilder {
+        AbstractGridGroupBuilder {
+            color_grids: self
+                .color_grids
+                .iter()
+                .map(|grid| AbstractColorGridBuilder {
+                    start_operation: grid.start_operation,
+                    purpose: grid.purpose,
+                    format: grid.format,
+                    sample_count: grid.sample_count,
+                    size: grid.size,
+                })
+                .collect(),
+            depth_stencil_grids: self
+                .depth_stencil_grids
+                .iter()
+                .map(|grid| AbstractDepthStencilGridBuilder {
+                    start_operation: grid.start_operation,
+                    purpose: grid.purpose,
+                    sample_count: grid.sample_count,
+                    size: grid.size,
+                })
+                .collect(),
+        }
+    }
+
+    /// Creates the *RenderFlowBuilder* of this definition for the given
+    /// *group*, which must have been created from the result of
+    /// *create_grid_group_builder*. The *ids* must be the *GridGroupIDs* that
+    /// were returned together with the *group*.
+    ///
+    /// The pipelines are created with the *create_graphics_pipeline* method of
+    /// the *group*, using the shader pairs of the *registry*. All references to
+    /// grids, grid nodes, pipelines, and shader pairs are resolved, and the
+    /// result is checked with the *validate_grid_nodes*,
+    /// *validate_sample_counts*, *validate_grid_inputs*, and
+    /// *validate_color_formats* methods of *RenderFlowBuilder*. Furthermore,
+    /// every input and output must be linked to a shader variable of the right
+    /// kind, and every external variable and output variable of the pipeline
+    /// must be linked to an input or output (except for the vertex attributes,
+    /// which are not checked). Every error
+    /// has the position of the statement that caused it. If the *ids* have a
+    /// different number of grids than this definition, a *GridCountMismatch*
+    /// error is returned.
+    pub fn create_render_flow_builder(
+        &self,
+        group: &dyn AbstractGridGroup,
+        ids: &GridGroupIDs,
+        registry: &ShaderPairRegistry,
+    ) -> Result<RenderFlowBuilder, DefinitionError> {
+        self.check_grid_count(ids)?;
+
+        let color_grids = self
+            .color_grids
+            .iter()
+            .zip(&ids.colors)
+            .map(|(grid, id)| (grid.name.as_str(), id.get_abstract()));
+        let depth_stencil_grids = self
+            .depth_stencil_grids
+            .iter()
+            .zip(&ids.depth_stencils)
+            .map(|(grid, id)| (grid.name.as_str(), id.get_abstract()));
+        let grids: Vec<_> = color_grids.chain(depth_stencil_grids).collect();
+        let find_grid = |name: &str, position| {
+            find(&grids, name, position, DefinitionErrorKind::UnknownGrid).copied()
+        };
+
+        let mut pipelines = Vec::with_capacity(self.pipelines.len());
+        for pipeline in &self.pipelines {
+            let shaders = registry.get(&pipeline.shaders).ok_or_else(|| {
+                DefinitionError::new(
+                    pipeline.position,
+                    DefinitionErrorKind::UnknownShaders(pipeline.shaders.clone()),
+                )
+            })?;
+            let created = group.create_graphics_pipeline(
+                shaders,
+                pipeline.topology,
+                &pipeline.create_state_builder(),
+            );
+            pipelines.push((pipeline.name.as_str(), created));
+        }
+
+        let mut builder = group.create_render_flow_builder();
+        let mut grid_nodes = Vec::with_capacity(self.grid_nodes.len());
+        for node in &self.grid_nodes {
+            let grid = find_grid(&node.grid, node.position)?;
+            grid_nodes.push((node.name.as_str(), builder.add_grid_node(grid)));
+        }
+        let find_node = |name: &str, position| {
+            find(
+                &grid_nodes,
+                name,
+                position,
+                DefinitionErrorKind::UnknownGridNode,
+            )
+            .copied()
+        };
+        let resolve_destination = |destination: &DestinationDefinition, position| {
+            Ok(match destination {
+                DestinationDefinition::Internal(node) => {
+                    DrawingNodeOutputDestination::Internal(find_node(node, position)?)
+                }
+                DestinationDefinition::External(grid) => {
+                    DrawingNodeOutputDestination::External(find_grid(grid, position)?)
+                }
+            })
+        };
+
+        for node in &self.drawing_nodes {
+            let pipeline = find(
+                &pipelines,
+                &node.pipeline,
+                node.position,
+                DefinitionErrorKind::UnknownPipeline,
+            )?;
+
+            let mut inputs = Vec::with_capacity(node.inputs.len());
+            for input in &node.inputs {
+                let position = input.position;
+                let source = match &input.source {
+                    InputSourceDefinition::Cleared(grid) => {
+                        DrawingNodeInputSource::Cleared(find_grid(grid, position)?)
+                    }
+                    InputSourceDefinition::Internal(node) => {
+                        DrawingNodeInputSource::Internal(find_node(node, position)?)
+                    }
+                    InputSourceDefinition::External(grid) => {
+                        DrawingNodeInputSource::External(find_grid(grid, position)?)
+                    }
+                    InputSourceDefinition::Sampled(grid) => DrawingNodeInputSource::SampledGrid(
+                        find_grid(grid, position)?,
+                        SamplerState::new(),
+                    ),
+                    InputSourceDefinition::Model => DrawingNodeInputSource::Model,
+                    InputSourceDefinition::Texture => DrawingNodeInputSource::Texture,
+                    InputSourceDefinition::Uniform => DrawingNodeInputSource::Uniform,
+                    InputSourceDefinition::StorageBuffer => DrawingNodeInputSource::StorageBuffer,
+                    InputSourceDefinition::PushConstant => DrawingNodeInputSource::PushConstant,
+                };
+                inputs.push(DrawingNodeInput {
+                    source,
+                    shader_variable_name: string_ref(input.variable.clone()),
+                });
+            }
+
+            let mut outputs = Vec::with_capacity(node.outputs.len());
+            for output in &node.outputs {
+                outputs.push(DrawingNodeOutput {
+                    destination: resolve_destination(&output.destination, output.position)?,
+                    shader_variable_name: string_ref(output.variable.clone()),
+                });
+            }
+
+            check_variables(pipeline.as_ref(), node)?;
+            builder.add_drawing_node(DrawingNodeBuilder {
+                pipeline: Arc::clone(pipeline),
+                inputs,
+                outputs,
+                viewport: None,
+                scissor: None,
+            });
+        }
+
+        for resolve in &self.resolves {
+            builder.add_resolve(ResolveBuilder {
+                source: find_node(&resolve.source, resolve.position)?,
+                destination: resolve_destination(&resolve.destination, resolve.position)?,
+            });
+        }
+
+        // The nodes are validated one by one (in the same order as the
+        // validation methods of the builder), so every error can be attributed
+        // to the statement of the node that caused it.
+        let error_at = |position, kind| DefinitionError::new(position, kind);
+        let (passes, _) = builder.order_passes();
+        let check_grid_nodes = |pass, position| {
+            builder
+                .validate_pass_grid_nodes(pass, &passes)
+                .map_err(|error| error_at(position, DefinitionErrorKind::GridNode(error)))
+        };
+        for (index, (node, definition)) in builder
+            .get_drawing_nodes()
+            .iter()
+            .zip(&self.drawing_nodes)
+            .enumerate()
+        {
+            let position = definition.position;
+            check_grid_nodes(FlowPass::Drawing(index), position)?;
+            builder
+                .validate_drawing_node_sample_counts(node, group)
+                .map_err(|error| error_at(position, DefinitionErrorKind::SampleCount(error)))?;
+            builder
+                .validate_drawing_node_grid_inputs(node, group)
+                .map_err(|error| error_at(position, DefinitionErrorKind::GridInput(error)))?;
+            builder
+                .validate_drawing_node_color_formats(node, group)
+                .map_err(|error| error_at(position, DefinitionErrorKind::ColorOutput(error)))?;
+        }
+        for (index, (resolve, definition)) in builder
+            .get_resolves()
+            .iter()
+            .zip(&self.resolves)
+            .enumerate()
+        {
+            check_grid_nodes(FlowPass::Resolve(index), definition.position)?;
+            builder
+                .validate_resolve_sample_counts(resolve, group)
+                .map_err(|error| {
+                    error_at(definition.position, DefinitionErrorKind::SampleCount(error))
+                })?;
+        }
+
+        Ok(builder)
+    }
+
+    fn check_grid_count(&self, ids: &GridGroupIDs) -> Result<(), DefinitionError> {
+        let color_positions: Vec<_> = self.color_grids.iter().map(|grid| grid.position).collect();
+        let depth_stencil_positions: Vec<_> = self
+            .depth_stencil_grids
+            .iter()
+            .map(|grid| grid.position)
+            .collect();
+        for (positions, num_ids) in [
+            (color_positions, ids.colors.len()),
+            (depth_stencil_positions, ids.depth_stencils.len()),
+        ] {
+            if positions.len() != num_ids {
+                // Point to the first grid without an id, or to the start of the
+                // definition when there are more ids than grids
+                let position = positions
+                    .get(num_ids)
+                    .copied()
+                    .unwrap_or(FilePosition { line: 1, column: 1 });
+                let kind = DefinitionErrorKind::GridCountMismatch {
+                    num_grids: positions.len(),
+                    num_ids,
+                };
+                return Err(DefinitionError::new(position, kind));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    fn create_registry() -> ShaderPairRegistry {
+        let vec4 = DataType::new(FLOAT, VEC4);
+        let color_output =
+            fragment_variable("outColor", vec4, FragmentShaderVariableType::ColorOutput);
+        let create_pair = |variables| {
+            let vertex_shader: Arc<dyn VertexShader> = Arc::new(DummyVertexShader {
+                variables: Vec::new(),
+            });
+            let fragment_shader: Arc<dyn FragmentShader> =
+                Arc::new(DummyFragmentShader { variables });
+            Arc::new(ShaderPair::link_by_attribute_names(&vertex_shader, &fragment_shader).unwrap())
+        };
+
+        let mut registry = ShaderPairRegistry::new();
+        let basic = create_pair(vec![
+            color_output.clone(),
+            fragment_variable(
+                "depth",
+                DataType::new(FLOAT, SINGLE),
+                FragmentShaderVariableType::DepthStencilOutput,
+            ),
+        ]);
+        let blur = create_pair(vec![
+            color_output,
+            fragment_variable(
+                "source",
+                vec4,
+                FragmentShaderVariableType::External(ExternalShaderVariableType::TextureInput),
+            ),
+        ]);
+        assert!(registry.register("basic", basic).is_none());
+        assert!(registry.register("blur", blur).is_none());
+        registry
+    }
+
+    fn load(source: &str) -> Result<RenderFlowBuilder, DefinitionError> {
+        let definition = FlowDefinition::parse(source).unwrap();
+        let group = DummyGroup::new(definition.create_grid_group_builder());
+        definition.create_render_flow_builder(&group, &group.ids, &create_registry())
+    }
+
+    const GRIDS: &str = "
+color_grid scene format=RGBA16Float start=Clear purpose=Nothing samples=4
+color_grid resolved format=RGBA16Float start=DontCare purpose=ShaderRead
+color_grid display format=RGBA8Srgb start=DontCare purpose=Display
+depth_stencil_grid depth start=Clear purpose=Nothing samples=4
+pipeline opaque shaders=basic topology=Triangles samples=4
+pipeline blur shaders=blur topology=TriangleStrips depth=Disabled
+";
+
+    #[test]
+    fn test_load() {
+        let source = format!(
+            "{}{}",
+            GRIDS,
+            "grid_node scene_node scene
+draw opaque
+    input depth cleared depth
+    output outColor internal scene_node
+resolve scene_node external resolved
+draw blur
+    input source sampled resolved
+    output outColor external display
+"
+        );
+        let builder = load(&source).unwrap();
+        assert_eq!(1, builder.get_grid_nodes().len());
+        assert_eq!(1, builder.get_resolves().len());
+
+        let drawing_nodes = builder.get_drawing_nodes();
+        assert_eq!(2, drawing_nodes.len());
+        assert_eq!(
+            SampleCount::Four,
+            drawing_nodes[0].pipeline.get_state().get_sample_count()
+        );
+        assert_eq!(
+            PrimitiveTopology::TriangleStrips,
+            drawing_nodes[1].pipeline.get_topology()
+        );
+        assert_eq!(
+            "source",
+            drawing_nodes[1].inputs[0].shader_variable_name.to_str()
+        );
+        assert!(matches!(
+            drawing_nodes[1].inputs[0].source,
+            DrawingNodeInputSource::SampledGrid(..)
+        ));
+    }
+
+    fn load_error(statements: &str) -> DefinitionError {
+        load(&format!("{}{}", GRIDS, statements)).err().unwrap()
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let error = load_error("draw opaque\n    output outColor external sceen");
+        assert_eq!(FilePosition { line: 9, column: 5 }, error.position);
+        assert!(matches!(
+            error.kind,
+            DefinitionErrorKind::UnknownGrid(ref name) if name == "sceen"
+        ));
+
+        assert!(matches!(
+            load_error("draw opaque\n    output outColor internal nowhere").kind,
+            DefinitionErrorKind::UnknownGridNode(_)
+        ));
+        assert!(matches!(
+            load_error("draw transparent").kind,
+            DefinitionErrorKind::UnknownPipeline(_)
+        ));
+        assert!(matches!(
+            load_error("pipeline bloom shaders=bloom topology=Triangles").kind,
+            DefinitionErrorKind::UnknownShaders(_)
+        ));
+
+        // The blur pipeline has 1 sample per pixel, but the scene grid has 4
+        let error = load_error(
+            "draw opaque
+    input depth cleared depth
+    output outColor external scene
+draw blur
+    input source sampled resolved
+    output outColor external scene",
+        );
+        assert_eq!(
+            FilePosition {
+                line: 11,
+                column: 1
+            },
+            error.position
+        );
+        assert!(matches!(
+            error.kind,
+            DefinitionErrorKind::SampleCount(SampleCountError::PipelineMismatch { .. })
+        ));
+
+        // The display grid doesn't have the ShaderRead purpose
+        assert!(matches!(
+            load_error("draw blur\n    input source sampled display\n    output outColor external resolved").kind,
+            DefinitionErrorKind::GridInput(GridInputError::NotShaderRead { .. })
+        ));
+        assert!(matches!(
+            load_error(
+                "draw opaque\n    input depth cleared depth\n    output outColor external depth"
+            )
+            .kind,
+            DefinitionErrorKind::ColorOutput(ColorOutputError::GridKindMismatch { .. })
+        ));
+        assert!(matches!(
+            load_error("grid_node n resolved\nresolve n external display").kind,
+            DefinitionErrorKind::SampleCount(SampleCountError::SourceNotMultisampled { .. })
+        ));
+    }
+
+    #[test]
+    fn test_variable_errors() {
+        let error = load_error(
+            "draw blur
+    input source sampled resolved
+    input tint uniform
+    output outColor external display",
+        );
+        assert_eq!(
+            FilePosition {
+                line: 10,
+                column: 5
+            },
+            error.position
+        );
+        assert!(matches!(
+            error.kind,
+            DefinitionErrorKind::UnknownVariable(ref name) if name == "tint"
+        ));
+
+        let error =
+            load_error("draw blur\n    input source uniform\n    output outColor external display");
+        assert_eq!(FilePosition { line: 9, column: 5 }, error.position);
+        assert!(matches!(
+            error.kind,
+            DefinitionErrorKind::WrongVariableKind(ref name) if name == "source"
+        ));
+
+        let error = load_error(
+            "draw blur\n    input source sampled resolved\n    output source external display",
+        );
+        assert_eq!(
+            FilePosition {
+                line: 10,
+                column: 5
+            },
+            error.position
+        );
+        assert!(matches!(
+            error.kind,
+            DefinitionErrorKind::WrongVariableKind(_)
+        ));
+
+        let error = load_error("draw blur\n    output outColor external display");
+        assert_eq!(FilePosition { line: 8, column: 1 }, error.position);
+        assert!(matches!(
+            error.kind,
+            DefinitionErrorKind::UnlinkedVariable(ref name) if name == "source"
+        ));
+
+        // The depth output of the opaque pipeline is not linked
+        assert!(matches!(
+            load_error("draw opaque\n    output outColor external scene").kind,
+            DefinitionErrorKind::UnlinkedVariable(ref name) if name == "depth"
+        ));
+    }
+
+    #[test]
+    fn test_grid_node_errors() {
+        let draw = |input: &str, output: &str| {
+            format!(
+                "draw opaque\n    input depth cleared depth\n{}    output outColor internal {}\n",
+                input, output
+            )
+        };
+
+        let source = format!("grid_node n scene\n{}{}", draw("", "n"), draw("", "n"));
+        let error = load_error(&source);
+        assert_eq!(
+            FilePosition {
+                line: 12,
+                column: 1
+            },
+            error.position
+        );
+        assert!(matches!(
+            error.kind,
+            DefinitionErrorKind::GridNode(GridNodeError::MultipleWriters { .. })
+        ));
+
+        let source = format!(
+            "grid_node a scene\ngrid_node b scene\n{}{}",
+            draw("    input outColor internal a\n", "b"),
+            draw("    input outColor internal b\n", "a")
+        );
+        let error = load_error(&source);
+        assert_eq!(
+            FilePosition {
+                line: 10,
+                column: 1
+            },
+            error.position
+        );
+        assert!(matches!(
+            error.kind,
+            DefinitionErrorKind::GridNode(GridNodeError::Cycle { .. })
+        ));
+    }
+
+    #[test]
+    fn test_grid_count_mismatch() {
+        let definition = FlowDefinition::parse(GRIDS).unwrap();
+        let mut builder = definition.create_grid_group_builder();
+        builder.color_grids.pop();
+        let group = DummyGroup::new(builder);
+        let error = definition
+            .create_render_flow_builder(&group, &group.ids, &create_registry())
+            .err()
+            .unwrap();
+        assert_eq!(FilePosition { line: 4, column: 1 }, error.position);
+        assert!(matches!(
+            error.kind,
+            DefinitionErrorKind::GridCountMismatch {
+                num_grids: 3,
+                num_ids: 2
+            }
+        ));
+    }
+}
@@ -0,0 +1,265 @@
+mod error;
+mod load;
+mod parse;
+
+pub use error::*;
+pub use load::*;
+
+use crate::*;
+
+use std::fmt::{Display, Formatter};
+
+/// A serializable description of an *AbstractGridGroupBuilder* and a
+/// *RenderFlowBuilder*, which refers to grids, grid nodes, pipelines, and
+/// shaders by name. This makes it possible to change the structure of a
+/// *RenderFlow* without recompiling the application: the definition can be
+/// stored in a text file that is loaded at runtime.
+///
+/// Use *FlowDefinition::parse* to read a definition from its text format, and
+/// the *Display* implementation to write it back. Use
+/// *create_grid_group_builder* and *create_render_flow_builder* to turn it into
+/// builders. See the documentation of *FlowDefinition::parse* for the text
+/// format.
+///
+/// Definitions can describe drawing nodes, grid nodes, and resolve operations.
+/// Compute nodes and buffer nodes must still be added in code.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FlowDefinition {
+    pub color_grids: Vec<ColorGridDefinition>,
+    pub depth_stencil_grids: Vec<DepthStencilGridDefinition>,
+    pub pipelines: Vec<PipelineDefinition>,
+    pub grid_nodes: Vec<GridNodeDefinition>,
+    pub drawing_nodes: Vec<DrawingNodeDefinition>,
+    pub resolves: Vec<ResolveDefinition>,
+}
+
+/// A position in the text of a *FlowDefinition*. Both the line and the column
+/// start counting at 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FilePosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for FilePosition {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// The definition of an *AbstractColorGridBuilder*, which can be referred to by
+/// its *name*.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorGridDefinition {
+    pub name: String,
+    pub start_operation: ColorStartOperation,
+    pub purpose: ColorPurpose,
+    pub format: ColorFormat,
+    pub sample_count: SampleCount,
+    pub size: GridSize,
+    pub position: FilePosition,
+}
+
+/// The definition of an *AbstractDepthStencilGridBuilder*, which can be
+/// referred to by its *name*.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepthStencilGridDefinition {
+    pub name: String,
+    pub start_operation: DepthStencilStartOperation,
+    pub purpose: DepthStencilPurpose,
+    pub sample_count: SampleCount,
+    pub size: GridSize,
+    pub position: FilePosition,
+}
+
+/// The definition of a *GraphicsPipeline*, which can be referred to by its
+/// *name*. The *shaders* is the name of a *ShaderPair* in the
+/// *ShaderPairRegistry* that is used to load the definition. The properties of
+/// the *PipelineStateBuilder* that are not listed here keep their default
+/// values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipelineDefinition {
+    pub name: String,
+    pub shaders: String,
+    pub topology: PrimitiveTopology,
+    pub sample_count: SampleCount,
+    pub cull_mode: CullMode,
+    pub depth_compare_op: Option<CompareOp>,
+    pub depth_write: bool,
+    pub position: FilePosition,
+}
+
+impl PipelineDefinition {
+    /// Creates the *PipelineStateBuilder* for this pipeline.
+    pub fn create_state_builder(&self) -> PipelineStateBuilder {
+        let mut state = PipelineStateBuilder::new();
+        state.sample_count = self.sample_count;
+        state.cull_mode = self.cull_mode;
+        state.depth_compare_op = self.depth_compare_op;
+        state.depth_write = self.depth_write;
+        state
+    }
+}
+
+/// The definition of a grid node of the color grid or depth stencil grid with
+/// the name *grid*. The grid node can be referred to by its own *name*.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridNodeDefinition {
+    pub name: String,
+    pub grid: String,
+    pub position: FilePosition,
+}
+
+/// The definition of a drawing node that uses the pipeline with the name
+/// *pipeline*.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawingNodeDefinition {
+    pub pipeline: String,
+    pub inputs: Vec<InputDefinition>,
+    pub outputs: Vec<OutputDefinition>,
+    pub position: FilePosition,
+}
+
+/// The definition of a *DrawingNodeInput*.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputDefinition {
+    pub variable: String,
+    pub source: InputSourceDefinition,
+    pub position: FilePosition,
+}
+
+/// The definition of a *DrawingNodeInputSource*, where grids and grid nodes are
+/// referred to by name. *Sampled* grids use the default *SamplerState*.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputSourceDefinition {
+    Cleared(String),
+    Internal(String),
+    External(String),
+    Sampled(String),
+    Model,
+    Texture,
+    Uniform,
+    StorageBuffer,
+    PushConstant,
+}
+
+/// The definition of a *DrawingNodeOutput*.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputDefinition {
+    pub variable: String,
+    pub destination: DestinationDefinition,
+    pub position: FilePosition,
+}
+
+/// The definition of a *DrawingNodeOutputDestination*, where grids and grid
+/// nodes are referred to by name.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DestinationDefinition {
+    Internal(String),
+    External(String),
+}
+
+/// The definition of a *ResolveBuilder* whose source is the grid node with the
+/// name *source*.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolveDefinition {
+    pub source: String,
+    pub destination: DestinationDefinition,
+    pub position: FilePosition,
+}
+
+fn write_size(f: &mut Formatter, size: GridSize) -> Result<(), std::fmt::Error> {
+    match size {
+        GridSize::Relative { scale } => write!(f, "size={}", scale),
+        GridSize::Absolute { width, height } => write!(f, "size={}x{}", width, height),
+    }
+}
+
+impl Display for DestinationDefinition {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::Internal(node) => write!(f, "internal {}", node),
+            Self::External(grid) => write!(f, "external {}", grid),
+        }
+    }
+}
+
+impl Display for InputSourceDefinition {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::Cleared(grid) => write!(f, "cleared {}", grid),
+            Self::Internal(node) => write!(f, "internal {}", node),
+            Self::External(grid) => write!(f, "external {}", grid),
+            Self::Sampled(grid) => write!(f, "sampled {}", grid),
+            Self::Model => write!(f, "model"),
+            Self::Texture => write!(f, "texture"),
+            Self::Uniform => write!(f, "uniform"),
+            Self::StorageBuffer => write!(f, "storage_buffer"),
+            Self::PushConstant => write!(f, "push_constant"),
+        }
+    }
+}
+
+/// Writes the definition in the text format that is accepted by
+/// *FlowDefinition::parse*. The positions are not written.
+impl Display for FlowDefinition {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        for grid in &self.color_grids {
+            write!(
+                f,
+                "color_grid {} format={:?} start={:?} purpose={:?} samples={} ",
+                grid.name,
+                grid.format,
+                grid.start_operation,
+                grid.purpose,
+                grid.sample_count.get_num_samples()
+            )?;
+            write_size(f, grid.size)?;
+            writeln!(f)?;
+        }
+        for grid in &self.depth_stencil_grids {
+            write!(
+                f,
+                "depth_stencil_grid {} start={:?} purpose={:?} samples={} ",
+                grid.name,
+                grid.start_operation,
+                grid.purpose,
+                grid.sample_count.get_num_samples()
+            )?;
+            write_size(f, grid.size)?;
+            writeln!(f)?;
+        }
+        for pipeline in &self.pipelines {
+            write!(
+                f,
+                "pipeline {} shaders={} topology={:?} samples={} cull={:?} depth=",
+                pipeline.name,
+                pipeline.shaders,
+                pipeline.topology,
+                pipeline.sample_count.get_num_samples(),
+                pipeline.cull_mode
+            )?;
+            match pipeline.depth_compare_op {
+                Some(compare_op) => write!(f, "{:?}", compare_op)?,
+                None => write!(f, "Disabled")?,
+            };
+            writeln!(f, " depth_write={}", pipeline.depth_write)?;
+        }
+        for node in &self.grid_nodes {
+            writeln!(f, "grid_node {} {}", node.name, node.grid)?;
+        }
+        for node in &self.drawing_nodes {
+            writeln!(f, "draw {}", node.pipeline)?;
+            for input in &node.inputs {
+                writeln!(f, "    input {} {}", input.variable, input.source)?;
+            }
+            for output in &node.outputs {
+                writeln!(f, "    output {} {}", output.variable, output.destination)?;
+            }
+        }
+        for resolve in &self.resolves {
+            writeln!(f, "resolve {} {}", resolve.source, resolve.destination)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,704 @@
+use crate::*;
+
+#[derive(Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    position: FilePosition,
+}
+
+fn tokenize(line: &str, line_number: usize) -> Vec<Token<'_>> {
+    let line = match line.find('#') {
+        Some(comment_start) => &line[..comment_start],
+        None => line,
+    };
+
+    let mut tokens = Vec::new();
+    let mut token_start = None;
+    let mut column = 0;
+    for (index, character) in line.char_indices() {
+        column += 1;
+        if character.is_whitespace() {
+            if let Some((start_index, start_column)) = token_start.take() {
+                tokens.push(Token {
+                    text: &line[start_index..index],
+                    position: FilePosition {
+                        line: line_number,
+                        column: start_column,
+                    },
+                });
+            }
+        } else if token_start.is_none() {
+            token_start = Some((index, column));
+        }
+    }
+    if let Some((start_index, start_column)) = token_start {
+        tokens.push(Token {
+            text: &line[start_index..],
+            position: FilePosition {
+                line: line_number,
+                column: start_column,
+            },
+        });
+    }
+    tokens
+}
+
+/// The arguments of a single statement: the positional arguments come first,
+/// followed by the *key=value* properties.
+struct Arguments<'a> {
+    statement: Token<'a>,
+    positional: Vec<Token<'a>>,
+    properties: Vec<(Token<'a>, Token<'a>)>,
+}
+
+impl<'a> Arguments<'a> {
+    fn new(statement: Token<'a>, tokens: &[Token<'a>]) -> Result<Self, DefinitionError> {
+        let mut positional = Vec::new();
+        let mut properties: Vec<(Token, Token)> = Vec::new();
+        for token in tokens {
+            match token.text.find('=') {
+                Some(split_index) => {
+                    let key = Token {
+                        text: &token.text[..split_index],
+                        position: token.position,
+                    };
+                    let value = Token {
+                        text: &token.text[split_index + 1..],
+                        position: FilePosition {
+                            line: token.position.line,
+                            column: token.position.column + key.text.chars().count() + 1,
+                        },
+                    };
+                    if properties.iter().any(|(other, _)| other.text == key.text) {
+                        return Err(DefinitionError::new(
+                            key.position,
+                            DefinitionErrorKind::DuplicateProperty(key.text.to_string()),
+                        ));
+                    }
+                    properties.push((key, value));
+                }
+                None => {
+                    if !properties.is_empty() {
+                        return Err(unexpected(*token));
+                    }
+                    positional.push(*token);
+                }
+            }
+        }
+        Ok(Self {
+            statement,
+            positional,
+            properties,
+        })
+    }
+
+    fn next(&mut self, name: &'static str) -> Result<Token<'a>, DefinitionError> {
+        if self.positional.is_empty() {
+            Err(DefinitionError::new(
+                self.statement.position,
+                DefinitionErrorKind::MissingArgument(name),
+            ))
+        } else {
+            Ok(self.positional.remove(0))
+        }
+    }
+
+    fn next_name(&mut self, name: &'static str) -> Result<String, DefinitionError> {
+        Ok(self.next(name)?.text.to_string())
+    }
+
+    fn take(&mut self, key: &str) -> Option<Token<'a>> {
+        let index = self
+            .properties
+            .iter()
+            .position(|(other, _)| other.text == key)?;
+        Some(self.properties.remove(index).1)
+    }
+
+    fn require(&mut self, key: &'static str) -> Result<Token<'a>, DefinitionError> {
+        self.take(key).ok_or_else(|| {
+            DefinitionError::new(
+                self.statement.position,
+                DefinitionErrorKind::MissingArgument(key),
+            )
+        })
+    }
+
+    fn finish(self) -> Result<(), DefinitionError> {
+        if let Some(token) = self.positional.first() {
+            return Err(unexpected(*token));
+        }
+        if let Some((key, _)) = self.properties.first() {
+            return Err(unexpected(*key));
+        }
+        Ok(())
+    }
+}
+
+fn unexpected(token: Token) -> DefinitionError {
+    DefinitionError::new(
+        token.position,
+        DefinitionErrorKind::UnexpectedArgument(token.text.to_string()),
+    )
+}
+
+fn invalid(property: &str, value: Token) -> DefinitionError {
+    DefinitionError::new(
+        value.position,
+        DefinitionErrorKind::InvalidValue {
+            property: property.to_string(),
+            value: value.text.to_string(),
+        },
+    )
+}
+
+fn parse_option<T: Copy>(
+    property: &str,
+    value: Token,
+    options: &[(&str, T)],
+) -> Result<T, DefinitionError> {
+    options
+        .iter()
+        .find(|(name, _)| *name == value.text)
+        .map(|(_, option)| *option)
+        .ok_or_else(|| invalid(property, value))
+}
+
+const COLOR_FORMATS: [(&str, ColorFormat); 16] = [
+    ("R8Unorm", ColorFormat::R8Unorm),
+    ("RG8Unorm", ColorFormat::RG8Unorm),
+    ("RGBA8Unorm", ColorFormat::RGBA8Unorm),
+    ("RGBA8Srgb", ColorFormat::RGBA8Srgb),
+    ("BGRA8Unorm", ColorFormat::BGRA8Unorm),
+    ("BGRA8Srgb", ColorFormat::BGRA8Srgb),
+    ("R16Float", ColorFormat::R16Float),
+    ("RG16Float", ColorFormat::RG16Float),
+    ("RGBA16Float", ColorFormat::RGBA16Float),
+    ("R32Float", ColorFormat::R32Float),
+    ("RG32Float", ColorFormat::RG32Float),
+    ("RGBA32Float", ColorFormat::RGBA32Float),
+    ("R32Uint", ColorFormat::R32Uint),
+    ("RG32Uint", ColorFormat::RG32Uint),
+    ("RGBA32Uint", ColorFormat::RGBA32Uint),
+    ("R32Sint", ColorFormat::R32Sint),
+];
+
+const COLOR_START_OPERATIONS: [(&str, ColorStartOperation); 3] = [
+    ("Preserve", ColorStartOperation::Preserve),
+    ("Clear", ColorStartOperation::Clear),
+    ("DontCare", ColorStartOperation::DontCare),
+];
+
+const COLOR_PURPOSES: [(&str, ColorPurpose); 5] = [
+    ("Nothing", ColorPurpose::Nothing),
+    ("Display", ColorPurpose::Display),
+    ("ShaderRead", ColorPurpose::ShaderRead),
+    ("Transfer", ColorPurpose::Transfer),
+    ("Replace", ColorPurpose::Replace),
+];
+
+const DEPTH_STENCIL_START_OPERATIONS: [(&str, DepthStencilStartOperation); 3] = [
+    ("Preserve", DepthStencilStartOperation::Preserve),
+    ("Clear", DepthStencilStartOperation::Clear),
+    ("DontCare", DepthStencilStartOperation::DontCare),
+];
+
+const DEPTH_STENCIL_PURPOSES: [(&str, DepthStencilPurpose); 4] = [
+    ("Nothing", DepthStencilPurpose::Nothing),
+    ("ShaderRead", DepthStencilPurpose::ShaderRead),
+    ("Transfer", DepthStencilPurpose::Transfer),
+    ("Replace", DepthStencilPurpose::Replace),
+];
+
+const SAMPLE_COUNTS: [(&str, SampleCount); 5] = [
+    ("1", SampleCount::One),
+    ("2", SampleCount::Two),
+    ("4", SampleCount::Four),
+    ("8", SampleCount::Eight),
+    ("16", SampleCount::Sixteen),
+];
+
+const TOPOLOGIES: [(&str, PrimitiveTopology); 5] = [
+    ("Points", PrimitiveTopology::Points),
+    ("Lines", PrimitiveTopology::Lines),
+    ("LineStrips", PrimitiveTopology::LineStrips),
+    ("Triangles", PrimitiveTopology::Triangles),
+    ("TriangleStrips", PrimitiveTopology::TriangleStrips),
+];
+
+const CULL_MODES: [(&str, CullMode); 4] = [
+    ("None", CullMode::None),
+    ("Front", CullMode::Front),
+    ("Back", CullMode::Back),
+    ("FrontAndBack", CullMode::FrontAndBack),
+];
+
+const DEPTH_COMPARE_OPS: [(&str, Option<CompareOp>); 9] = [
+    ("Disabled", None),
+    ("Never", Some(CompareOp::Never)),
+    ("Less", Some(CompareOp::Less)),
+    ("Equal", Some(CompareOp::Equal)),
+    ("LessOrEqual", Some(CompareOp::LessOrEqual)),
+    ("Greater", Some(CompareOp::Greater)),
+    ("NotEqual", Some(CompareOp::NotEqual)),
+    ("GreaterOrEqual", Some(CompareOp::GreaterOrEqual)),
+    ("Always", Some(CompareOp::Always)),
+];
+
+const BOOLEANS: [(&str, bool); 2] = [("true", true), ("false", false)];
+
+fn parse_sample_count(arguments: &mut Arguments) -> Result<SampleCount, DefinitionError> {
+    match arguments.take("samples") {
+        Some(value) => parse_option("samples", value, &SAMPLE_COUNTS),
+        None => Ok(SampleCount::default()),
+    }
+}
+
+fn parse_size(arguments: &mut Arguments) -> Result<GridSize, DefinitionError> {
+    let value = match arguments.take("size") {
+        Some(value) => value,
+        None => return Ok(GridSize::default()),
+    };
+
+    let size = match value.text.find('x') {
+        Some(split_index) => {
+            let width = value.text[..split_index].parse::<u32>();
+            let height = value.text[split_index + 1..].parse::<u32>();
+            match (width, height) {
+                (Ok(width), Ok(height)) if width > 0 && height > 0 => {
+                    Some(GridSize::Absolute { width, height })
+                }
+                _ => None,
+            }
+        }
+        None => match value.text.parse::<f32>() {
+            Ok(scale) if scale > 0.0 && scale.is_finite() => Some(GridSize::Relative { scale }),
+            _ => None,
+        },
+    };
+    size.ok_or_else(|| invalid("size", value))
+}
+
+fn parse_destination(arguments: &mut Arguments) -> Result<DestinationDefinition, DefinitionError> {
+    let kind = arguments.next("destination")?;
+    match kind.text {
+        "internal" => Ok(DestinationDefinition::Internal(
+            arguments.next_name("grid node")?,
+        )),
+        "external" => Ok(DestinationDefinition::External(
+            arguments.next_name("grid")?,
+        )),
+        _ => Err(invalid("destination", kind)),
+    }
+}
+
+fn parse_source(arguments: &mut Arguments) -> Result<InputSourceDefinition, DefinitionError> {
+    let kind = arguments.next("source")?;
+    match kind.text {
+        "cleared" => Ok(InputSourceDefinition::Cleared(arguments.next_name("grid")?)),
+        "internal" => Ok(InputSourceDefinition::Internal(
+            arguments.next_name("grid node")?,
+        )),
+        "external" => Ok(InputSourceDefinition::External(
+            arguments.next_name("grid")?,
+        )),
+        "sampled" => Ok(InputSourceDefinition::Sampled(arguments.next_name("grid")?)),
+        "model" => Ok(InputSourceDefinition::Model),
+        "texture" => Ok(InputSourceDefinition::Texture),
+        "uniform" => Ok(InputSourceDefinition::Uniform),
+        "storage_buffer" => Ok(InputSourceDefinition::StorageBuffer),
+        "push_constant" => Ok(InputSourceDefinition::PushConstant),
+        _ => Err(invalid("source", kind)),
+    }
+}
+
+fn claim_name<'a>(names: &mut Vec<&'a str>, token: Token<'a>) -> Result<String, DefinitionError> {
+    if names.contains(&token.text) {
+        return Err(DefinitionError::new(
+            token.position,
+            DefinitionErrorKind::DuplicateName(token.text.to_string()),
+        ));
+    }
+    names.push(token.text);
+    Ok(token.text.to_string())
+}
+
+impl FlowDefinition {
+    /// Parses a *FlowDefinition* from its text format. Every line contains a
+    /// single statement, and everything after a '#' is a comment. Statements
+    /// start with a keyword, followed by positional arguments, followed by
+    /// *key=value* properties. The statements are:
+    ///
+    /// - `color_grid <name> format=<ColorFormat> start=<ColorStartOperation>
+    ///   purpose=<ColorPurpose> [samples=<1|2|4|8|16>] [size=<scale|WxH>]`
+    /// - `depth_stencil_grid <name> start=<DepthStencilStartOperation>
+    ///   purpose=<DepthStencilPurpose> [samples=...] [size=...]`
+    /// - `pipeline <name> shaders=<shader pair> topology=<PrimitiveTopology>
+    ///   [samples=...] [cull=<CullMode>] [depth=<CompareOp|Disabled>]
+    ///   [depth_write=<true|false>]`
+    /// - `grid_node <name> <grid>`
+    /// - `draw <pipeline>`
+    /// - `input <variable> <source>`, where the source is *cleared <grid>*,
+    ///   *internal <grid node>*, *external <grid>*, *sampled <grid>*, *model*,
+    ///   *texture*, *uniform*, *storage_buffer*, or *push_constant*
+    /// - `output <variable> <destination>`, where the destination is
+    ///   *internal <grid node>* or *external <grid>*
+    /// - `resolve <grid node> <destination>`
+    ///
+    /// The enum values are written exactly like the variants of their Rust
+    /// enums. The *input* and *output* statements belong to the preceding
+    /// *draw* statement (they are typically indented, but this is not
+    /// required). The sizes are written as a relative scale (like *0.5*) or
+    /// as an absolute size (like *1024x1024*). Omitted properties get the
+    /// default values of *SampleCount*, *GridSize*, and *PipelineStateBuilder*.
+    ///
+    /// This only checks the syntax of the definition and whether names are
+    /// unique: references to other names are checked when the definition is
+    /// loaded.
+    pub fn parse(source: &str) -> Result<Self, DefinitionError> {
+        let mut definition = Self::default();
+        let mut names: Vec<&str> = Vec::new();
+
+        for (line_index, line) in source.lines().enumerate() {
+            let tokens = tokenize(line, line_index + 1);
+            let statement = match tokens.first() {
+                Some(statement) => *statement,
+                None => continue,
+            };
+            let mut arguments = Arguments::new(statement, &tokens[1..])?;
+            let position = statement.position;
+
+            match statement.text {
+                "color_grid" => {
+                    let name = claim_name(&mut names, arguments.next("name")?)?;
+                    let format = arguments.require("format")?;
+                    let start = arguments.require("start")?;
+                    let purpose = arguments.require("purpose")?;
+                    definition.color_grids.push(ColorGridDefinition {
+                        name,
+                        format: parse_option("format", format, &COLOR_FORMATS)?,
+                        start_operation: parse_option("start", start, &COLOR_START_OPERATIONS)?,
+                        purpose: parse_option("purpose", purpose, &COLOR_PURPOSES)?,
+                        sample_count: parse_sample_count(&mut arguments)?,
+                        size: parse_size(&mut arguments)?,
+                        position,
+                    });
+                }
+                "depth_stencil_grid" => {
+                    let name = claim_name(&mut names, arguments.next("name")?)?;
+                    let start = arguments.require("start")?;
+                    let purpose = arguments.require("purpose")?;
+                    definition
+                        .depth_stencil_grids
+                        .push(DepthStencilGridDefinition {
+                            name,
+                            start_operation: parse_option(
+                                "start",
+                                start,
+                                &DEPTH_STENCIL_START_OPERATIONS,
+                            )?,
+                            purpose: parse_option("purpose", purpose, &DEPTH_STENCIL_PURPOSES)?,
+                            sample_count: parse_sample_count(&mut arguments)?,
+                            size: parse_size(&mut arguments)?,
+                            position,
+                        });
+                }
+                "pipeline" => {
+                    let name = claim_name(&mut names, arguments.next("name")?)?;
+                    let shaders = arguments.require("shaders")?.text.to_string();
+                    let topology = arguments.require("topology")?;
+                    let defaults = PipelineStateBuilder::new();
+                    let mut pipeline = PipelineDefinition {
+                        name,
+                        shaders,
+                        topology: parse_option("topology", topology, &TOPOLOGIES)?,
+                        sample_count: parse_sample_count(&mut arguments)?,
+                        cull_mode: defaults.cull_mode,
+                        depth_compare_op: defaults.depth_compare_op,
+                        depth_write: defaults.depth_write,
+                        position,
+                    };
+                    if let Some(value) = arguments.take("cull") {
+                        pipeline.cull_mode = parse_option("cull", value, &CULL_MODES)?;
+                    }
+                    if let Some(value) = arguments.take("depth") {
+                        pipeline.depth_compare_op =
+                            parse_option("depth", value, &DEPTH_COMPARE_OPS)?;
+                    }
+                    if let Some(value) = arguments.take("depth_write") {
+                        pipeline.depth_write = parse_option("depth_write", value, &BOOLEANS)?;
+                    }
+                    definition.pipelines.push(pipeline);
+                }
+                "grid_node" => {
+                    let name = claim_name(&mut names, arguments.next("name")?)?;
+                    definition.grid_nodes.push(GridNodeDefinition {
+                        name,
+                        grid: arguments.next_name("grid")?,
+                        position,
+                    });
+                }
+                "draw" => {
+                    definition.drawing_nodes.push(DrawingNodeDefinition {
+                        pipeline: arguments.next_name("pipeline")?,
+                        inputs: Vec::new(),
+                        outputs: Vec::new(),
+                        position,
+                    });
+                }
+                "input" | "output" => {
+                    let node = definition.drawing_nodes.last_mut().ok_or_else(|| {
+                        DefinitionError::new(position, DefinitionErrorKind::NoDrawingNode)
+                    })?;
+                    let variable = arguments.next_name("variable")?;
+                    if statement.text == "input" {
+                        node.inputs.push(InputDefinition {
+                            variable,
+                            source: parse_source(&mut arguments)?,
+                            position,
+                        });
+                    } else {
+                        node.outputs.push(OutputDefinition {
+                            variable,
+                            destination: parse_destination(&mut arguments)?,
+                            position,
+                        });
+                    }
+                }
+                "resolve" => {
+                    definition.resolves.push(ResolveDefinition {
+                        source: arguments.next_name("grid node")?,
+                        destination: parse_destination(&mut arguments)?,
+                        position,
+                    });
+                }
+                _ => {
+                    return Err(DefinitionError::new(
+                        position,
+                        DefinitionErrorKind::UnknownStatement(statement.text.to_string()),
+                    ))
+                }
+            };
+
+            arguments.finish()?;
+        }
+
+        Ok(definition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const SOURCE: &str = "
+# The scene is drawn with 4x MSAA and resolved into a grid that is blurred
+color_grid scene format=RGBA16Float start=Clear purpose=Nothing samples=4
+color_grid resolved format=RGBA16Float start=DontCare purpose=ShaderRead
+color_grid blurred format=RGBA8Unorm start=DontCare purpose=Display size=0.5
+depth_stencil_grid depth start=Clear purpose=Nothing samples=4 size=1024x1024
+
+pipeline opaque shaders=basic topology=Triangles samples=4 cull=Back
+pipeline blur shaders=blur topology=TriangleStrips depth=Disabled depth_write=false
+
+grid_node scene_node scene
+draw opaque
+    input sceneColor cleared scene
+    input modelPosition model
+    output outColor internal scene_node
+resolve scene_node external resolved
+draw blur
+    input source sampled resolved   # Uses the default sampler
+    output outColor external blurred
+";
+
+    #[test]
+    fn test_parse() {
+        let definition = FlowDefinition::parse(SOURCE).unwrap();
+        assert_eq!(3, definition.color_grids.len());
+        assert_eq!(SampleCount::Four, definition.color_grids[0].sample_count);
+        assert_eq!(GridSize::FULL, definition.color_grids[1].size);
+        assert_eq!(GridSize::HALF, definition.color_grids[2].size);
+        assert_eq!(
+            GridSize::Absolute {
+                width: 1024,
+                height: 1024
+            },
+            definition.depth_stencil_grids[0].size
+        );
+
+        let blur = &definition.pipelines[1];
+        assert_eq!(PrimitiveTopology::TriangleStrips, blur.topology);
+        assert_eq!(None, blur.depth_compare_op);
+        assert_eq!(CullMode::Back, definition.pipelines[0].cull_mode);
+
+        assert_eq!(2, definition.drawing_nodes.len());
+        let opaque = &definition.drawing_nodes[0];
+        assert_eq!(
+            FilePosition {
+                line: 12,
+                column: 1
+            },
+            opaque.position
+        );
+        assert_eq!(
+            InputSourceDefinition::Cleared("scene".to_string()),
+            opaque.inputs[0].source
+        );
+        assert_eq!(InputSourceDefinition::Model, opaque.inputs[1].source);
+        assert_eq!(
+            FilePosition {
+                line: 15,
+                column: 5
+            },
+            opaque.outputs[0].position
+        );
+        assert_eq!(
+            DestinationDefinition::External("resolved".to_string()),
+            definition.resolves[0].destination
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let definition = FlowDefinition::parse(SOURCE).unwrap();
+        let text = definition.to_string();
+        let parsed = FlowDefinition::parse(&text).unwrap();
+        assert_eq!(text, parsed.to_string());
+        assert_eq!(definition.color_grids[2].size, parsed.color_grids[2].size);
+        assert_eq!(
+            definition.pipelines[1].depth_write,
+            parsed.pipelines[1].depth_write
+        );
+        assert_eq!(
+            definition.drawing_nodes[1].inputs[0].source,
+            parsed.drawing_nodes[1].inputs[0].source
+        );
+    }
+
+    fn round_trip(source: &str) -> FlowDefinition {
+        let definition = FlowDefinition::parse(source).unwrap();
+        FlowDefinition::parse(&definition.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_enum_values() {
+        for (name, format) in &COLOR_FORMATS {
+            let source = format!("color_grid g format={} start=Clear purpose=Nothing", name);
+            assert_eq!(*format, round_trip(&source).color_grids[0].format);
+        }
+        for (name, start) in &COLOR_START_OPERATIONS {
+            let source = format!("color_grid g format=R8Unorm start={} purpose=Nothing", name);
+            assert_eq!(*start, round_trip(&source).color_grids[0].start_operation);
+        }
+        for (name, purpose) in &COLOR_PURPOSES {
+            let source = format!("color_grid g format=R8Unorm start=Clear purpose={}", name);
+            assert_eq!(*purpose, round_trip(&source).color_grids[0].purpose);
+        }
+        for (name, start) in &DEPTH_STENCIL_START_OPERATIONS {
+            let source = format!("depth_stencil_grid d start={} purpose=Nothing", name);
+            let definition = round_trip(&source);
+            assert_eq!(*start, definition.depth_stencil_grids[0].start_operation);
+        }
+        for (name, purpose) in &DEPTH_STENCIL_PURPOSES {
+            let source = format!("depth_stencil_grid d start=Clear purpose={}", name);
+            assert_eq!(*purpose, round_trip(&source).depth_stencil_grids[0].purpose);
+        }
+        for (name, sample_count) in &SAMPLE_COUNTS {
+            let source = format!(
+                "depth_stencil_grid d start=Clear purpose=Nothing samples={}",
+                name
+            );
+            let definition = round_trip(&source);
+            assert_eq!(
+                *sample_count,
+                definition.depth_stencil_grids[0].sample_count
+            );
+        }
+
+        let pipeline = |properties: String| {
+            round_trip(&format!("pipeline p shaders=s {}", properties)).pipelines[0].clone()
+        };
+        for (name, topology) in &TOPOLOGIES {
+            assert_eq!(*topology, pipeline(format!("topology={}", name)).topology);
+        }
+        for (name, cull_mode) in &CULL_MODES {
+            let properties = format!("topology=Points cull={}", name);
+            assert_eq!(*cull_mode, pipeline(properties).cull_mode);
+        }
+        for (name, compare_op) in &DEPTH_COMPARE_OPS {
+            let properties = format!("topology=Points depth={}", name);
+            assert_eq!(*compare_op, pipeline(properties).depth_compare_op);
+        }
+        for (name, depth_write) in &BOOLEANS {
+            let properties = format!("topology=Points depth_write={}", name);
+            assert_eq!(*depth_write, pipeline(properties).depth_write);
+        }
+    }
+
+    fn parse_error(source: &str) -> DefinitionError {
+        FlowDefinition::parse(source).unwrap_err()
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = parse_error("color_grid a format=RGBA8Unorm start=Clear purpose=Sometimes");
+        assert_eq!(
+            FilePosition {
+                line: 1,
+                column: 52
+            },
+            error.position
+        );
+        assert!(matches!(
+            error.kind,
+            DefinitionErrorKind::InvalidValue { ref property, ref value }
+                if property == "purpose" && value == "Sometimes"
+        ));
+
+        let error = parse_error("\n  color_grid a start=Clear purpose=Nothing");
+        assert_eq!(FilePosition { line: 2, column: 3 }, error.position);
+        assert!(matches!(
+            error.kind,
+            DefinitionErrorKind::MissingArgument("format")
+        ));
+
+        let error = parse_error("grid_node a b c");
+        assert!(matches!(
+            error.kind,
+            DefinitionErrorKind::UnexpectedArgument(ref argument) if argument == "c"
+        ));
+
+        let error = parse_error("depth_stencil_grid a start=Clear purpose=Nothing\ngrid_node a a");
+        assert_eq!(
+            FilePosition {
+                line: 2,
+                column: 11
+            },
+            error.position
+        );
+        assert!(matches!(error.kind, DefinitionErrorKind::DuplicateName(_)));
+
+        assert!(matches!(
+            parse_error("input color model").kind,
+            DefinitionErrorKind::NoDrawingNode
+        ));
+        assert!(matches!(
+            parse_error("draw a\n    input color sampled").kind,
+            DefinitionErrorKind::MissingArgument("grid")
+        ));
+        assert!(matches!(
+            parse_error("pipeline p shaders=a shaders=b").kind,
+            DefinitionErrorKind::DuplicateProperty(_)
+        ));
+        assert!(matches!(
+            parse_error("depth_stencil_grid d start=Clear purpose=Nothing size=0x5").kind,
+            DefinitionErrorKind::InvalidValue { .. }
+        ));
+        assert!(matches!(
+            parse_error("compute c").kind,
+            DefinitionErrorKind::UnknownStatement(_)
+        ));
+    }
+}
@@ -25,6 +25,20 @@ use super::GridNodeBuilder;
 /// node. For each of these output grids, the node needs an outgoing link to a
 /// grid node.
 ///
+/// Besides drawing nodes and grid nodes, a *RenderFlow* can also have compute
+/// nodes and buffer nodes. Compute nodes run a *ComputeShader* (see the
+/// documentation of *ComputeNodeBuilder*), and buffer nodes represent the content
+/// of a storage buffer at a single moment during the *RenderFlow*. Buffer nodes
+/// work like grid nodes: a compute node that writes a storage buffer needs an
+/// outgoing link to a buffer node, and every compute node or drawing node that
+/// reads it needs an incoming link from that buffer node.
+///
+/// Finally, a *RenderFlow* can have resolve operations, which combine the samples
+/// of a multisampled color grid into a color grid with a single sample per pixel
+/// (see the documentation of *ResolveBuilder*). The *GraphicsPipeline* of every
+/// drawing node must have the same *SampleCount* as the grids it draws on. Use the
+/// *validate_sample_counts* method to check this.
+///
 /// To obtain an instance of this struct, use the *create_render_flow_builder*
 /// method of the *AbstractGridGroup* for which you want to create a new
 /// *RenderFlow*.
@@ -35,9 +49,12 @@ use super::GridNodeBuilder;
 pub struct RenderFlowBuilder {
     drawing_nodes: Vec<DrawingNodeBuilder>,
     grid_nodes: Vec<GridNodeBuilder>,
+    compute_nodes: Vec<ComputeNodeBuilder>,
+    resolves: Vec<ResolveBuilder>,
 
     id: RenderFlowBuilderID,
     next_grid_state_id: u32,
+    next_buffer_node_id: u32,
 }
 
 impl RenderFlowBuilder {
@@ -49,8 +66,11 @@ impl RenderFlowBuilder {
         Self {
             drawing_nodes: Vec::new(),
             grid_nodes: Vec::new(),
+            compute_nodes: Vec::new(),
+            resolves: Vec::new(),
             id,
             next_grid_state_id: 0,
+            next_buffer_node_id: 0,
         }
     }
 
@@ -73,19 +93,374 @@ impl RenderFlowBuilder {
     /// the newly added grid node will be returned, because you will need this
     /// id when constructing the inputs and outputs of the drawing nodes. See
     /// the documentation of this struct for more information about grid nodes.
-    pub fn add_grid_node(&mut self, grid: AbstractGridID) -> GridNodeID {
+    pub fn add_grid_node(&mut self, grid: impl Into<AbstractGridID>) -> GridNodeID {
         let node = GridNodeBuilder::new(
             GridNodeID {
                 flow_id: self.id,
                 own_id: self.next_grid_state_id,
             },
-            grid,
+            grid.into(),
         );
         self.next_grid_state_id += 1;
         let id = node.get_id();
         self.grid_nodes.push(node);
         id
     }
+
+    /// Adds a new compute node to this *RenderFlowBuilder* (and thus to the future
+    /// *RenderFlow*). See the documentation of *ComputeNodeBuilder* for more
+    /// information about compute nodes.
+    pub fn add_compute_node(&mut self, node: ComputeNodeBuilder) {
+        self.compute_nodes.push(node);
+    }
+
+    /// Adds a new buffer node to this *RenderFlowBuilder* (and thus to the future
+    /// *RenderFlow*). The id of the newly added buffer node will be returned,
+    /// because you will need this id when constructing the inputs and outputs of
+    /// the compute nodes and drawing nodes. See the documentation of this struct
+    /// for more information about buffer nodes.
+    pub fn add_buffer_node(&mut self) -> BufferNodeID {
+        let id = BufferNodeID {
+            flow_id: self.id,
+            own_id: self.next_buffer_node_id,
+        };
+        self.next_buffer_node_id += 1;
+        id
+    }
+
+    /// Adds a new resolve operation to this *RenderFlowBuilder* (and thus to the
+    /// future *RenderFlow*). See the documentation of *ResolveBuilder* for more
+    /// information about resolve operations.
+    pub fn add_resolve(&mut self, resolve: ResolveBuilder) {
+        self.resolves.push(resolve);
+    }
+
+    /// Gets the drawing nodes that have been added to this *RenderFlowBuilder*.
+    /// This method is meant for Griphin implementations.
+    pub fn get_drawing_nodes(&self) -> &Vec<DrawingNodeBuilder> {
+        &self.drawing_nodes
+    }
+
+    /// Gets the grid nodes that have been added to this *RenderFlowBuilder*.
+    /// This method is meant for Griphin implementations.
+    pub fn get_grid_nodes(&self) -> &Vec<GridNodeBuilder> {
+        &self.grid_nodes
+    }
+
+    /// Gets the compute nodes that have been added to this *RenderFlowBuilder*.
+    /// This method is meant for Griphin implementations.
+    pub fn get_compute_nodes(&self) -> &Vec<ComputeNodeBuilder> {
+        &self.compute_nodes
+    }
+
+    /// Gets the number of buffer nodes that have been added to this
+    /// *RenderFlowBuilder*. This method is meant for Griphin implementations.
+    pub fn get_num_buffer_nodes(&self) -> u32 {
+        self.next_buffer_node_id
+    }
+
+    /// Gets the resolve operations that have been added to this
+    /// *RenderFlowBuilder*. This method is meant for Griphin implementations.
+    pub fn get_resolves(&self) -> &Vec<ResolveBuilder> {
+        &self.resolves
+    }
+
+    pub(super) fn get_destination_grid(
+        &self,
+        destination: &DrawingNodeOutputDestination,
+    ) -> AbstractGridID {
+        match destination {
+            DrawingNodeOutputDestination::Internal(node) => self.get_node_grid(*node),
+            DrawingNodeOutputDestination::External(grid) => *grid,
+        }
+    }
+
+    pub(super) fn find_node_grid(&self, node: GridNodeID) -> Option<AbstractGridID> {
+        self.grid_nodes
+            .iter()
+            .find(|candidate| candidate.get_id() == node)
+            .map(|grid_node| grid_node.get_grid())
+    }
+
+    pub(super) fn get_node_grid(&self, node: GridNodeID) -> AbstractGridID {
+        match self.find_node_grid(node) {
+            Some(grid) => grid,
+            None => panic!(
+                "The grid node {:?} doesn't belong to this RenderFlowBuilder",
+                node
+            ),
+        }
+    }
+
+    /// Checks that the sample counts of the drawing nodes and resolve operations of
+    /// this *RenderFlowBuilder* match the sample counts of the grids of the given
+    /// *AbstractGridGroup* (which should be the group that created this builder):
+    ///
+    /// - The *SampleCount* of the pipeline of every drawing node must be equal
+    ///   to the *SampleCount* of every grid the node draws on. These are the
+    ///   grids of its outputs, and the grids of its *Cleared*, *Internal*, and
+    ///   *External* inputs whose pixels are not read directly by the shaders
+    ///   (see the *reads_grid_pixels* method of *DrawingNodeBuilder*). All
+    ///   these grids must also have the same *GridSize*.
+    /// - The source grid of every resolve operation must be multisampled, and
+    ///   the destination grid must have *SampleCount::One* and the same
+    ///   *ColorFormat* and *GridSize*.
+    ///
+    /// Griphin implementations should use this in their *create_render_flow*
+    /// method.
+    pub fn validate_sample_counts(
+        &self,
+        group: &dyn AbstractGridGroup,
+    ) -> Result<(), SampleCountError> {
+        for node in &self.drawing_nodes {
+            self.validate_drawing_node_sample_counts(node, group)?;
+        }
+        for resolve in &self.resolves {
+            self.validate_resolve_sample_counts(resolve, group)?;
+        }
+        Ok(())
+    }
+
+    /// Does the checks of *validate_sample_counts* for a single drawing *node*.
+    pub(crate) fn validate_drawing_node_sample_counts(
+        &self,
+        node: &DrawingNodeBuilder,
+        group: &dyn AbstractGridGroup,
+    ) -> Result<(), SampleCountError> {
+        let mut grids = Vec::new();
+        for input in &node.inputs {
+            match input.source {
+                DrawingNodeInputSource::Cleared(grid) => grids.push(grid),
+                DrawingNodeInputSource::Internal(grid_node) => {
+                    grids.push(self.get_checked_node_grid(grid_node)?)
+                }
+                DrawingNodeInputSource::External(grid) if !node.reads_grid_pixels(input) => {
+                    grids.push(grid)
+                }
+                _ => {}
+            };
+        }
+        for output in &node.outputs {
+            grids.push(self.get_checked_destination_grid(&output.destination)?);
+        }
+
+        let pipeline_sample_count = node.pipeline.get_state().get_sample_count();
+        for grid in &grids {
+            let grid_sample_count = group.get_sample_count(*grid);
+            if grid_sample_count != pipeline_sample_count {
+                return Err(SampleCountError::PipelineMismatch {
+                    pipeline_sample_count,
+                    grid: *grid,
+                    grid_sample_count,
+                });
+            }
+        }
+        if let Some(first_grid) = grids.first() {
+            for grid in &grids[1..] {
+                check_same_size(*first_grid, *grid, group)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Does the checks of *validate_sample_counts* for a single *resolve*
+    /// operation.
+    pub(crate) fn validate_resolve_sample_counts(
+        &self,
+        resolve: &ResolveBuilder,
+        group: &dyn AbstractGridGroup,
+    ) -> Result<(), SampleCountError> {
+        let source = self.get_checked_node_grid(resolve.source)?;
+        let destination = self.get_checked_destination_grid(&resolve.destination)?;
+        if !group.get_sample_count(source).is_multisampled() {
+            return Err(SampleCountError::SourceNotMultisampled { grid: source });
+        }
+        let destination_sample_count = group.get_sample_count(destination);
+        if destination_sample_count.is_multisampled() {
+            return Err(SampleCountError::DestinationMultisampled {
+                grid: destination,
+                sample_count: destination_sample_count,
+            });
+        }
+        let as_color = |grid: AbstractGridID| {
+            grid.as_color()
+                .ok_or(SampleCountError::NotColorGrid { grid })
+        };
+        let source_format = group.get_color_format(as_color(source)?);
+        let destination_format = group.get_color_format(as_color(destination)?);
+        if source_format != destination_format {
+            return Err(SampleCountError::FormatMismatch {
+                source,
+                source_format,
+                destination,
+                destination_format,
+            });
+        }
+        check_same_size(source, destination, group)
+    }
+
+    /// Checks that the outputs of the drawing nodes of this *RenderFlowBuilder*
+    /// match the grids they write to in the given *AbstractGridGroup* (which
+    /// should be the group that created this builder). The pipeline of every
+    /// drawing node must have a *ColorOutput* or *DepthStencilOutput* variable
+    /// with the name of each of its outputs. *ColorOutput* variables must write
+    /// to color grids whose *ColorFormat* matches their *DataType* (see the
+    /// *validate_output* method of *ColorFormat*), and *DepthStencilOutput*
+    /// variables must write to depth stencil grids.
+    ///
+    /// Griphin implementations should use this in their *create_render_flow*
+    /// method.
+    pub fn validate_color_formats(
+        &self,
+        group: &dyn AbstractGridGroup,
+    ) -> Result<(), ColorOutputError> {
+        for node in &self.drawing_nodes {
+            self.validate_drawing_node_color_formats(node, group)?;
+        }
+        Ok(())
+    }
+
+    /// Does the checks of *validate_color_formats* for a single drawing *node*.
+    pub(crate) fn validate_drawing_node_color_formats(
+        &self,
+        node: &DrawingNodeBuilder,
+        group: &dyn AbstractGridGroup,
+    ) -> Result<(), ColorOutputError> {
+        for output in &node.outputs {
+            let grid = match output.destination {
+                DrawingNodeOutputDestination::Internal(node) => self
+                    .find_node_grid(node)
+                    .ok_or(ColorOutputError::UnknownGridNode { node })?,
+                DrawingNodeOutputDestination::External(grid) => grid,
+            };
+            let name = &output.shader_variable_name;
+            let variable = node
+                .pipeline
+                .get_output_variables()
+                .iter()
+                .find(|variable| variable.get_name() == name)
+                .ok_or_else(|| ColorOutputError::UnknownOutput { name: name.clone() })?;
+            let kind_mismatch = || ColorOutputError::GridKindMismatch {
+                name: name.clone(),
+                grid,
+            };
+            match (variable.get_variable_type(), grid.as_color()) {
+                (FragmentShaderVariableType::ColorOutput, Some(color_grid)) => group
+                    .get_color_format(color_grid)
+                    .validate_output(variable.get_data_type())
+                    .map_err(|mismatch| ColorOutputError::FormatMismatch {
+                        name: name.clone(),
+                        grid,
+                        mismatch,
+                    })?,
+                (FragmentShaderVariableType::DepthStencilOutput, None) => {}
+                _ => return Err(kind_mismatch()),
+            };
+        }
+        Ok(())
+    }
+
+    fn get_checked_node_grid(&self, node: GridNodeID) -> Result<AbstractGridID, SampleCountError> {
+        self.find_node_grid(node)
+            .ok_or(SampleCountError::UnknownGridNode { node })
+    }
+
+    fn get_checked_destination_grid(
+        &self,
+        destination: &DrawingNodeOutputDestination,
+    ) -> Result<AbstractGridID, SampleCountError> {
+        match destination {
+            DrawingNodeOutputDestination::Internal(node) => self.get_checked_node_grid(*node),
+            DrawingNodeOutputDestination::External(grid) => Ok(*grid),
+        }
+    }
+
+    /// Checks that all grids that are read by the shaders of the drawing nodes
+    /// and compute nodes of this *RenderFlowBuilder* can be read by shaders: they
+    /// must have the *ShaderRead* purpose in the given *AbstractGridGroup* (which
+    /// should be the group that created this builder), and they must not be
+    /// multisampled. These are the grids of the *SampledGrid* and *ExternalGrid*
+    /// input sources, and the grids of the *External* input sources whose pixels
+    /// are read directly (see the *reads_grid_pixels* method of
+    /// *DrawingNodeBuilder*). Furthermore, the *SamplerState* of a sampled color
+    /// grid must not have a *compare_op*.
+    ///
+    /// Griphin implementations should use this in their *create_render_flow*
+    /// method.
+    pub fn validate_grid_inputs(
+        &self,
+        group: &dyn AbstractGridGroup,
+    ) -> Result<(), GridInputError> {
+        for node in &self.drawing_nodes {
+            self.validate_drawing_node_grid_inputs(node, group)?;
+        }
+        for node in &self.compute_nodes {
+            for input in &node.inputs {
+                if let ComputeNodeInputSource::ExternalGrid(grid) = input.source {
+                    check_grid_input(grid, None, group)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Does the checks of *validate_grid_inputs* for a single drawing *node*.
+    pub(crate) fn validate_drawing_node_grid_inputs(
+        &self,
+        node: &DrawingNodeBuilder,
+        group: &dyn AbstractGridGroup,
+    ) -> Result<(), GridInputError> {
+        for input in &node.inputs {
+            match &input.source {
+                DrawingNodeInputSource::External(grid) if node.reads_grid_pixels(input) => {
+                    check_grid_input(*grid, None, group)?
+                }
+                DrawingNodeInputSource::SampledGrid(grid, sampler) => {
+                    check_grid_input(*grid, Some(sampler), group)?
+                }
+                _ => {}
+            };
+        }
+        Ok(())
+    }
+}
+
+fn check_same_size(
+    first_grid: AbstractGridID,
+    second_grid: AbstractGridID,
+    group: &dyn AbstractGridGroup,
+) -> Result<(), SampleCountError> {
+    let first_size = group.get_grid_size(first_grid);
+    let second_size = group.get_grid_size(second_grid);
+    if first_size != second_size {
+        return Err(SampleCountError::SizeMismatch {
+            first_grid,
+            first_size,
+            second_grid,
+            second_size,
+        });
+    }
+    Ok(())
+}
+
+fn check_grid_input(
+    grid: AbstractGridID,
+    sampler: Option<&SamplerState>,
+    group: &dyn AbstractGridGroup,
+) -> Result<(), GridInputError> {
+    if let Some(sampler) = sampler {
+        if grid.as_color().is_some() && sampler.compare_op.is_some() {
+            return Err(GridInputError::ColorCompareOp { grid });
+        }
+    }
+    if !group.has_shader_read_purpose(grid) {
+        return Err(GridInputError::NotShaderRead { grid });
+    }
+    let sample_count = group.get_sample_count(grid);
+    if sample_count.is_multisampled() {
+        return Err(GridInputError::Multisampled { grid, sample_count });
+    }
+    Ok(())
 }
 
 /// The type to be used as identifier for *RenderFlowBuilder*s. Griphin
@@ -93,3 +468,422 @@ impl RenderFlowBuilder {
 /// a *RenderFlowBuilder* in another *RenderFlowBuilder*, which could give hard
 /// to debug problems.
 pub type RenderFlowBuilderID = u32;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    // The grids with local id 0 and 1 have 4 samples per pixel, the others have 1.
+    // The grid with local id 2 is the only grid without the ShaderRead purpose.
+    fn create_group() -> DummyGroup {
+        let grid = |purpose, format, sample_count| {
+            color_grid(ColorStartOperation::Preserve, purpose, format, sample_count)
+        };
+        DummyGroup::new(AbstractGridGroupBuilder {
+            color_grids: vec![
+                grid(
+                    ColorPurpose::ShaderRead,
+                    ColorFormat::RGBA8Unorm,
+                    SampleCount::Four,
+                ),
+                grid(
+                    ColorPurpose::ShaderRead,
+                    ColorFormat::RGBA16Float,
+                    SampleCount::Four,
+                ),
+                grid(
+                    ColorPurpose::Display,
+                    ColorFormat::RGBA8Unorm,
+                    SampleCount::One,
+                ),
+                grid(
+                    ColorPurpose::ShaderRead,
+                    ColorFormat::RGBA8Unorm,
+                    SampleCount::One,
+                ),
+            ],
+            depth_stencil_grids: Vec::new(),
+        })
+    }
+
+    fn create_output_node(
+        sample_count: SampleCount,
+        destination: DrawingNodeOutputDestination,
+    ) -> DrawingNodeBuilder {
+        create_drawing_node(
+            create_multisampled_pipeline(sample_count),
+            Vec::new(),
+            vec![(destination, "outColor")],
+        )
+    }
+
+    #[test]
+    fn test_validate_drawing_nodes() {
+        let group = create_group();
+        let ids = &group.ids;
+        let multi_grid = ids.colors[0].get_abstract();
+        let single_grid = ids.colors[2].get_abstract();
+
+        let mut builder = group.create_render_flow_builder();
+        let node = builder.add_grid_node(multi_grid);
+        builder.add_drawing_node(create_output_node(
+            SampleCount::Four,
+            DrawingNodeOutputDestination::Internal(node),
+        ));
+        builder.add_drawing_node(create_output_node(
+            SampleCount::One,
+            DrawingNodeOutputDestination::External(single_grid),
+        ));
+        assert!(builder.validate_sample_counts(&group).is_ok());
+
+        builder.add_drawing_node(create_output_node(
+            SampleCount::One,
+            DrawingNodeOutputDestination::Internal(node),
+        ));
+        match builder.validate_sample_counts(&group) {
+            Err(SampleCountError::PipelineMismatch {
+                pipeline_sample_count,
+                grid,
+                grid_sample_count,
+            }) => {
+                assert_eq!(SampleCount::One, pipeline_sample_count);
+                assert_eq!(multi_grid, grid);
+                assert_eq!(SampleCount::Four, grid_sample_count);
+            }
+            _ => panic!("Expected a pipeline mismatch"),
+        };
+    }
+
+    #[test]
+    fn test_validate_drawing_node_inputs() {
+        let group = create_group();
+        let ids = &group.ids;
+        let multi_grid = ids.colors[0].get_abstract();
+        let single_grid = ids.colors[3].get_abstract();
+
+        let create_builder = |source: DrawingNodeInputSource, variable_type| {
+            let mut builder = group.create_render_flow_builder();
+            let mut state = PipelineStateBuilder::new();
+            state.sample_count = SampleCount::Four;
+            let pipeline = create_pipeline_with_state(
+                vec![variable(
+                    "inputGrid",
+                    DataType::new(FLOAT, VEC4),
+                    variable_type,
+                )],
+                &state,
+                PrimitiveTopology::Triangles,
+            );
+            builder.add_drawing_node(create_drawing_node(
+                pipeline,
+                vec![(source, "inputGrid")],
+                vec![(
+                    DrawingNodeOutputDestination::External(multi_grid),
+                    "outColor",
+                )],
+            ));
+            builder
+        };
+        let uniform = ExternalShaderVariableType::UniformInput;
+        let grid_input = ExternalShaderVariableType::ColorGridInput;
+
+        let builder = create_builder(DrawingNodeInputSource::Cleared(multi_grid), uniform);
+        assert!(builder.validate_sample_counts(&group).is_ok());
+
+        // The multisampled pipeline can't draw on grids with 1 sample per pixel
+        let builder = create_builder(DrawingNodeInputSource::Cleared(single_grid), uniform);
+        assert!(matches!(
+            builder.validate_sample_counts(&group),
+            Err(SampleCountError::PipelineMismatch { grid, .. }) if grid == single_grid
+        ));
+        let builder = create_builder(DrawingNodeInputSource::External(single_grid), uniform);
+        assert!(matches!(
+            builder.validate_sample_counts(&group),
+            Err(SampleCountError::PipelineMismatch { grid, .. }) if grid == single_grid
+        ));
+
+        // But its shaders can read the pixels of such a grid
+        let builder = create_builder(DrawingNodeInputSource::External(single_grid), grid_input);
+        assert!(builder.validate_sample_counts(&group).is_ok());
+
+        // A grid node of another RenderFlowBuilder
+        let node = RenderFlowBuilder::new(1).add_grid_node(single_grid);
+        let builder = create_builder(DrawingNodeInputSource::Internal(node), uniform);
+        assert!(matches!(
+            builder.validate_sample_counts(&group),
+            Err(SampleCountError::UnknownGridNode { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_display_grids() {
+        let group = create_group();
+        assert!(group.builder.validate_sample_counts(&group.ids).is_ok());
+
+        let mut builder = AbstractGridGroupBuilder {
+            color_grids: vec![color_grid(
+                ColorStartOperation::DontCare,
+                ColorPurpose::Display,
+                ColorFormat::RGBA8Srgb,
+                SampleCount::Four,
+            )],
+            depth_stencil_grids: Vec::new(),
+        };
+        let ids = GridIDAllocator::new().allocate(&builder);
+        assert!(matches!(
+            builder.validate_sample_counts(&ids),
+            Err(SampleCountError::DisplayMultisampled { grid, .. })
+                if grid == ids.colors[0].get_abstract()
+        ));
+
+        builder.color_grids[0].sample_count = SampleCount::One;
+        assert!(builder.validate_sample_counts(&ids).is_ok());
+    }
+
+    #[test]
+    fn test_validate_resolves() {
+        let group = create_group();
+        let ids = &group.ids;
+        let multi_grid = ids.colors[0].get_abstract();
+        let multi_float_grid = ids.colors[1].get_abstract();
+        let display_grid = ids.colors[2].get_abstract();
+
+        let mut builder = group.create_render_flow_builder();
+        let multi_node = builder.add_grid_node(multi_grid);
+        builder.add_resolve(ResolveBuilder {
+            source: multi_node,
+            destination: DrawingNodeOutputDestination::External(display_grid),
+        });
+        assert!(builder.validate_sample_counts(&group).is_ok());
+
+        let mut builder = group.create_render_flow_builder();
+        let display_node = builder.add_grid_node(display_grid);
+        builder.add_resolve(ResolveBuilder {
+            source: display_node,
+            destination: DrawingNodeOutputDestination::External(display_grid),
+        });
+        assert!(matches!(
+            builder.validate_sample_counts(&group),
+            Err(SampleCountError::SourceNotMultisampled { .. })
+        ));
+
+        let mut builder = group.create_render_flow_builder();
+        let multi_node = builder.add_grid_node(multi_grid);
+        builder.add_resolve(ResolveBuilder {
+            source: multi_node,
+            destination: DrawingNodeOutputDestination::External(multi_float_grid),
+        });
+        assert!(matches!(
+            builder.validate_sample_counts(&group),
+            Err(SampleCountError::DestinationMultisampled { .. })
+        ));
+
+        let mut builder = group.create_render_flow_builder();
+        let multi_float_node = builder.add_grid_node(multi_float_grid);
+        builder.add_resolve(ResolveBuilder {
+            source: multi_float_node,
+            destination: DrawingNodeOutputDestination::External(display_grid),
+        });
+        assert!(matches!(
+            builder.validate_sample_counts(&group),
+            Err(SampleCountError::FormatMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_grid_sizes() {
+        let sized_grid = |sample_count, size| AbstractColorGridBuilder {
+            size,
+            ..color_grid(
+                ColorStartOperation::Preserve,
+                ColorPurpose::ShaderRead,
+                ColorFormat::RGBA8Unorm,
+                sample_count,
+            )
+        };
+        let group = DummyGroup::new(AbstractGridGroupBuilder {
+            color_grids: vec![
+                sized_grid(SampleCount::Four, GridSize::FULL),
+                sized_grid(SampleCount::Four, GridSize::HALF),
+                sized_grid(SampleCount::One, GridSize::HALF),
+            ],
+            depth_stencil_grids: Vec::new(),
+        });
+        let ids = group.get_color_ids();
+        let (full_grid, half_multi_grid, half_grid) = (ids[0], ids[1], ids[2]);
+
+        let create_node = |cleared_grid, output_grid| {
+            create_drawing_node(
+                create_multisampled_pipeline(SampleCount::Four),
+                vec![(DrawingNodeInputSource::Cleared(cleared_grid), "inColor")],
+                vec![(
+                    DrawingNodeOutputDestination::External(output_grid),
+                    "outColor",
+                )],
+            )
+        };
+        let mut builder = group.create_render_flow_builder();
+        builder.add_drawing_node(create_node(half_multi_grid, half_multi_grid));
+        let node = builder.add_grid_node(half_multi_grid);
+        builder.add_resolve(ResolveBuilder {
+            source: node,
+            destination: DrawingNodeOutputDestination::External(half_grid),
+        });
+        assert!(builder.validate_sample_counts(&group).is_ok());
+
+        let mut builder = group.create_render_flow_builder();
+        builder.add_drawing_node(create_node(full_grid, half_multi_grid));
+        assert!(matches!(
+            builder.validate_sample_counts(&group),
+            Err(SampleCountError::SizeMismatch {
+                first_grid,
+                second_grid,
+                ..
+            }) if first_grid == full_grid && second_grid == half_multi_grid
+        ));
+
+        let mut builder = group.create_render_flow_builder();
+        let node = builder.add_grid_node(full_grid);
+        builder.add_resolve(ResolveBuilder {
+            source: node,
+            destination: DrawingNodeOutputDestination::External(half_grid),
+        });
+        assert!(matches!(
+            builder.validate_sample_counts(&group),
+            Err(SampleCountError::SizeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_color_formats() {
+        let group = create_group();
+        let ids = &group.ids;
+        let unorm_grid = ids.colors[0].get_abstract();
+        let float_grid = ids.colors[1].get_abstract();
+        let vec4 = DataType::new(FLOAT, VEC4);
+        let color = FragmentShaderVariableType::ColorOutput;
+        let create_node = |outputs, destination| {
+            create_drawing_node(
+                create_pipeline_with_outputs(outputs),
+                Vec::new(),
+                vec![(destination, "outColor")],
+            )
+        };
+
+        let mut builder = group.create_render_flow_builder();
+        let node = builder.add_grid_node(float_grid);
+        builder.add_drawing_node(create_node(
+            vec![fragment_variable("outColor", vec4, color)],
+            DrawingNodeOutputDestination::Internal(node),
+        ));
+        builder.add_drawing_node(create_node(
+            vec![fragment_variable("outColor", vec4, color)],
+            DrawingNodeOutputDestination::External(unorm_grid),
+        ));
+        assert!(builder.validate_color_formats(&group).is_ok());
+
+        builder.add_drawing_node(create_node(
+            vec![fragment_variable(
+                "outColor",
+                DataType::new(UINT, VEC4),
+                color,
+            )],
+            DrawingNodeOutputDestination::External(unorm_grid),
+        ));
+        assert!(matches!(
+            builder.validate_color_formats(&group),
+            Err(ColorOutputError::FormatMismatch { grid, .. }) if grid == unorm_grid
+        ));
+
+        let mut builder = group.create_render_flow_builder();
+        builder.add_drawing_node(create_node(
+            vec![fragment_variable("outNormal", vec4, color)],
+            DrawingNodeOutputDestination::External(unorm_grid),
+        ));
+        assert!(matches!(
+            builder.validate_color_formats(&group),
+            Err(ColorOutputError::UnknownOutput { .. })
+        ));
+
+        let mut builder = group.create_render_flow_builder();
+        builder.add_drawing_node(create_node(
+            vec![fragment_variable(
+                "outColor",
+                DataType::new(FLOAT, SINGLE),
+                FragmentShaderVariableType::DepthStencilOutput,
+            )],
+            DrawingNodeOutputDestination::External(float_grid),
+        ));
+        assert!(matches!(
+            builder.validate_color_formats(&group),
+            Err(ColorOutputError::GridKindMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_grid_inputs() {
+        let group = create_group();
+        let ids = &group.ids;
+        let multi_grid = ids.colors[0].get_abstract();
+        let display_grid = ids.colors[2].get_abstract();
+        let texture_grid = ids.colors[3].get_abstract();
+
+        let create_builder = |source: DrawingNodeInputSource, variable_type| {
+            let mut builder = group.create_render_flow_builder();
+            let pipeline = create_pipeline(vec![variable(
+                "inputGrid",
+                DataType::new(FLOAT, VEC4),
+                variable_type,
+            )]);
+            builder.add_drawing_node(create_drawing_node(
+                pipeline,
+                vec![(source, "inputGrid")],
+                vec![(
+                    DrawingNodeOutputDestination::External(display_grid),
+                    "outColor",
+                )],
+            ));
+            builder
+        };
+        let texture = ExternalShaderVariableType::TextureInput;
+        let grid_input = ExternalShaderVariableType::ColorGridInput;
+
+        let sampled = |grid| DrawingNodeInputSource::SampledGrid(grid, SamplerState::new());
+        let builder = create_builder(sampled(texture_grid), texture);
+        assert!(builder.validate_grid_inputs(&group).is_ok());
+
+        let builder = create_builder(DrawingNodeInputSource::External(texture_grid), grid_input);
+        assert!(builder.validate_grid_inputs(&group).is_ok());
+
+        let builder = create_builder(sampled(display_grid), texture);
+        assert!(matches!(
+            builder.validate_grid_inputs(&group),
+            Err(GridInputError::NotShaderRead { .. })
+        ));
+
+        let builder = create_builder(DrawingNodeInputSource::External(multi_grid), grid_input);
+        assert!(matches!(
+            builder.validate_grid_inputs(&group),
+            Err(GridInputError::Multisampled { .. })
+        ));
+
+        // The node draws on this External grid instead of reading it, so it
+        // doesn't need to be readable by shaders
+        let uniform = ExternalShaderVariableType::UniformInput;
+        let builder = create_builder(DrawingNodeInputSource::External(display_grid), uniform);
+        assert!(builder.validate_grid_inputs(&group).is_ok());
+
+        let mut sampler = SamplerState::new();
+        sampler.compare_op = Some(CompareOp::Less);
+        let builder = create_builder(
+            DrawingNodeInputSource::SampledGrid(texture_grid, sampler),
+            texture,
+        );
+        assert!(matches!(
+            builder.validate_grid_inputs(&group),
+            Err(GridInputError::ColorCompareOp { grid }) if grid == texture_grid
+        ));
+    }
+}
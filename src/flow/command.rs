@@ -0,0 +1,913 @@
+use crate::*;
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A single command that was recorded by a *DrawCommandRecorder*. Griphin
+/// implementations should execute these commands in order when they run the
+/// drawing node for which they were recorded.
+///
+/// Bindings stay in effect until they are replaced, so a single binding can be
+/// used by multiple draw commands.
+#[derive(Clone)]
+pub enum DrawCommand {
+    /// Binds the vertex buffer that subsequent draws will read their vertices
+    /// from.
+    BindVertexBuffer(Arc<dyn VertexBuffer>),
+
+    /// Binds the index buffer that subsequent indexed draws will read their
+    /// indices from, or unbinds the current index buffer if this is *None*.
+    BindIndexBuffer(Option<Arc<dyn IndexBuffer>>),
+
+    /// Binds the given value to the *UniformInput* shader variable with the
+    /// given name.
+    BindUniform {
+        name: StringRef,
+        value: UniformValue,
+    },
+
+    /// Binds the given texture to the *TextureInput* shader variable with the
+    /// given name.
+    BindTexture {
+        name: StringRef,
+        texture: Arc<dyn Texture>,
+    },
+
+    /// Binds the given storage buffer to the *StorageBuffer* shader variable
+    /// with the given name.
+    BindStorageBuffer {
+        name: StringRef,
+        buffer: Arc<dyn StorageBuffer>,
+    },
+
+    /// Sets the *PushConstant* shader variable with the given name to the given
+    /// value. The *offset* is the position of the variable in the
+    /// *PushConstantLayout* of the pipeline, in bytes.
+    SetPushConstant {
+        name: StringRef,
+        offset: usize,
+        value: UniformValue,
+    },
+
+    /// Draws the given range of vertices of the bound vertex buffer, once for
+    /// every instance in the given range of instances.
+    Draw {
+        vertices: Range<u32>,
+        instances: Range<u32>,
+    },
+
+    /// Draws the vertices that are referenced by the given range of indices of
+    /// the bound index buffer, once for every instance in the given range of
+    /// instances. The *vertex_offset* is added to every index before it is used
+    /// to read the vertex buffer.
+    DrawIndexed {
+        indices: Range<u32>,
+        instances: Range<u32>,
+        vertex_offset: i32,
+    },
+
+    /// Starts counting the samples of the subsequent draws that pass the depth
+    /// and stencil tests, using the given query.
+    BeginOcclusionQuery(Arc<dyn OcclusionQuery>),
+
+    /// Stops counting the samples for the query that was started most recently.
+    EndOcclusionQuery,
+
+    /// Does the non-indexed draws whose parameters are stored in a GPU buffer.
+    /// See *IndirectDraw*.
+    DrawIndirect(IndirectDraw),
+
+    /// Does the indexed draws whose parameters are stored in a GPU buffer. See
+    /// *IndirectDraw*.
+    DrawIndexedIndirect(IndirectDraw),
+}
+
+/// Records the *DrawCommand*s of a drawing node: which vertex buffer (and index
+/// buffer) to use, which values to bind to the *UniformInput*, *TextureInput*,
+/// *StorageBuffer*, and *PushConstant* variables of its *GraphicsPipeline*, and
+/// which ranges of vertices and instances to draw.
+///
+/// Every command is checked against the external variables of the pipeline and
+/// the *VertexBufferUsage* of the bound vertex buffer, depending on the
+/// *DebugLevel* of the recorder:
+///
+/// (1) *Minimal* doesn't check anything.
+///
+/// (2) *Low* checks that bound variables exist, have the right
+/// *ExternalShaderVariableType* and *DataType*, that a vertex buffer (and an
+/// index buffer for indexed draws) is bound, and that its *VertexBufferUsage*
+/// is compatible with the draw and the *PrimitiveTopology* of the pipeline.
+/// It also checks that the *PushConstantLayout* of the pipeline fits in the
+/// maximum push constant size that was given to *new*.
+///
+/// (3) *Basic* also checks that the drawn ranges are within the bounds of the
+/// bound buffers, that no vertex, index, or instance range ends before it
+/// starts, and that bound storage buffers have the right element type.
+///
+/// (4) *High* and *All* also check that all *UniformInput*, *TextureInput*, and
+/// *PushConstant* variables of the pipeline are bound before every draw.
+/// (Storage buffers are not required, since they can also come from buffer
+/// nodes.)
+///
+/// When a command fails a check, it is not recorded, and an error is returned.
+///
+/// A recorder records the commands of a single drawing node, but it only knows
+/// the *GraphicsPipeline* of that node (use *for_node* to take the pipeline from
+/// a *DrawingNodeBuilder*). The code that executes the *RenderFlow* attaches the
+/// commands returned by *finish* to the drawing node, which must use the same
+/// pipeline as the recorder. (The *submit* method of *ParallelCommandRecording*
+/// checks this.)
+pub struct DrawCommandRecorder {
+    pipeline: Arc<dyn GraphicsPipeline>,
+    debug_level: DebugLevel,
+    commands: Vec<DrawCommand>,
+
+    vertex_buffer: Option<Arc<dyn VertexBuffer>>,
+    index_buffer: Option<Arc<dyn IndexBuffer>>,
+    bound_variables: HashSet<StringRef>,
+    push_constant_layout: PushConstantLayout,
+    max_push_constant_size: usize,
+    active_query: Option<Arc<dyn OcclusionQuery>>,
+    used_queries: Vec<Arc<dyn OcclusionQuery>>,
+}
+
+impl DrawCommandRecorder {
+    /// Constructs a new *DrawCommandRecorder* that records commands for drawing
+    /// nodes with the given *pipeline*. The *debug_level* determines which
+    /// checks will be done (see the documentation of this struct).
+    ///
+    /// The *max_push_constant_size* is the maximum size (in bytes) of the push
+    /// constant block of the pipeline, which should be the
+    /// *get_max_push_constant_size* of the *Instance*. If the debug level is at
+    /// least *Low*, *set_push_constant* and the draw methods will return an
+    /// error when the *PushConstantLayout* of the pipeline is larger.
+    pub fn new(
+        pipeline: Arc<dyn GraphicsPipeline>,
+        debug_level: DebugLevel,
+        max_push_constant_size: usize,
+    ) -> Self {
+        let push_constant_layout = PushConstantLayout::new(pipeline.get_external_variables());
+        Self {
+            pipeline,
+            debug_level,
+            commands: Vec::new(),
+            vertex_buffer: None,
+            index_buffer: None,
+            bound_variables: HashSet::new(),
+            push_constant_layout,
+            max_push_constant_size,
+            active_query: None,
+            used_queries: Vec::new(),
+        }
+    }
+
+    /// Constructs a new *DrawCommandRecorder* that records commands for the given
+    /// drawing *node*, using its pipeline.
+    pub fn for_node(
+        node: &DrawingNodeBuilder,
+        debug_level: DebugLevel,
+        max_push_constant_size: usize,
+    ) -> Self {
+        Self::new(
+            Arc::clone(&node.pipeline),
+            debug_level,
+            max_push_constant_size,
+        )
+    }
+
+    /// Gets the *GraphicsPipeline* for which this recorder records commands.
+    pub fn get_pipeline(&self) -> &Arc<dyn GraphicsPipeline> {
+        &self.pipeline
+    }
+
+    /// Gets the *DebugLevel* of this recorder.
+    pub fn get_debug_level(&self) -> DebugLevel {
+        self.debug_level
+    }
+
+    /// Gets the *PushConstantLayout* of the pipeline of this recorder.
+    pub fn get_push_constant_layout(&self) -> &PushConstantLayout {
+        &self.push_constant_layout
+    }
+
+    /// Gets the commands that have been recorded so far.
+    pub fn get_commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    /// Stops recording and returns all recorded commands. If the debug level is
+    /// at least *Low*, this will return an error when an occlusion query is
+    /// still active. Otherwise, the active query will be ended.
+    pub fn finish(mut self) -> Result<Vec<DrawCommand>, DrawCommandError> {
+        if self.active_query.is_some() {
+            if self.debug_level >= DebugLevel::Low {
+                return Err(DrawCommandError::OcclusionQueryNotEnded);
+            }
+            self.commands.push(DrawCommand::EndOcclusionQuery);
+        }
+        Ok(self.commands)
+    }
+
+    /// Binds the vertex buffer that subsequent draws will read their vertices
+    /// from. Its *VertexBufferUsage* will be checked when the next draw is
+    /// recorded.
+    pub fn bind_vertex_buffer(&mut self, buffer: Arc<dyn VertexBuffer>) {
+        self.vertex_buffer = Some(Arc::clone(&buffer));
+        self.commands.push(DrawCommand::BindVertexBuffer(buffer));
+    }
+
+    /// Binds the index buffer that subsequent indexed draws will use, or unbinds
+    /// the current index buffer if *buffer* is *None*.
+    pub fn bind_index_buffer(&mut self, buffer: Option<Arc<dyn IndexBuffer>>) {
+        self.index_buffer = buffer.clone();
+        self.commands.push(DrawCommand::BindIndexBuffer(buffer));
+    }
+
+    /// Binds the given *value* to the *UniformInput* shader variable with the
+    /// given *name*.
+    pub fn bind_uniform(
+        &mut self,
+        name: &StringRef,
+        value: impl Into<UniformValue>,
+    ) -> Result<(), DrawCommandError> {
+        let value = value.into();
+        self.check_variable(
+            name,
+            ExternalShaderVariableType::UniformInput,
+            Some(value.get_data_type()),
+            DebugLevel::Low,
+        )?;
+        self.bound_variables.insert(name.clone());
+        self.commands.push(DrawCommand::BindUniform {
+            name: name.clone(),
+            value,
+        });
+        Ok(())
+    }
+
+    /// Binds the given *texture* to the *TextureInput* shader variable with the
+    /// given *name*.
+    pub fn bind_texture(
+        &mut self,
+        name: &StringRef,
+        texture: Arc<dyn Texture>,
+    ) -> Result<(), DrawCommandError> {
+        self.check_variable(
+            name,
+            ExternalShaderVariableType::TextureInput,
+            None,
+            DebugLevel::Low,
+        )?;
+        self.bound_variables.insert(name.clone());
+        self.commands.push(DrawCommand::BindTexture {
+            name: name.clone(),
+            texture,
+        });
+        Ok(())
+    }
+
+    /// Binds the given *buffer* to the *StorageBuffer* shader variable with the
+    /// given *name*. The element type of the buffer is only checked when the
+    /// debug level is at least *Basic*.
+    pub fn bind_storage_buffer(
+        &mut self,
+        name: &StringRef,
+        buffer: Arc<dyn StorageBuffer>,
+    ) -> Result<(), DrawCommandError> {
+        self.check_variable(
+            name,
+            ExternalShaderVariableType::StorageBuffer,
+            Some(buffer.get_element_type()),
+            DebugLevel::Basic,
+        )?;
+        self.bound_variables.insert(name.clone());
+        self.commands.push(DrawCommand::BindStorageBuffer {
+            name: name.clone(),
+            buffer,
+        });
+        Ok(())
+    }
+
+    /// Sets the *PushConstant* shader variable with the given *name* to the
+    /// given *value*, for all subsequent draws. Unlike the other variables, this
+    /// returns an *UnknownVariable* error even when the debug level is
+    /// *Minimal*, since the offset of the variable is needed.
+    pub fn set_push_constant(
+        &mut self,
+        name: &StringRef,
+        value: impl Into<UniformValue>,
+    ) -> Result<(), DrawCommandError> {
+        let value = value.into();
+        self.check_variable(
+            name,
+            ExternalShaderVariableType::PushConstant,
+            Some(value.get_data_type()),
+            DebugLevel::Low,
+        )?;
+        self.check_push_constant_size()?;
+        let offset = match self.push_constant_layout.get_range(name.to_str()) {
+            Some(range) => range.offset,
+            None => return Err(DrawCommandError::UnknownVariable { name: name.clone() }),
+        };
+        self.bound_variables.insert(name.clone());
+        self.commands.push(DrawCommand::SetPushConstant {
+            name: name.clone(),
+            offset,
+            value,
+        });
+        Ok(())
+    }
+
+    /// Starts the given occlusion *query*: the samples of all draws that are
+    /// recorded before the next *end_occlusion_query* will be counted. If the
+    /// debug level is at least *Low*, this will return an error when another
+    /// query is still active (since occlusion queries can't be nested), or when
+    /// the *query* was already used by this recorder (since a query can be used
+    /// at most once per *RenderFlow* execution).
+    pub fn begin_occlusion_query(
+        &mut self,
+        query: Arc<dyn OcclusionQuery>,
+    ) -> Result<(), DrawCommandError> {
+        if self.debug_level >= DebugLevel::Low {
+            if self.active_query.is_some() {
+                return Err(DrawCommandError::OcclusionQueryActive);
+            }
+            if self
+                .used_queries
+                .iter()
+                .any(|used| Arc::ptr_eq(used, &query))
+            {
+                return Err(DrawCommandError::OcclusionQueryReused);
+            }
+        }
+        self.active_query = Some(Arc::clone(&query));
+        self.used_queries.push(Arc::clone(&query));
+        self.commands.push(DrawCommand::BeginOcclusionQuery(query));
+        Ok(())
+    }
+
+    /// Ends the occlusion query that was started by *begin_occlusion_query*. If
+    /// the debug level is at least *Low*, this will return an error when no
+    /// query is active.
+    pub fn end_occlusion_query(&mut self) -> Result<(), DrawCommandError> {
+        if self.debug_level >= DebugLevel::Low && self.active_query.is_none() {
+            return Err(DrawCommandError::NoOcclusionQuery);
+        }
+        self.active_query = None;
+        self.commands.push(DrawCommand::EndOcclusionQuery);
+        Ok(())
+    }
+
+    /// Draws the given range of *vertices* of the bound vertex buffer, once for
+    /// every instance in the given range of *instances*. The bound vertex buffer
+    /// must have a *NoIndices* or *WildCard* *VertexBufferUsage*.
+    pub fn draw(
+        &mut self,
+        vertices: Range<u32>,
+        instances: Range<u32>,
+    ) -> Result<(), DrawCommandError> {
+        if self.debug_level >= DebugLevel::Low {
+            let vertex_buffer = self.check_vertex_buffer(false)?;
+            self.check_push_constant_size()?;
+            if self.debug_level >= DebugLevel::Basic {
+                check_range(&vertices, vertex_buffer.get_num_vertices())?;
+                check_range(&instances, u32::MAX as usize)?;
+            }
+            self.check_bound_variables()?;
+        }
+        self.commands.push(DrawCommand::Draw {
+            vertices,
+            instances,
+        });
+        Ok(())
+    }
+
+    /// Draws the vertices that are referenced by the given range of *indices* of
+    /// the bound index buffer, once for every instance in the given range of
+    /// *instances*. The bound vertex buffer must have an *Indices* or *WildCard*
+    /// *VertexBufferUsage*.
+    ///
+    /// Note that the indices themselves are not checked against the number of
+    /// vertices, since that would require reading the index buffer.
+    pub fn draw_indexed(
+        &mut self,
+        indices: Range<u32>,
+        instances: Range<u32>,
+    ) -> Result<(), DrawCommandError> {
+        self.draw_indexed_with_offset(indices, instances, 0)
+    }
+
+    /// Like *draw_indexed*, but the given *vertex_offset* is added to every
+    /// index before it is used to read the vertex buffer. This is useful when
+    /// the vertices of multiple models are stored in the same vertex buffer.
+    pub fn draw_indexed_with_offset(
+        &mut self,
+        indices: Range<u32>,
+        instances: Range<u32>,
+        vertex_offset: i32,
+    ) -> Result<(), DrawCommandError> {
+        if self.debug_level >= DebugLevel::Low {
+            self.check_vertex_buffer(true)?;
+            self.check_push_constant_size()?;
+            let index_buffer = match &self.index_buffer {
+                Some(index_buffer) => index_buffer,
+                None => return Err(DrawCommandError::NoIndexBuffer),
+            };
+            if self.debug_level >= DebugLevel::Basic {
+                check_range(&indices, index_buffer.get_num_indices())?;
+                check_range(&instances, u32::MAX as usize)?;
+            }
+            self.check_bound_variables()?;
+        }
+        self.commands.push(DrawCommand::DrawIndexed {
+            indices,
+            instances,
+            vertex_offset,
+        });
+        Ok(())
+    }
+
+    /// Does the non-indexed draws whose parameters are stored in the buffer of
+    /// the given *IndirectDraw*. The bound vertex buffer must have a *NoIndices*
+    /// or *WildCard* *VertexBufferUsage*. If the debug level is at least
+    /// *Basic*, the alignment of the offsets and stride, and the size of the
+    /// buffers will be checked. (The parameters themselves can't be checked,
+    /// since they are only known by the GPU.)
+    pub fn draw_indirect(&mut self, draw: IndirectDraw) -> Result<(), DrawCommandError> {
+        if self.debug_level >= DebugLevel::Low {
+            self.check_vertex_buffer(false)?;
+            self.check_push_constant_size()?;
+            if self.debug_level >= DebugLevel::Basic {
+                draw.check_bounds(false)?;
+            }
+            self.check_bound_variables()?;
+        }
+        self.commands.push(DrawCommand::DrawIndirect(draw));
+        Ok(())
+    }
+
+    /// Does the indexed draws whose parameters are stored in the buffer of the
+    /// given *IndirectDraw*. Like *draw_indexed*, this requires a bound index
+    /// buffer, and a vertex buffer with an *Indices* or *WildCard*
+    /// *VertexBufferUsage*. See *draw_indirect* for the other checks.
+    pub fn draw_indexed_indirect(&mut self, draw: IndirectDraw) -> Result<(), DrawCommandError> {
+        if self.debug_level >= DebugLevel::Low {
+            self.check_vertex_buffer(true)?;
+            self.check_push_constant_size()?;
+            if self.index_buffer.is_none() {
+                return Err(DrawCommandError::NoIndexBuffer);
+            }
+            if self.debug_level >= DebugLevel::Basic {
+                draw.check_bounds(true)?;
+            }
+            self.check_bound_variables()?;
+        }
+        self.commands.push(DrawCommand::DrawIndexedIndirect(draw));
+        Ok(())
+    }
+
+    fn check_variable(
+        &self,
+        name: &StringRef,
+        expected_type: ExternalShaderVariableType,
+        data_type: Option<DataType>,
+        data_type_level: DebugLevel,
+    ) -> Result<(), DrawCommandError> {
+        if self.debug_level < DebugLevel::Low {
+            return Ok(());
+        }
+
+        let variable = self
+            .pipeline
+            .get_external_variables()
+            .iter()
+            .find(|variable| variable.get_name() == name)
+            .ok_or_else(|| DrawCommandError::UnknownVariable { name: name.clone() })?;
+
+        if variable.get_variable_type() != expected_type {
+            return Err(DrawCommandError::WrongVariableType {
+                name: name.clone(),
+                expected: expected_type,
+                actual: variable.get_variable_type(),
+            });
+        }
+
+        if let Some(data_type) = data_type {
+            if self.debug_level >= data_type_level && variable.get_data_type() != data_type {
+                return Err(DrawCommandError::DataTypeMismatch(Box::new(
+                    VariableDataTypeMismatch {
+                        name: name.clone(),
+                        variable_type: variable.get_data_type(),
+                        value_type: data_type,
+                    },
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_vertex_buffer(
+        &self,
+        indexed: bool,
+    ) -> Result<&Arc<dyn VertexBuffer>, DrawCommandError> {
+        let vertex_buffer = match &self.vertex_buffer {
+            Some(vertex_buffer) => vertex_buffer,
+            None => return Err(DrawCommandError::NoVertexBuffer),
+        };
+
+        let topology = self.pipeline.get_topology();
+        let is_compatible = match vertex_buffer.get_usage() {
+            VertexBufferUsage::NoIndices {
+                topology: usage_topology,
+            } => !indexed && usage_topology == topology,
+            VertexBufferUsage::Indices {
+                topology: usage_topology,
+            } => indexed && usage_topology == topology,
+            VertexBufferUsage::WildCard => true,
+        };
+        if !is_compatible {
+            return Err(DrawCommandError::UsageMismatch {
+                usage: vertex_buffer.get_usage(),
+                indexed,
+                topology,
+            });
+        }
+
+        Ok(vertex_buffer)
+    }
+
+    fn check_push_constant_size(&self) -> Result<(), DrawCommandError> {
+        if self.debug_level < DebugLevel::Low {
+            return Ok(());
+        }
+        self.push_constant_layout
+            .check_size(self.max_push_constant_size)
+            .map_err(DrawCommandError::PushConstantSize)
+    }
+
+    fn check_bound_variables(&self) -> Result<(), DrawCommandError> {
+        if self.debug_level < DebugLevel::High {
+            return Ok(());
+        }
+
+        for variable in self.pipeline.get_external_variables() {
+            let must_be_bound = matches!(
+                variable.get_variable_type(),
+                ExternalShaderVariableType::UniformInput
+                    | ExternalShaderVariableType::TextureInput
+                    | ExternalShaderVariableType::PushConstant
+            );
+            if must_be_bound && !self.bound_variables.contains(variable.get_name()) {
+                return Err(DrawCommandError::UnboundVariable {
+                    name: variable.get_name().clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn check_range(range: &Range<u32>, available: usize) -> Result<(), DrawCommandError> {
+    if range.start > range.end || range.end as usize > available {
+        Err(DrawCommandError::OutOfRange {
+            range: range.clone(),
+            available,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// The errors that can be returned by the methods of *DrawCommandRecorder*. See
+/// the documentation of *DrawCommandRecorder* for the checks that are done at
+/// each *DebugLevel*.
+#[derive(Debug)]
+pub enum DrawCommandError {
+    /// The pipeline doesn't have an external variable with the given name.
+    UnknownVariable { name: StringRef },
+
+    /// The external variable with the given name has a different
+    /// *ExternalShaderVariableType* than the bound value.
+    WrongVariableType {
+        name: StringRef,
+        expected: ExternalShaderVariableType,
+        actual: ExternalShaderVariableType,
+    },
+
+    /// The *DataType* of the bound uniform value or storage buffer elements
+    /// doesn't match the *DataType* of the external variable.
+    DataTypeMismatch(Box<VariableDataTypeMismatch>),
+
+    /// A draw was recorded before a vertex buffer was bound.
+    NoVertexBuffer,
+
+    /// An indexed draw was recorded while no index buffer was bound.
+    NoIndexBuffer,
+
+    /// The *VertexBufferUsage* of the bound vertex buffer doesn't allow it to be
+    /// drawn this way (with or without indices, and with the *PrimitiveTopology*
+    /// of the pipeline).
+    UsageMismatch {
+        usage: VertexBufferUsage,
+        indexed: bool,
+        topology: PrimitiveTopology,
+    },
+
+    /// The range of vertices or indices is reversed or exceeds the number of
+    /// vertices or indices in the bound buffer.
+    OutOfRange { range: Range<u32>, available: usize },
+
+    /// A *UniformInput*, *TextureInput*, or *PushConstant* variable of the
+    /// pipeline was not bound before drawing.
+    UnboundVariable { name: StringRef },
+
+    /// The *PushConstantLayout* of the pipeline is larger than the maximum push
+    /// constant size of the recorder.
+    PushConstantSize(PushConstantSizeError),
+
+    /// The offset or stride of an *IndirectDraw* (or the offset of its count)
+    /// is not a multiple of 4.
+    IndirectAlignment { offset: usize, stride: usize },
+
+    /// The stride of an *IndirectDraw* is smaller than the size of the draw
+    /// arguments, so the arguments of consecutive draws would overlap.
+    IndirectStride {
+        stride: usize,
+        arguments_size: usize,
+    },
+
+    /// The buffer of an *IndirectDraw* (or of its count) is too small.
+    IndirectOutOfRange {
+        required_size: usize,
+        buffer_size: usize,
+    },
+
+    /// An *IndirectDraw* with a *count* was expanded without the content of
+    /// the count buffer.
+    MissingIndirectCount,
+
+    /// An *IndirectDraw* was expanded, but the first vertex, index, or instance
+    /// plus the corresponding count of the draw with the given index exceeds
+    /// *u32::MAX*.
+    IndirectArgumentsOverflow { draw_index: usize },
+
+    /// An occlusion query was started while another occlusion query was still
+    /// active.
+    OcclusionQueryActive,
+
+    /// An occlusion query was ended while no occlusion query was active.
+    NoOcclusionQuery,
+
+    /// An occlusion query was started that was already used by the same
+    /// recorder.
+    OcclusionQueryReused,
+
+    /// The recorder was finished while an occlusion query was still active.
+    OcclusionQueryNotEnded,
+}
+
+impl Display for DrawCommandError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::UnknownVariable { name } => {
+                write!(f, "The pipeline doesn't have an external variable {}", name)
+            }
+            Self::WrongVariableType {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Can't bind a {:?} to variable {}, which is a {:?}",
+                expected, name, actual
+            ),
+            Self::DataTypeMismatch(mismatch) => write!(
+                f,
+                "Can't bind a value of type {} to variable {} of type {}",
+                mismatch.value_type.get_glsl_name(),
+                mismatch.name,
+                mismatch.variable_type.get_glsl_name()
+            ),
+            Self::NoVertexBuffer => write!(f, "No vertex buffer is bound"),
+            Self::NoIndexBuffer => write!(f, "No index buffer is bound"),
+            Self::UsageMismatch {
+                usage,
+                indexed,
+                topology,
+            } => write!(
+                f,
+                "A vertex buffer with usage {:?} can't be drawn {} indices with topology {:?}",
+                usage,
+                if *indexed { "with" } else { "without" },
+                topology
+            ),
+            Self::OutOfRange { range, available } => write!(
+                f,
+                "The range {:?} is invalid for a buffer with {} elements",
+                range, available
+            ),
+            Self::UnboundVariable { name } => {
+                write!(f, "Variable {} was not bound before drawing", name)
+            }
+            Self::PushConstantSize(error) => write!(f, "{}", error),
+            Self::IndirectAlignment { offset, stride } => write!(
+                f,
+                "The offset ({}) and stride ({}) of indirect draws must be multiples of 4",
+                offset, stride
+            ),
+            Self::IndirectStride {
+                stride,
+                arguments_size,
+            } => write!(
+                f,
+                "The stride of indirect draws ({}) must be at least {}",
+                stride, arguments_size
+            ),
+            Self::IndirectOutOfRange {
+                required_size,
+                buffer_size,
+            } => write!(
+                f,
+                "Indirect draws need a buffer of at least {} bytes, but it has only {} bytes",
+                required_size, buffer_size
+            ),
+            Self::MissingIndirectCount => {
+                write!(f, "The content of the indirect count buffer is required")
+            }
+            Self::IndirectArgumentsOverflow { draw_index } => write!(
+                f,
+                "The ranges of indirect draw {} would exceed the maximum value of a u32",
+                draw_index
+            ),
+            Self::OcclusionQueryActive => write!(f, "Another occlusion query is still active"),
+            Self::NoOcclusionQuery => write!(f, "No occlusion query is active"),
+            Self::OcclusionQueryReused => {
+                write!(
+                    f,
+                    "An occlusion query can be used only once per render flow"
+                )
+            }
+            Self::OcclusionQueryNotEnded => write!(
+                f,
+                "The recorder was finished while an occlusion query was still active"
+            ),
+        }
+    }
+}
+
+impl Error for DrawCommandError {}
+
+/// The details of a *DataTypeMismatch* *DrawCommandError*: the *DataType* of the
+/// bound value differs from the *DataType* of the shader variable.
+#[derive(Debug)]
+pub struct VariableDataTypeMismatch {
+    pub name: StringRef,
+    pub variable_type: DataType,
+    pub value_type: DataType,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    use cgmath::Vector4;
+
+    fn create_recorder(debug_level: DebugLevel) -> DrawCommandRecorder {
+        let pipeline = create_pipeline(vec![
+            variable(
+                "tint",
+                DataType::new(FLOAT, VEC4),
+                ExternalShaderVariableType::UniformInput,
+            ),
+            variable(
+                "diffuse",
+                DataType::new(FLOAT, VEC4),
+                ExternalShaderVariableType::TextureInput,
+            ),
+        ]);
+        DrawCommandRecorder::new(pipeline, debug_level, 128)
+    }
+
+    fn vertex_buffer(usage: VertexBufferUsage) -> Arc<dyn VertexBuffer> {
+        Arc::new(DummyVertexBuffer {
+            usage,
+            num_vertices: 30,
+        })
+    }
+
+    #[test]
+    fn test_bind_variables() {
+        let mut recorder = create_recorder(DebugLevel::Low);
+        let tint = Vector4::new(1.0, 0.5, 0.5, 1.0);
+        assert!(recorder.bind_uniform(&str_ref("tint"), tint).is_ok());
+        assert!(matches!(
+            recorder.bind_uniform(&str_ref("tint"), 1.0),
+            Err(DrawCommandError::DataTypeMismatch(_))
+        ));
+        assert!(matches!(
+            recorder.bind_uniform(&str_ref("diffuse"), tint),
+            Err(DrawCommandError::WrongVariableType { .. })
+        ));
+        assert!(matches!(
+            recorder.bind_texture(&str_ref("normals"), Arc::new(DummyTexture {})),
+            Err(DrawCommandError::UnknownVariable { .. })
+        ));
+        assert!(recorder
+            .bind_texture(&str_ref("diffuse"), Arc::new(DummyTexture {}))
+            .is_ok());
+        assert_eq!(2, recorder.finish().unwrap().len());
+
+        let mut recorder = create_recorder(DebugLevel::Minimal);
+        assert!(recorder.bind_uniform(&str_ref("unknown"), 1.0).is_ok());
+    }
+
+    #[test]
+    fn test_draw() {
+        let triangles = PrimitiveTopology::Triangles;
+        let mut recorder = create_recorder(DebugLevel::Basic);
+        assert!(matches!(
+            recorder.draw(0..3, 0..1),
+            Err(DrawCommandError::NoVertexBuffer)
+        ));
+
+        recorder.bind_vertex_buffer(vertex_buffer(VertexBufferUsage::Indices {
+            topology: triangles,
+        }));
+        assert!(matches!(
+            recorder.draw(0..3, 0..1),
+            Err(DrawCommandError::UsageMismatch { indexed: false, .. })
+        ));
+        assert!(matches!(
+            recorder.draw_indexed(0..3, 0..1),
+            Err(DrawCommandError::NoIndexBuffer)
+        ));
+        recorder.bind_index_buffer(Some(Arc::new(DummyIndexBuffer { num_indices: 60 })));
+        assert!(recorder.draw_indexed(0..60, 0..10).is_ok());
+        assert!(recorder.draw_indexed_with_offset(0..6, 0..1, -3).is_ok());
+        assert!(matches!(
+            recorder.commands.last(),
+            Some(DrawCommand::DrawIndexed {
+                vertex_offset: -3,
+                ..
+            })
+        ));
+        assert!(matches!(
+            recorder.draw_indexed(30..61, 0..1),
+            Err(DrawCommandError::OutOfRange { available: 60, .. })
+        ));
+
+        recorder.bind_vertex_buffer(vertex_buffer(VertexBufferUsage::NoIndices {
+            topology: PrimitiveTopology::Lines,
+        }));
+        assert!(matches!(
+            recorder.draw(0..3, 0..1),
+            Err(DrawCommandError::UsageMismatch { .. })
+        ));
+
+        recorder.bind_vertex_buffer(vertex_buffer(VertexBufferUsage::WildCard));
+        assert!(recorder.draw(0..30, 0..1).is_ok());
+        assert!(recorder.draw_indexed(0..3, 0..1).is_ok());
+        assert!(matches!(
+            recorder.draw(20..31, 0..1),
+            Err(DrawCommandError::OutOfRange { available: 30, .. })
+        ));
+        let reversed = Range { start: 2, end: 1 };
+        assert!(matches!(
+            recorder.draw(0..3, reversed.clone()),
+            Err(DrawCommandError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            recorder.draw_indexed(0..3, reversed),
+            Err(DrawCommandError::OutOfRange { .. })
+        ));
+
+        // The variables only need to be bound at debug level High
+        let mut recorder = create_recorder(DebugLevel::High);
+        recorder.bind_vertex_buffer(vertex_buffer(VertexBufferUsage::WildCard));
+        assert!(recorder
+            .bind_uniform(&str_ref("tint"), ColorF32::WHITE)
+            .is_ok());
+        assert!(matches!(
+            recorder.draw(0..3, 0..1),
+            Err(DrawCommandError::UnboundVariable { .. })
+        ));
+        assert!(recorder
+            .bind_texture(&str_ref("diffuse"), Arc::new(DummyTexture {}))
+            .is_ok());
+        assert!(recorder.draw(0..3, 0..1).is_ok());
+    }
+
+    #[test]
+    fn test_for_node() {
+        let node = create_drawing_node(create_pipeline(Vec::new()), Vec::new(), Vec::new());
+        let recorder = DrawCommandRecorder::for_node(&node, DebugLevel::Low, 128);
+        assert!(Arc::ptr_eq(&node.pipeline, recorder.get_pipeline()));
+    }
+}
@@ -0,0 +1,509 @@
+use crate::*;
+
+use std::any::Any;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// Represents a compute node of a *RenderFlow*. See the documentation of
+/// *ComputeNodeBuilder* for more information.
+///
+/// TODO Add a way to use it
+pub trait ComputeNode: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// This struct contains the information needed to add a new compute node to a
+/// *RenderFlowBuilder*. It consists of a *ComputeShader*, a *DispatchSize*, input
+/// links, and output links.
+///
+/// Compute nodes are to *ComputeShader*s what drawing nodes are to
+/// *GraphicsPipeline*s. For every external variable of the compute shader that is
+/// read, the node must have a corresponding *ComputeNodeInput*. For every storage
+/// buffer variable that is written, the node must have a corresponding
+/// *ComputeNodeOutput*. (A storage buffer that is both read and written will need
+/// both an input and an output.)
+///
+/// The order in which compute nodes and drawing nodes are executed is determined
+/// by the buffer nodes and grid nodes they read from and write to, in the same
+/// way as the order between drawing nodes is determined by the grid nodes.
+///
+/// To add the node, use the *add_compute_node* method of a *RenderFlowBuilder*.
+pub struct ComputeNodeBuilder {
+    pub shader: Arc<dyn ComputeShader>,
+    pub dispatch_size: DispatchSize,
+    pub inputs: Vec<ComputeNodeInput>,
+    pub outputs: Vec<ComputeNodeOutput>,
+}
+
+/// The number of workgroups that a compute node should dispatch, in each
+/// dimension. The total number of shader invocations will be this number of
+/// workgroups, multiplied by the *WorkgroupSize* of the *ComputeShader*.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DispatchSize {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+}
+
+impl DispatchSize {
+    /// Constructs a new *DispatchSize* with the given number of workgroups in each
+    /// dimension.
+    pub fn new(x: u32, y: u32, z: u32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Gets the total number of workgroups that will be dispatched. That is, the
+    /// product of the number of workgroups in all 3 dimensions. Returns *None* if
+    /// that product doesn't fit in a *u32*.
+    pub fn get_num_workgroups(&self) -> Option<u32> {
+        self.x.checked_mul(self.y)?.checked_mul(self.z)
+    }
+}
+
+/// Represents an input of a compute node. This consists of a
+/// *ComputeNodeInputSource* that specifies where the input data comes from, and
+/// the name of the shader variable to which this data will be bound.
+#[derive(Clone)]
+pub struct ComputeNodeInput {
+    pub source: ComputeNodeInputSource,
+    pub shader_variable_name: StringRef,
+}
+
+/// This enum specifies where the input data of a compute node should come from.
+/// See the documentation of each of the possibilities for more information.
+#[derive(Clone)]
+pub enum ComputeNodeInputSource {
+    /// The input values should come from a buffer node (of the same render flow).
+    BufferNode(BufferNodeID),
+
+    /// The input values should come from a grid node (of the same render flow).
+    GridNode(GridNodeID),
+
+    /// A source from outside the render flow should ensure the input values are
+    /// stored in the given grid before the flow starts.
+    ExternalGrid(AbstractGridID),
+
+    /// The input value is a global variable that is chosen right before the
+    /// compute node is executed.
+    Uniform,
+
+    /// The input value is a *StorageBuffer* that is chosen right before the
+    /// compute node is executed, but created in advance (for instance via the
+    /// *Gateway*).
+    StorageBuffer,
+}
+
+/// Represents an output of a compute node. This consists of the buffer node to
+/// which the output should be written, and the name of the storage buffer
+/// variable from which the data will come.
+#[derive(Clone)]
+pub struct ComputeNodeOutput {
+    pub destination: BufferNodeID,
+    pub shader_variable_name: StringRef,
+}
+
+/// This error indicates that the buffer nodes of a *RenderFlowBuilder* are not
+/// linked correctly. See the *validate_buffer_nodes* method of
+/// *RenderFlowBuilder*.
+#[derive(Debug)]
+pub enum BufferNodeError {
+    /// The buffer node was not added to the *RenderFlowBuilder*.
+    UnknownNode { node: BufferNodeID },
+
+    /// More than 1 compute node writes to the buffer node.
+    MultipleWriters { node: BufferNodeID },
+
+    /// The buffer node is read, but no compute node writes to it.
+    NotWritten { node: BufferNodeID },
+
+    /// The buffer node is read by a pass that has to be executed before (or at
+    /// the same time as) the compute node that writes to it.
+    Cycle { node: BufferNodeID },
+}
+
+impl Display for BufferNodeError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::UnknownNode { node } => write!(
+                f,
+                "Buffer node {:?} doesn't belong to this RenderFlowBuilder",
+                node
+            ),
+            Self::MultipleWriters { node } => write!(
+                f,
+                "Buffer node {:?} is written by more than 1 compute node",
+                node
+            ),
+            Self::NotWritten { node } => write!(
+                f,
+                "Buffer node {:?} is read, but no compute node writes to it",
+                node
+            ),
+            Self::Cycle { node } => write!(
+                f,
+                "Buffer node {:?} is read before the compute node that writes to it",
+                node
+            ),
+        }
+    }
+}
+
+impl Error for BufferNodeError {}
+
+/// This error indicates that an input or output of a compute node doesn't match
+/// the variables of its *ComputeShader*. See the *validate_compute_nodes* method
+/// of *RenderFlowBuilder*.
+#[derive(Debug)]
+pub enum ComputeNodeError {
+    /// The compute shader of the node with the given index doesn't have an
+    /// external variable with the given name.
+    UnknownVariable { node: usize, name: StringRef },
+
+    /// The input or output of the node with the given index is linked to a
+    /// buffer node (or a *StorageBuffer*), but the shader variable with the given
+    /// name is not a *StorageBuffer* variable.
+    NotStorageBuffer {
+        node: usize,
+        name: StringRef,
+        actual: ExternalShaderVariableType,
+    },
+}
+
+impl Display for ComputeNodeError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::UnknownVariable { node, name } => write!(
+                f,
+                "The compute shader of compute node {} doesn't have an external variable {}",
+                node, name
+            ),
+            Self::NotStorageBuffer { node, name, actual } => write!(
+                f,
+                "Variable {} of compute node {} is linked to a storage buffer, but it is a {:?}",
+                name, node, actual
+            ),
+        }
+    }
+}
+
+impl Error for ComputeNodeError {}
+
+impl RenderFlowBuilder {
+    /// Checks that the buffer nodes of this *RenderFlowBuilder* are linked like
+    /// grid nodes should be:
+    ///
+    /// - Every buffer node that is used must have been added to this builder.
+    /// - Every buffer node must be written by at most 1 compute node.
+    /// - Every buffer node that is read by a compute node or drawing node must
+    ///   be written by a compute node.
+    /// - The links must not form a cycle. In particular, a compute node that
+    ///   reads and writes the same storage buffer needs 2 different buffer nodes.
+    ///
+    /// Griphin implementations should use this in their *create_render_flow*
+    /// method.
+    pub fn validate_buffer_nodes(&self) -> Result<(), BufferNodeError> {
+        let num_nodes = self.get_num_buffer_nodes();
+        let get_index = |node: BufferNodeID| {
+            if node.flow_id == self.get_id() && node.own_id < num_nodes {
+                Ok(node.own_id as usize)
+            } else {
+                Err(BufferNodeError::UnknownNode { node })
+            }
+        };
+
+        let (passes, _) = self.order_passes();
+        let mut writers = vec![None; num_nodes as usize];
+        for (pass_index, pass) in passes.iter().enumerate() {
+            for node in self.get_written_nodes(*pass).1 {
+                if writers[get_index(node)?].replace(pass_index).is_some() {
+                    return Err(BufferNodeError::MultipleWriters { node });
+                }
+            }
+        }
+
+        for (pass_index, pass) in passes.iter().enumerate() {
+            for node in self.get_read_nodes(*pass).1 {
+                match writers[get_index(node)?] {
+                    None => return Err(BufferNodeError::NotWritten { node }),
+                    Some(writer) if writer >= pass_index => {
+                        return Err(BufferNodeError::Cycle { node })
+                    }
+                    Some(_) => {}
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the inputs and outputs of the compute nodes of this
+    /// *RenderFlowBuilder* match the variables of their *ComputeShader*s:
+    ///
+    /// - The shader of every compute node must have an external variable with
+    ///   the *shader_variable_name* of each of its inputs and outputs.
+    /// - The inputs with a *BufferNode* or *StorageBuffer* source, and all
+    ///   outputs, must use a *StorageBuffer* variable.
+    ///
+    /// Griphin implementations should use this in their *create_render_flow*
+    /// method.
+    pub fn validate_compute_nodes(&self) -> Result<(), ComputeNodeError> {
+        for (index, node) in self.get_compute_nodes().iter().enumerate() {
+            let check = |name: &StringRef, needs_storage_buffer: bool| {
+                let variable = node
+                    .shader
+                    .get_variables()
+                    .iter()
+                    .find(|variable| variable.get_name() == name)
+                    .ok_or_else(|| ComputeNodeError::UnknownVariable {
+                        node: index,
+                        name: name.clone(),
+                    })?;
+                let actual = variable.get_variable_type();
+                if needs_storage_buffer && actual != ExternalShaderVariableType::StorageBuffer {
+                    return Err(ComputeNodeError::NotStorageBuffer {
+                        node: index,
+                        name: name.clone(),
+                        actual,
+                    });
+                }
+                Ok(())
+            };
+
+            for input in &node.inputs {
+                let needs_storage_buffer = matches!(
+                    input.source,
+                    ComputeNodeInputSource::BufferNode(_) | ComputeNodeInputSource::StorageBuffer
+                );
+                check(&input.shader_variable_name, needs_storage_buffer)?;
+            }
+            for output in &node.outputs {
+                check(&output.shader_variable_name, true)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    #[derive(Debug)]
+    struct DummyComputeShader {
+        variables: Vec<ExternalShaderVariable>,
+    }
+
+    impl ComputeShader for DummyComputeShader {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn get_debug_name(&self) -> &StringRef {
+            static NAME: StringRef = StringRef::Static("dummy");
+            &NAME
+        }
+
+        fn get_variables(&self) -> &Vec<ExternalShaderVariable> {
+            &self.variables
+        }
+
+        fn get_workgroup_size(&self) -> WorkgroupSize {
+            WorkgroupSize::new(64, 1, 1)
+        }
+    }
+
+    fn create_compute_node(
+        inputs: Vec<BufferNodeID>,
+        outputs: Vec<BufferNodeID>,
+    ) -> ComputeNodeBuilder {
+        ComputeNodeBuilder {
+            shader: Arc::new(DummyComputeShader {
+                variables: Vec::new(),
+            }),
+            dispatch_size: DispatchSize::new(16, 1, 1),
+            inputs: inputs
+                .into_iter()
+                .map(|node| ComputeNodeInput {
+                    source: ComputeNodeInputSource::BufferNode(node),
+                    shader_variable_name: str_ref("input"),
+                })
+                .collect(),
+            outputs: outputs
+                .into_iter()
+                .map(|destination| ComputeNodeOutput {
+                    destination,
+                    shader_variable_name: str_ref("output"),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_num_workgroups() {
+        assert_eq!(Some(24), DispatchSize::new(2, 3, 4).get_num_workgroups());
+        assert_eq!(
+            None,
+            DispatchSize::new(65536, 65536, 1).get_num_workgroups()
+        );
+        assert_eq!(
+            Some(u32::MAX),
+            DispatchSize::new(u32::MAX, 1, 1).get_num_workgroups()
+        );
+        assert_eq!(
+            None,
+            WorkgroupSize::new(1024, 1024, 4096).get_num_invocations()
+        );
+    }
+
+    #[test]
+    fn test_validate_buffer_nodes() {
+        let ids = GridIDAllocator::new().allocate_ids(1, 0);
+        let display = ids.colors[0].get_abstract();
+
+        // A simulation pass, a culling pass that reads the particles, and a
+        // drawing node that draws the visible particles. The nodes are added in
+        // the wrong order, but the buffer nodes determine the real order.
+        let mut builder = RenderFlowBuilder::new(0);
+        let particles = builder.add_buffer_node();
+        let visible = builder.add_buffer_node();
+        builder.add_compute_node(create_compute_node(vec![particles], vec![visible]));
+        builder.add_compute_node(create_compute_node(Vec::new(), vec![particles]));
+        builder.add_drawing_node(create_drawing_node(
+            create_pipeline(Vec::new()),
+            vec![(DrawingNodeInputSource::BufferNode(visible), "particles")],
+            vec![(DrawingNodeOutputDestination::External(display), "outColor")],
+        ));
+        assert!(builder.validate_buffer_nodes().is_ok());
+        assert_eq!(
+            vec![
+                FlowPass::Compute(1),
+                FlowPass::Compute(0),
+                FlowPass::Drawing(0)
+            ],
+            builder.get_pass_order().unwrap()
+        );
+
+        let mut other_builder = RenderFlowBuilder::new(1);
+        let foreign = other_builder.add_buffer_node();
+        builder.add_compute_node(create_compute_node(vec![foreign], Vec::new()));
+        assert!(matches!(
+            builder.validate_buffer_nodes(),
+            Err(BufferNodeError::UnknownNode { .. })
+        ));
+
+        let mut builder = RenderFlowBuilder::new(0);
+        let unwritten = builder.add_buffer_node();
+        builder.add_compute_node(create_compute_node(vec![unwritten], Vec::new()));
+        assert!(matches!(
+            builder.validate_buffer_nodes(),
+            Err(BufferNodeError::NotWritten { .. })
+        ));
+
+        let mut builder = RenderFlowBuilder::new(0);
+        let node = builder.add_buffer_node();
+        builder.add_compute_node(create_compute_node(Vec::new(), vec![node]));
+        builder.add_compute_node(create_compute_node(Vec::new(), vec![node]));
+        assert!(matches!(
+            builder.validate_buffer_nodes(),
+            Err(BufferNodeError::MultipleWriters { .. })
+        ));
+
+        // Reading and writing the same buffer node is a cycle
+        let mut builder = RenderFlowBuilder::new(0);
+        let node = builder.add_buffer_node();
+        builder.add_compute_node(create_compute_node(vec![node], vec![node]));
+        assert!(matches!(
+            builder.validate_buffer_nodes(),
+            Err(BufferNodeError::Cycle { .. })
+        ));
+
+        let mut builder = RenderFlowBuilder::new(0);
+        let first = builder.add_buffer_node();
+        let second = builder.add_buffer_node();
+        builder.add_compute_node(create_compute_node(vec![first], vec![second]));
+        builder.add_compute_node(create_compute_node(vec![second], vec![first]));
+        assert!(matches!(
+            builder.validate_buffer_nodes(),
+            Err(BufferNodeError::Cycle { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_compute_nodes() {
+        let storage_buffer = |name| {
+            variable(
+                name,
+                DataType::new(FLOAT, VEC4),
+                ExternalShaderVariableType::StorageBuffer,
+            )
+        };
+        let shader = Arc::new(DummyComputeShader {
+            variables: vec![
+                storage_buffer("particles"),
+                storage_buffer("newParticles"),
+                variable(
+                    "deltaTime",
+                    DataType::new(FLOAT, SINGLE),
+                    ExternalShaderVariableType::UniformInput,
+                ),
+            ],
+        });
+        let create_builder = |input_source, input_name, output_name| {
+            let mut builder = RenderFlowBuilder::new(0);
+            let node = builder.add_buffer_node();
+            builder.add_compute_node(ComputeNodeBuilder {
+                shader: Arc::clone(&shader) as Arc<dyn ComputeShader>,
+                dispatch_size: DispatchSize::new(16, 1, 1),
+                inputs: vec![ComputeNodeInput {
+                    source: input_source,
+                    shader_variable_name: str_ref(input_name),
+                }],
+                outputs: vec![ComputeNodeOutput {
+                    destination: node,
+                    shader_variable_name: str_ref(output_name),
+                }],
+            });
+            builder
+        };
+
+        let source = ComputeNodeInputSource::StorageBuffer;
+        let builder = create_builder(source, "particles", "newParticles");
+        assert!(builder.validate_compute_nodes().is_ok());
+
+        let builder = create_builder(ComputeNodeInputSource::Uniform, "deltaTime", "particles");
+        assert!(builder.validate_compute_nodes().is_ok());
+
+        let source = ComputeNodeInputSource::StorageBuffer;
+        let builder = create_builder(source, "velocities", "newParticles");
+        assert!(matches!(
+            builder.validate_compute_nodes(),
+            Err(ComputeNodeError::UnknownVariable { node: 0, .. })
+        ));
+
+        let source = ComputeNodeInputSource::StorageBuffer;
+        let builder = create_builder(source, "particles", "newVelocities");
+        assert!(matches!(
+            builder.validate_compute_nodes(),
+            Err(ComputeNodeError::UnknownVariable { .. })
+        ));
+
+        let source = ComputeNodeInputSource::StorageBuffer;
+        let builder = create_builder(source, "deltaTime", "newParticles");
+        assert!(matches!(
+            builder.validate_compute_nodes(),
+            Err(ComputeNodeError::NotStorageBuffer {
+                actual: ExternalShaderVariableType::UniformInput,
+                ..
+            })
+        ));
+
+        let builder = create_builder(ComputeNodeInputSource::Uniform, "deltaTime", "deltaTime");
+        assert!(matches!(
+            builder.validate_compute_nodes(),
+            Err(ComputeNodeError::NotStorageBuffer { .. })
+        ));
+    }
+}
@@ -0,0 +1,452 @@
+use crate::*;
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+impl RenderFlowBuilder {
+    /// Exports the graph of this *RenderFlowBuilder* in the Graphviz DOT format,
+    /// which is useful for debugging flows with many nodes. (Use for instance
+    /// `dot -Tsvg` to render it.)
+    ///
+    /// Drawing nodes and compute nodes are drawn as boxes that show the debug
+    /// name of their pipeline or shader, and grid nodes and buffer nodes as
+    /// ellipses. External grids and the other input sources (cleared grids,
+    /// uniforms, textures...) get their own nodes. Every input and output is an
+    /// edge that is labelled with the name of its shader variable, and resolve
+    /// operations are dashed edges.
+    pub fn to_dot(&self) -> String {
+        let graph = FlowGraph::new(self);
+        let mut dot = String::from("digraph RenderFlow {\n");
+        for node in &graph.nodes {
+            writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\", shape={}];",
+                node.id,
+                escape_dot(&node.label),
+                node.kind.get_dot_shape()
+            )
+            .unwrap();
+        }
+        for edge in &graph.edges {
+            let style = if edge.kind == "resolve" {
+                ", style=dashed"
+            } else {
+                ""
+            };
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{}\"{}];",
+                edge.from,
+                edge.to,
+                escape_dot(&edge.label),
+                style
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Exports the same graph as *to_dot* in JSON format, for tooling. The output
+    /// is stable: it only depends on the nodes that were added to this builder
+    /// and the order in which they were added. It has the following form:
+    ///
+    /// ```json
+    /// {
+    ///   "nodes": [
+    ///     {"id": "draw0", "kind": "drawing", "label": "0: lighting"}
+    ///   ],
+    ///   "edges": [
+    ///     {"from": "grid0", "to": "draw0", "kind": "internal", "label": "albedo"}
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// The node kinds are *drawing*, *compute*, *grid*, *buffer*,
+    /// *external_grid*, and *source* (for the input sources that are not nodes
+    /// of the flow). The edge kinds are *output*, *resolve*, and the kinds of
+    /// input sources: *cleared*, *internal*, *external*, *sampled*, *buffer*,
+    /// *model*, *texture*, *uniform*, *storage_buffer*, and *push_constant*.
+    pub fn to_json(&self) -> String {
+        let graph = FlowGraph::new(self);
+        let mut json = String::from("{\n  \"nodes\": [");
+        for (index, node) in graph.nodes.iter().enumerate() {
+            json.push_str(if index == 0 { "\n" } else { ",\n" });
+            write!(
+                json,
+                "    {{\"id\": \"{}\", \"kind\": \"{}\", \"label\": \"{}\"}}",
+                node.id,
+                node.kind.get_json_name(),
+                escape_json(&node.label)
+            )
+            .unwrap();
+        }
+        json.push_str("\n  ],\n  \"edges\": [");
+        for (index, edge) in graph.edges.iter().enumerate() {
+            json.push_str(if index == 0 { "\n" } else { ",\n" });
+            write!(
+                json,
+                "    {{\"from\": \"{}\", \"to\": \"{}\", \"kind\": \"{}\", \"label\": \"{}\"}}",
+                edge.from,
+                edge.to,
+                edge.kind,
+                escape_json(&edge.label)
+            )
+            .unwrap();
+        }
+        json.push_str("\n  ]\n}\n");
+        json
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    Drawing,
+    Compute,
+    Grid,
+    Buffer,
+    ExternalGrid,
+    Source,
+}
+
+impl NodeKind {
+    fn get_dot_shape(&self) -> &'static str {
+        match self {
+            Self::Drawing | Self::Compute => "box",
+            Self::Grid | Self::Buffer => "ellipse",
+            Self::ExternalGrid => "note",
+            Self::Source => "plaintext",
+        }
+    }
+
+    fn get_json_name(&self) -> &'static str {
+        match self {
+            Self::Drawing => "drawing",
+            Self::Compute => "compute",
+            Self::Grid => "grid",
+            Self::Buffer => "buffer",
+            Self::ExternalGrid => "external_grid",
+            Self::Source => "source",
+        }
+    }
+}
+
+struct GraphNode {
+    id: String,
+    kind: NodeKind,
+    label: String,
+}
+
+struct GraphEdge {
+    from: String,
+    to: String,
+    kind: &'static str,
+    label: String,
+}
+
+/// The graph that both *to_dot* and *to_json* export, so they always agree.
+struct FlowGraph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+impl FlowGraph {
+    fn new(builder: &RenderFlowBuilder) -> Self {
+        let mut graph = Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+        let mut external_grids = BTreeSet::new();
+
+        for grid_node in builder.get_grid_nodes() {
+            let id = grid_node.get_id().own_id;
+            graph.add_node(
+                format!("grid{}", id),
+                NodeKind::Grid,
+                format!("grid node {}\n{}", id, describe_grid(grid_node.get_grid())),
+            );
+        }
+        for id in 0..builder.get_num_buffer_nodes() {
+            graph.add_node(
+                format!("buffer{}", id),
+                NodeKind::Buffer,
+                format!("buffer node {}", id),
+            );
+        }
+
+        for (index, node) in builder.get_drawing_nodes().iter().enumerate() {
+            let node_id = format!("draw{}", index);
+            graph.add_node(
+                node_id.clone(),
+                NodeKind::Drawing,
+                format!("{}: {}", index, node.pipeline.get_debug_name()),
+            );
+
+            for (input_index, input) in node.inputs.iter().enumerate() {
+                let source_id = format!("{}_input{}", node_id, input_index);
+                let (from, kind) = match &input.source {
+                    DrawingNodeInputSource::Cleared(grid) => {
+                        let label = format!("cleared {}", describe_grid(*grid));
+                        graph.add_node(source_id.clone(), NodeKind::Source, label);
+                        (source_id, "cleared")
+                    }
+                    DrawingNodeInputSource::Internal(grid_node) => {
+                        (format!("grid{}", grid_node.own_id), "internal")
+                    }
+                    DrawingNodeInputSource::BufferNode(buffer_node) => {
+                        (format!("buffer{}", buffer_node.own_id), "buffer")
+                    }
+                    DrawingNodeInputSource::External(grid) => {
+                        external_grids.insert(grid_key(*grid));
+                        (external_grid_id(*grid), "external")
+                    }
+                    DrawingNodeInputSource::SampledGrid(grid, _) => {
+                        external_grids.insert(grid_key(*grid));
+                        (external_grid_id(*grid), "sampled")
+                    }
+                    DrawingNodeInputSource::Model => graph.add_source(source_id, "model"),
+                    DrawingNodeInputSource::Texture => graph.add_source(source_id, "texture"),
+                    DrawingNodeInputSource::Uniform => graph.add_source(source_id, "uniform"),
+                    DrawingNodeInputSource::StorageBuffer => {
+                        graph.add_source(source_id, "storage_buffer")
+                    }
+                    DrawingNodeInputSource::PushConstant => {
+                        graph.add_source(source_id, "push_constant")
+                    }
+                };
+                graph.add_edge(from, node_id.clone(), kind, &input.shader_variable_name);
+            }
+
+            for output in &node.outputs {
+                let to = match output.destination {
+                    DrawingNodeOutputDestination::Internal(grid_node) => {
+                        format!("grid{}", grid_node.own_id)
+                    }
+                    DrawingNodeOutputDestination::External(grid) => {
+                        external_grids.insert(grid_key(grid));
+                        external_grid_id(grid)
+                    }
+                };
+                graph.add_edge(node_id.clone(), to, "output", &output.shader_variable_name);
+            }
+        }
+
+        for (index, node) in builder.get_compute_nodes().iter().enumerate() {
+            let node_id = format!("compute{}", index);
+            graph.add_node(
+                node_id.clone(),
+                NodeKind::Compute,
+                format!("{}: {}", index, node.shader.get_debug_name()),
+            );
+
+            for (input_index, input) in node.inputs.iter().enumerate() {
+                let source_id = format!("{}_input{}", node_id, input_index);
+                let (from, kind) = match &input.source {
+                    ComputeNodeInputSource::BufferNode(buffer_node) => {
+                        (format!("buffer{}", buffer_node.own_id), "buffer")
+                    }
+                    ComputeNodeInputSource::GridNode(grid_node) => {
+                        (format!("grid{}", grid_node.own_id), "internal")
+                    }
+                    ComputeNodeInputSource::ExternalGrid(grid) => {
+                        external_grids.insert(grid_key(*grid));
+                        (external_grid_id(*grid), "external")
+                    }
+                    ComputeNodeInputSource::Uniform => graph.add_source(source_id, "uniform"),
+                    ComputeNodeInputSource::StorageBuffer => {
+                        graph.add_source(source_id, "storage_buffer")
+                    }
+                };
+                graph.add_edge(from, node_id.clone(), kind, &input.shader_variable_name);
+            }
+
+            for output in &node.outputs {
+                graph.add_edge(
+                    node_id.clone(),
+                    format!("buffer{}", output.destination.own_id),
+                    "output",
+                    &output.shader_variable_name,
+                );
+            }
+        }
+
+        for resolve in builder.get_resolves() {
+            let to = match resolve.destination {
+                DrawingNodeOutputDestination::Internal(grid_node) => {
+                    format!("grid{}", grid_node.own_id)
+                }
+                DrawingNodeOutputDestination::External(grid) => {
+                    external_grids.insert(grid_key(grid));
+                    external_grid_id(grid)
+                }
+            };
+            graph.edges.push(GraphEdge {
+                from: format!("grid{}", resolve.source.own_id),
+                to,
+                kind: "resolve",
+                label: String::from("resolve"),
+            });
+        }
+
+        for (is_depth_stencil, local_id) in external_grids {
+            let (kind_name, id_prefix) = if is_depth_stencil {
+                ("depth-stencil", "external_depth_stencil")
+            } else {
+                ("color", "external_color")
+            };
+            graph.add_node(
+                format!("{}{}", id_prefix, local_id),
+                NodeKind::ExternalGrid,
+                format!("external {} grid {}", kind_name, local_id),
+            );
+        }
+
+        graph
+    }
+
+    fn add_node(&mut self, id: String, kind: NodeKind, label: String) {
+        self.nodes.push(GraphNode { id, kind, label });
+    }
+
+    fn add_source(&mut self, id: String, kind: &'static str) -> (String, &'static str) {
+        self.add_node(id.clone(), NodeKind::Source, kind.replace('_', " "));
+        (id, kind)
+    }
+
+    fn add_edge(&mut self, from: String, to: String, kind: &'static str, label: &StringRef) {
+        self.edges.push(GraphEdge {
+            from,
+            to,
+            kind,
+            label: label.to_str().to_string(),
+        });
+    }
+}
+
+fn grid_key(grid: AbstractGridID) -> (bool, u16) {
+    (
+        grid.get_kind() == GridKind::DepthStencil,
+        grid.get_local_id(),
+    )
+}
+
+fn external_grid_id(grid: AbstractGridID) -> String {
+    match grid.get_kind() {
+        GridKind::Color => format!("external_color{}", grid.get_local_id()),
+        GridKind::DepthStencil => format!("external_depth_stencil{}", grid.get_local_id()),
+    }
+}
+
+fn describe_grid(grid: AbstractGridID) -> String {
+    match grid.get_kind() {
+        GridKind::Color => format!("color grid {}", grid.get_local_id()),
+        GridKind::DepthStencil => format!("depth-stencil grid {}", grid.get_local_id()),
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            control if (control as u32) < 0x20 => {
+                write!(escaped, "\\u{:04x}", control as u32).unwrap()
+            }
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    fn drawing_node(
+        name: &'static str,
+        inputs: Vec<(DrawingNodeInputSource, &'static str)>,
+        outputs: Vec<(DrawingNodeOutputDestination, &'static str)>,
+    ) -> DrawingNodeBuilder {
+        create_drawing_node(create_named_pipeline(name), inputs, outputs)
+    }
+
+    // A geometry pass that writes the albedo grid, and a lighting pass that
+    // reads it and writes the display grid
+    fn create_builder() -> RenderFlowBuilder {
+        let ids = GridIDAllocator::new().allocate_ids(2, 1);
+        let mut builder = RenderFlowBuilder::new(0);
+        let albedo = builder.add_grid_node(ids.colors[0]);
+        builder.add_drawing_node(drawing_node(
+            "geometry",
+            vec![
+                (DrawingNodeInputSource::Model, "position"),
+                (
+                    DrawingNodeInputSource::Cleared(ids.depth_stencils[0].get_abstract()),
+                    "depth",
+                ),
+            ],
+            vec![(DrawingNodeOutputDestination::Internal(albedo), "albedo")],
+        ));
+        builder.add_drawing_node(drawing_node(
+            "lighting \"deferred\"",
+            vec![
+                (DrawingNodeInputSource::Internal(albedo), "albedo"),
+                (DrawingNodeInputSource::Uniform, "sunDirection"),
+            ],
+            vec![(
+                DrawingNodeOutputDestination::External(ids.colors[1].get_abstract()),
+                "color",
+            )],
+        ));
+        builder
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = create_builder().to_dot();
+        assert!(dot.starts_with("digraph RenderFlow {\n"));
+        assert!(dot.contains("\"draw0\" [label=\"0: geometry\", shape=box];"));
+        assert!(dot.contains("\"draw1\" [label=\"1: lighting \\\"deferred\\\"\", shape=box];"));
+        assert!(dot.contains("\"grid0\" [label=\"grid node 0\\ncolor grid 0\", shape=ellipse];"));
+        assert!(dot.contains(
+            "\"draw0_input1\" [label=\"cleared depth-stencil grid 2\", shape=plaintext];"
+        ));
+        assert!(dot.contains("\"draw0_input1\" -> \"draw0\" [label=\"depth\"];"));
+        assert!(dot.contains("\"draw0\" -> \"grid0\" [label=\"albedo\"];"));
+        assert!(dot.contains("\"grid0\" -> \"draw1\" [label=\"albedo\"];"));
+        assert!(dot.contains("\"draw1\" -> \"external_color1\" [label=\"color\"];"));
+        assert!(dot.contains("\"external_color1\" [label=\"external color grid 1\", shape=note];"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json = create_builder().to_json();
+        assert_eq!(json, create_builder().to_json());
+        assert!(json.starts_with("{\n  \"nodes\": [\n    {\"id\": \"grid0\", \"kind\": \"grid\""));
+        assert!(json.contains(
+            "{\"id\": \"draw1\", \"kind\": \"drawing\", \"label\": \"1: lighting \\\"deferred\\\"\"}"
+        ));
+        assert!(json.contains(
+            "{\"from\": \"draw1_input1\", \"to\": \"draw1\", \"kind\": \"uniform\", \"label\": \"sunDirection\"}"
+        ));
+        assert!(json.contains(
+            "{\"from\": \"draw1\", \"to\": \"external_color1\", \"kind\": \"output\", \"label\": \"color\"}"
+        ));
+        assert_eq!(7, json.matches("\"id\": ").count());
+        assert_eq!(6, json.matches("\"from\": ").count());
+        assert!(json.ends_with("  ]\n}\n"));
+    }
+}
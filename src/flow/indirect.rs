@@ -0,0 +1,477 @@
+use crate::*;
+
+use std::convert::TryInto;
+use std::sync::Arc;
+
+/// The parameters of a single non-indexed draw that is read from a GPU buffer
+/// by a *DrawIndirect* command. In the buffer, these are stored as 4
+/// consecutive 32-bit unsigned integers (in native byte order), in the order
+/// of the fields of this struct. This layout matches both Vulkan and OpenGL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct DrawIndirectArguments {
+    pub vertex_count: u32,
+    pub instance_count: u32,
+    pub first_vertex: u32,
+    pub first_instance: u32,
+}
+
+impl DrawIndirectArguments {
+    /// The size of the arguments in a GPU buffer, in bytes.
+    pub const SIZE: usize = 16;
+
+    /// Reads the arguments from the first *SIZE* bytes of *bytes*.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            vertex_count: read_u32(bytes, 0),
+            instance_count: read_u32(bytes, 4),
+            first_vertex: read_u32(bytes, 8),
+            first_instance: read_u32(bytes, 12),
+        }
+    }
+
+    /// Converts the arguments to the bytes that should be stored in a GPU buffer.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[0..4].copy_from_slice(&self.vertex_count.to_ne_bytes());
+        bytes[4..8].copy_from_slice(&self.instance_count.to_ne_bytes());
+        bytes[8..12].copy_from_slice(&self.first_vertex.to_ne_bytes());
+        bytes[12..16].copy_from_slice(&self.first_instance.to_ne_bytes());
+        bytes
+    }
+
+    /// Converts these arguments to the equivalent *Draw* command, or returns
+    /// *None* if the end of the range of vertices or instances would exceed
+    /// *u32::MAX*.
+    pub fn to_command(&self) -> Option<DrawCommand> {
+        Some(DrawCommand::Draw {
+            vertices: self.first_vertex..self.first_vertex.checked_add(self.vertex_count)?,
+            instances: self.first_instance..self.first_instance.checked_add(self.instance_count)?,
+        })
+    }
+}
+
+/// The parameters of a single indexed draw that is read from a GPU buffer by a
+/// *DrawIndexedIndirect* command. In the buffer, these are stored as 5
+/// consecutive 32-bit integers (in native byte order), in the order of the
+/// fields of this struct. This layout matches both Vulkan and OpenGL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct DrawIndexedIndirectArguments {
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub vertex_offset: i32,
+    pub first_instance: u32,
+}
+
+impl DrawIndexedIndirectArguments {
+    /// The size of the arguments in a GPU buffer, in bytes.
+    pub const SIZE: usize = 20;
+
+    /// Reads the arguments from the first *SIZE* bytes of *bytes*.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            index_count: read_u32(bytes, 0),
+            instance_count: read_u32(bytes, 4),
+            first_index: read_u32(bytes, 8),
+            vertex_offset: read_u32(bytes, 12) as i32,
+            first_instance: read_u32(bytes, 16),
+        }
+    }
+
+    /// Converts the arguments to the bytes that should be stored in a GPU buffer.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[0..4].copy_from_slice(&self.index_count.to_ne_bytes());
+        bytes[4..8].copy_from_slice(&self.instance_count.to_ne_bytes());
+        bytes[8..12].copy_from_slice(&self.first_index.to_ne_bytes());
+        bytes[12..16].copy_from_slice(&self.vertex_offset.to_ne_bytes());
+        bytes[16..20].copy_from_slice(&self.first_instance.to_ne_bytes());
+        bytes
+    }
+
+    /// Converts these arguments to the equivalent *DrawIndexed* command, or
+    /// returns *None* if the end of the range of indices or instances would
+    /// exceed *u32::MAX*.
+    pub fn to_command(&self) -> Option<DrawCommand> {
+        Some(DrawCommand::DrawIndexed {
+            indices: self.first_index..self.first_index.checked_add(self.index_count)?,
+            instances: self.first_instance..self.first_instance.checked_add(self.instance_count)?,
+            vertex_offset: self.vertex_offset,
+        })
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// Describes where the parameters of an indirect draw (or multi-draw) are
+/// stored. Use the *draw_indirect* or *draw_indexed_indirect* method of a
+/// *DrawCommandRecorder* to record it.
+///
+/// The parameters of draw *i* start at byte *offset + i * stride* of the
+/// *buffer*. Each of them is a *DrawIndirectArguments* or a
+/// *DrawIndexedIndirectArguments* (depending on the method that is used).
+/// Both *offset* and *stride* must be multiples of 4, and *stride* must be at
+/// least the size of the arguments (unless only 1 draw is done).
+#[derive(Clone)]
+pub struct IndirectDraw {
+    pub buffer: Arc<dyn StorageBuffer>,
+    pub offset: usize,
+    pub stride: usize,
+
+    /// The number of draws when *count* is *None*, or the maximum number of
+    /// draws otherwise.
+    pub draw_count: u32,
+
+    /// When this is not *None*, the number of draws is read from a GPU buffer
+    /// (and limited to *draw_count*). This is useful when the draws are
+    /// generated by a compute shader, for instance after GPU culling.
+    pub count: Option<IndirectCount>,
+}
+
+/// The location of the number of draws of a multi-draw-indirect: a 32-bit
+/// unsigned integer at byte *offset* of *buffer*. The *offset* must be a
+/// multiple of 4.
+#[derive(Clone)]
+pub struct IndirectCount {
+    pub buffer: Arc<dyn StorageBuffer>,
+    pub offset: usize,
+}
+
+impl IndirectDraw {
+    /// Constructs an *IndirectDraw* that does *draw_count* draws whose
+    /// arguments are tightly packed in *buffer*, starting at byte 0.
+    pub fn new(buffer: Arc<dyn StorageBuffer>, draw_count: u32, indexed: bool) -> Self {
+        Self {
+            buffer,
+            offset: 0,
+            stride: get_arguments_size(indexed),
+            draw_count,
+            count: None,
+        }
+    }
+
+    /// Checks that the *offset* and *stride* are properly aligned, and that the
+    /// parameters of all draws (and the count) lie within the buffers.
+    // is_multiple_of would require Rust 1.87
+    #[allow(clippy::manual_is_multiple_of)]
+    pub(super) fn check_bounds(&self, indexed: bool) -> Result<(), DrawCommandError> {
+        let arguments_size = get_arguments_size(indexed);
+        if self.offset % 4 != 0 || self.stride % 4 != 0 {
+            return Err(DrawCommandError::IndirectAlignment {
+                offset: self.offset,
+                stride: self.stride,
+            });
+        }
+        if self.draw_count > 1 && self.stride < arguments_size {
+            return Err(DrawCommandError::IndirectStride {
+                stride: self.stride,
+                arguments_size,
+            });
+        }
+        if self.draw_count > 0 {
+            let required_size = self
+                .offset
+                .saturating_add((self.draw_count as usize - 1).saturating_mul(self.stride))
+                .saturating_add(arguments_size);
+            check_indirect_size(required_size, &self.buffer)?;
+        }
+
+        if let Some(count) = &self.count {
+            if count.offset % 4 != 0 {
+                return Err(DrawCommandError::IndirectAlignment {
+                    offset: count.offset,
+                    stride: 0,
+                });
+            }
+            check_indirect_size(count.offset.saturating_add(4), &count.buffer)?;
+        }
+        Ok(())
+    }
+
+    /// Converts this indirect draw to the equivalent *Draw* commands, given the
+    /// content of its *buffer* (and of the buffer of its *count*). Griphin
+    /// implementations without indirect drawing support can use this when the
+    /// buffer content is available on the CPU, which yields exactly the same
+    /// draws as the GPU would do.
+    ///
+    /// Returns an *IndirectOutOfRange* error when a content is too small, a
+    /// *MissingIndirectCount* error when this draw has a *count*, but
+    /// *count_content* is *None*, and an *IndirectArgumentsOverflow* error when
+    /// the arguments of a draw can't be converted to a command (see the
+    /// *to_command* method of *DrawIndirectArguments*).
+    pub fn expand(
+        &self,
+        buffer_content: &[u8],
+        count_content: Option<&[u8]>,
+    ) -> Result<Vec<DrawCommand>, DrawCommandError> {
+        self.expand_arguments(buffer_content, count_content, false, |bytes| {
+            DrawIndirectArguments::from_bytes(bytes).to_command()
+        })
+    }
+
+    /// Like *expand*, but converts this indirect draw to the equivalent
+    /// *DrawIndexed* commands.
+    pub fn expand_indexed(
+        &self,
+        buffer_content: &[u8],
+        count_content: Option<&[u8]>,
+    ) -> Result<Vec<DrawCommand>, DrawCommandError> {
+        self.expand_arguments(buffer_content, count_content, true, |bytes| {
+            DrawIndexedIndirectArguments::from_bytes(bytes).to_command()
+        })
+    }
+
+    fn expand_arguments(
+        &self,
+        buffer_content: &[u8],
+        count_content: Option<&[u8]>,
+        indexed: bool,
+        to_command: impl Fn(&[u8]) -> Option<DrawCommand>,
+    ) -> Result<Vec<DrawCommand>, DrawCommandError> {
+        let draw_count = match (&self.count, count_content) {
+            (Some(count), Some(count_content)) => {
+                let bytes = get_content_range(count_content, Some(count.offset), 4)?;
+                read_u32(bytes, 0).min(self.draw_count)
+            }
+            (Some(_), None) => return Err(DrawCommandError::MissingIndirectCount),
+            (None, _) => self.draw_count,
+        };
+        let arguments_size = get_arguments_size(indexed);
+        (0..draw_count as usize)
+            .map(|index| {
+                let offset = index
+                    .checked_mul(self.stride)
+                    .and_then(|offset| offset.checked_add(self.offset));
+                let bytes = get_content_range(buffer_content, offset, arguments_size)?;
+                to_command(bytes)
+                    .ok_or(DrawCommandError::IndirectArgumentsOverflow { draw_index: index })
+            })
+            .collect()
+    }
+}
+
+/// Gets the *size* bytes of *content* that start at *offset*, where an *offset*
+/// of *None* means that the offset overflowed.
+fn get_content_range(
+    content: &[u8],
+    offset: Option<usize>,
+    size: usize,
+) -> Result<&[u8], DrawCommandError> {
+    let end = offset.and_then(|offset| offset.checked_add(size));
+    match (offset, end) {
+        (Some(offset), Some(end)) if end <= content.len() => Ok(&content[offset..end]),
+        _ => Err(DrawCommandError::IndirectOutOfRange {
+            required_size: end.unwrap_or(usize::MAX),
+            buffer_size: content.len(),
+        }),
+    }
+}
+
+fn get_arguments_size(indexed: bool) -> usize {
+    if indexed {
+        DrawIndexedIndirectArguments::SIZE
+    } else {
+        DrawIndirectArguments::SIZE
+    }
+}
+
+fn check_indirect_size(
+    required_size: usize,
+    buffer: &Arc<dyn StorageBuffer>,
+) -> Result<(), DrawCommandError> {
+    if required_size > buffer.get_size() {
+        Err(DrawCommandError::IndirectOutOfRange {
+            required_size,
+            buffer_size: buffer.get_size(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    use cgmath::Vector4;
+
+    fn int_buffer(num_elements: usize) -> Arc<dyn StorageBuffer> {
+        Arc::new(DummyStorageBuffer {
+            element_type: DataType::new(INT, SINGLE),
+            num_elements,
+        })
+    }
+
+    #[test]
+    fn test_check_bounds() {
+        let mut recorder =
+            DrawCommandRecorder::new(create_pipeline(Vec::new()), DebugLevel::Basic, 128);
+        recorder.bind_vertex_buffer(create_vertex_buffer(100));
+
+        // 3 draws of 16 bytes need 48 bytes = 12 ints
+        let draw = IndirectDraw::new(int_buffer(12), 3, false);
+        assert!(recorder.draw_indirect(draw.clone()).is_ok());
+        assert!(matches!(
+            recorder.draw_indexed_indirect(draw.clone()),
+            Err(DrawCommandError::NoIndexBuffer)
+        ));
+
+        let mut too_far = draw.clone();
+        too_far.offset = 4;
+        assert!(matches!(
+            recorder.draw_indirect(too_far),
+            Err(DrawCommandError::IndirectOutOfRange {
+                required_size: 52,
+                buffer_size: 48
+            })
+        ));
+
+        let mut misaligned = draw.clone();
+        misaligned.stride = 18;
+        assert!(matches!(
+            recorder.draw_indirect(misaligned),
+            Err(DrawCommandError::IndirectAlignment { .. })
+        ));
+
+        let mut overlapping = draw.clone();
+        overlapping.stride = 12;
+        assert!(matches!(
+            recorder.draw_indirect(overlapping),
+            Err(DrawCommandError::IndirectStride { .. })
+        ));
+
+        let mut counted = draw;
+        counted.count = Some(IndirectCount {
+            buffer: int_buffer(2),
+            offset: 8,
+        });
+        assert!(matches!(
+            recorder.draw_indirect(counted.clone()),
+            Err(DrawCommandError::IndirectOutOfRange { .. })
+        ));
+        counted.count.as_mut().unwrap().offset = 4;
+        assert!(recorder.draw_indirect(counted).is_ok());
+        assert_eq!(3, recorder.get_commands().len());
+    }
+
+    #[test]
+    fn test_expand() {
+        // The first draw draws triangle 2 once, and the second draws triangles 0 and 1 twice
+        let mut store = StorageBufferStore::new(DataType::new(INT, VEC4), 2);
+        store.put_vec4i(0, Vector4::new(3, 1, 6, 0));
+        store.put_vec4i(1, Vector4::new(6, 2, 0, 5));
+        let mut count = StorageBufferStore::new(DataType::new(INT, SINGLE), 1);
+        count.put_int(0, 1);
+
+        let mut draw = IndirectDraw::new(int_buffer(8), 2, false);
+        let commands = draw.expand(store.get_raw_buffer(), None).unwrap();
+        assert_eq!(2, commands.len());
+        assert!(matches!(
+            &commands[0],
+            DrawCommand::Draw { vertices, instances } if *vertices == (6..9) && *instances == (0..1)
+        ));
+        assert!(matches!(
+            &commands[1],
+            DrawCommand::Draw { vertices, instances } if *vertices == (0..6) && *instances == (5..7)
+        ));
+
+        draw.count = Some(IndirectCount {
+            buffer: int_buffer(1),
+            offset: 0,
+        });
+        let commands = draw
+            .expand(store.get_raw_buffer(), Some(count.get_raw_buffer()))
+            .unwrap();
+        assert_eq!(1, commands.len());
+        assert!(matches!(
+            draw.expand(store.get_raw_buffer(), None),
+            Err(DrawCommandError::MissingIndirectCount)
+        ));
+        assert!(matches!(
+            draw.expand(store.get_raw_buffer(), Some(&[0; 2])),
+            Err(DrawCommandError::IndirectOutOfRange {
+                required_size: 4,
+                buffer_size: 2
+            })
+        ));
+
+        let arguments = DrawIndexedIndirectArguments {
+            index_count: 6,
+            instance_count: 1,
+            first_index: 3,
+            vertex_offset: -2,
+            first_instance: 0,
+        };
+        let mut content = vec![0; 4];
+        content.extend_from_slice(&arguments.to_bytes());
+        assert_eq!(
+            arguments,
+            DrawIndexedIndirectArguments::from_bytes(&content[4..])
+        );
+
+        let mut draw = IndirectDraw::new(int_buffer(6), 1, true);
+        draw.offset = 4;
+        let commands = draw.expand_indexed(&content, None).unwrap();
+        assert!(matches!(
+            &commands[0],
+            DrawCommand::DrawIndexed { indices, vertex_offset: -2, .. } if *indices == (3..9)
+        ));
+
+        // The content is too small for the second draw
+        draw.draw_count = 2;
+        assert!(matches!(
+            draw.expand_indexed(&content, None),
+            Err(DrawCommandError::IndirectOutOfRange {
+                required_size: 44,
+                buffer_size: 24
+            })
+        ));
+        draw.stride = usize::MAX;
+        assert!(matches!(
+            draw.expand_indexed(&content, None),
+            Err(DrawCommandError::IndirectOutOfRange {
+                required_size: usize::MAX,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_to_command_overflow() {
+        let mut arguments = DrawIndirectArguments {
+            vertex_count: 3,
+            instance_count: 1,
+            first_vertex: u32::MAX - 3,
+            first_instance: 1,
+        };
+        assert!(matches!(
+            arguments.to_command(),
+            Some(DrawCommand::Draw { vertices, instances })
+                if vertices == (u32::MAX - 3..u32::MAX) && instances == (1..2)
+        ));
+        arguments.vertex_count = 4;
+        assert!(arguments.to_command().is_none());
+
+        let arguments = DrawIndexedIndirectArguments {
+            index_count: 3,
+            instance_count: u32::MAX,
+            first_index: 0,
+            vertex_offset: 0,
+            first_instance: 1,
+        };
+        assert!(arguments.to_command().is_none());
+
+        let mut content = Vec::new();
+        content.extend_from_slice(&DrawIndexedIndirectArguments::default().to_bytes());
+        content.extend_from_slice(&arguments.to_bytes());
+        let draw = IndirectDraw::new(int_buffer(10), 2, true);
+        assert!(matches!(
+            draw.expand_indexed(&content, None),
+            Err(DrawCommandError::IndirectArgumentsOverflow { draw_index: 1 })
+        ));
+    }
+}
@@ -1,5 +1,8 @@
 use crate::*;
 
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
 /// Represents an input of a drawing node. This consists of a
 /// *DrawingNodeInputSource* that specifies where the input data comes from,
 /// and the name of the shader variable to which this data will be bound.
@@ -20,18 +23,88 @@ pub enum DrawingNodeInputSource {
     /// The input values should come from a grid node (of the same render flow).
     Internal(GridNodeID),
 
+    /// The input values should come from a buffer node (of the same render flow).
+    /// This is typically used to read the results of a compute node.
+    BufferNode(BufferNodeID),
+
     /// A source from outside the render flow should ensure the input values are
-    /// stored in the given grid before the flow starts.
+    /// stored in the given grid before the flow starts. When the shader variable
+    /// is a *ColorGridInput* or *DepthStencilGridInput*, the shader can read the
+    /// pixels of the grid directly (without filtering), and the grid must have
+    /// the *ShaderRead* purpose. Otherwise, the node draws on the grid.
     External(AbstractGridID),
 
+    /// The input value is a grid that is sampled like a texture, using the given
+    /// *SamplerState*. This is typically used for render-to-texture: the content
+    /// of the grid is written during a previous *RenderFlow* (for instance a
+    /// shadow map or a reflection), and is sampled by the shaders of this render
+    /// flow. The grid must have the *ShaderRead* purpose and *SampleCount::One*.
+    SampledGrid(AbstractGridID, SamplerState),
+
     /// The input values are attributes of the Model that is about to be drawn.
     Model,
 
-    /// The input value is a Texture that is chosen right before drawing, but
-    /// submitted to the gpu in advance.
+    /// The input value is a *Texture* that is chosen right before drawing, but
+    /// submitted to the gpu in advance. It is bound with the *bind_texture*
+    /// method of *DrawCommandRecorder*.
     Texture,
 
     /// The input value is a global variable that is chosen right before
-    /// drawing.
+    /// drawing. It is bound with the *bind_uniform* method of
+    /// *DrawCommandRecorder*.
     Uniform,
+
+    /// The input value is a *StorageBuffer* that is chosen right before drawing,
+    /// but created in advance (for instance via the *Gateway*). It is bound with
+    /// the *bind_storage_buffer* method of *DrawCommandRecorder*.
+    StorageBuffer,
+
+    /// The input value is a small constant that can change for every draw. It
+    /// is set with the *set_push_constant* method of *DrawCommandRecorder*.
+    PushConstant,
+}
+
+/// This error indicates that a drawing node or compute node of a
+/// *RenderFlowBuilder* reads a grid that can't be read by shaders. See the
+/// *validate_grid_inputs* method of *RenderFlowBuilder*.
+#[derive(Debug)]
+pub enum GridInputError {
+    /// The grid was not created with the *ShaderRead* purpose.
+    NotShaderRead { grid: AbstractGridID },
+
+    /// The grid is multisampled, so it can't be sampled. Resolve it into a grid
+    /// with *SampleCount::One* instead.
+    Multisampled {
+        grid: AbstractGridID,
+        sample_count: SampleCount,
+    },
+
+    /// The color grid is sampled with a *SamplerState* that has a *compare_op*,
+    /// which is only allowed for depth stencil grids.
+    ColorCompareOp { grid: AbstractGridID },
 }
+
+impl Display for GridInputError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::NotShaderRead { grid } => write!(
+                f,
+                "Grid {:?} is read by a shader, but it doesn't have the ShaderRead purpose",
+                grid
+            ),
+            Self::Multisampled { grid, sample_count } => write!(
+                f,
+                "Grid {:?} has {} samples per pixel, so it can't be sampled by a shader",
+                grid,
+                sample_count.get_num_samples()
+            ),
+            Self::ColorCompareOp { grid } => write!(
+                f,
+                "Color grid {:?} is sampled with a compare_op, which is only allowed for depth stencil grids",
+                grid
+            ),
+        }
+    }
+}
+
+impl Error for GridInputError {}
@@ -1,20 +1,46 @@
 use std::any::Any;
 
 mod builder;
+mod command;
+mod compute;
+mod export;
+mod indirect;
 mod input;
 mod node;
+mod occlusion;
 mod output;
+mod parallel;
+mod pass;
+mod push_constant;
+mod query;
+mod resolve;
+mod uniform;
+mod viewport;
 
 pub use builder::*;
+pub use command::*;
+pub use compute::*;
+pub use indirect::*;
 pub use input::*;
 pub use node::*;
+pub use occlusion::*;
 pub use output::*;
+pub use parallel::*;
+pub use pass::*;
+pub use push_constant::*;
+pub use query::*;
+pub use resolve::*;
+pub use uniform::*;
+pub use viewport::*;
 
 /// A directed acyclic graph that specifies which graphics pipelines should be
 /// used to draw at which grids at which moments. See the documentation of
 /// *RenderFlowBuilder* for more information.
 ///
+/// Executions of a *RenderFlow* can be measured with GPU queries: see
+/// *FlowQueryOptions* and *FlowQueryReport*.
+///
 /// TODO Add a way to actually use *RenderFlow*s
-pub trait RenderFlow {
+pub trait RenderFlow: Send + Sync {
     fn as_any(&self) -> &dyn Any;
 }
@@ -7,7 +7,7 @@ use std::sync::Arc;
 /// *RenderFlowBuilder* for more information about the nodes.
 ///
 /// TODO Add a way to use it
-pub trait DrawingNode {
+pub trait DrawingNode: Send + Sync {
     fn as_any(&self) -> &dyn Any;
 }
 
@@ -21,19 +21,106 @@ pub trait DrawingNode {
 /// Similarly, this node must have a corresponding *DrawingNodeOutput* link
 /// for each external output variable of the graphics pipeline.
 ///
+/// All grids that a drawing node draws on must have the same size, so they
+/// should have the same *GridSize* (see the *size* of *AbstractColorGridBuilder*).
+///
+/// By default, a drawing node draws on the entire grids. Use the *viewport* and
+/// *scissor* to draw on only a part of the grids instead.
+///
 /// To add the node, use the *add_drawing_node* method of a
 /// *RenderFlowBuilder*.
 pub struct DrawingNodeBuilder {
     pub pipeline: Arc<dyn GraphicsPipeline>,
     pub inputs: Vec<DrawingNodeInput>,
     pub outputs: Vec<DrawingNodeOutput>,
+
+    /// The *Viewport* of this node, or *None* if the viewport should cover the
+    /// entire grids (with the full depth range).
+    pub viewport: Option<Viewport>,
+
+    /// The scissor region of this node, or *None* if there is no scissor test.
+    /// When this is not *None*, only the pixels inside this region will be drawn.
+    pub scissor: Option<GridRegion>,
+}
+
+impl DrawingNodeBuilder {
+    /// Checks if the shaders of this node read the pixels of the grid of the
+    /// given *input* directly. This is the case when the input is bound to a
+    /// *ColorGridInput* or *DepthStencilGridInput* variable of the pipeline. The
+    /// grids of the other *Cleared*, *Internal*, and *External* inputs are grids
+    /// that this node draws on.
+    pub fn reads_grid_pixels(&self, input: &DrawingNodeInput) -> bool {
+        self.pipeline
+            .get_external_variables()
+            .iter()
+            .any(|variable| {
+                variable.get_name() == &input.shader_variable_name
+                    && matches!(
+                        variable.get_variable_type(),
+                        ExternalShaderVariableType::ColorGridInput
+                            | ExternalShaderVariableType::DepthStencilGridInput
+                    )
+            })
+    }
+
+    /// Checks that the *viewport* and *scissor* of this node are valid and lie
+    /// within the bounds of grids with the given *grid_width* and *grid_height*.
+    /// Griphin implementations should call this when they run the node on a
+    /// concrete *GridGroup*, using the size of the grids the node draws on.
+    ///
+    /// If the *debug_level* is *Minimal*, this method won't check anything (and
+    /// always return *Ok*). Otherwise, all checks will be done, since they are
+    /// cheap.
+    pub fn check_bounds(
+        &self,
+        grid_width: u32,
+        grid_height: u32,
+        debug_level: DebugLevel,
+    ) -> Result<(), ViewportError> {
+        if debug_level == DebugLevel::Minimal {
+            return Ok(());
+        }
+
+        if let Some(viewport) = self.viewport {
+            if !(viewport.width > 0.0 && viewport.height > 0.0) {
+                return Err(ViewportError::EmptyViewport { viewport });
+            }
+            let is_valid_depth = |depth: f32| (0.0..=1.0).contains(&depth);
+            if !is_valid_depth(viewport.min_depth) || !is_valid_depth(viewport.max_depth) {
+                return Err(ViewportError::InvalidDepthRange { viewport });
+            }
+            if !(viewport.x >= 0.0
+                && viewport.y >= 0.0
+                && viewport.x + viewport.width <= grid_width as f32
+                && viewport.y + viewport.height <= grid_height as f32)
+            {
+                return Err(ViewportError::ViewportOutOfBounds {
+                    viewport,
+                    grid_width,
+                    grid_height,
+                });
+            }
+        }
+
+        if let Some(scissor) = self.scissor {
+            if !GridRegion::full(grid_width, grid_height).contains(&scissor) {
+                return Err(ViewportError::ScissorOutOfBounds {
+                    scissor,
+                    grid_width,
+                    grid_height,
+                });
+            }
+        }
+
+        Ok(())
+    }
 }
 
 /// Represents a grid node of a *RenderFlow*. See the documentation of
 /// *RenderFlowBuilder* for more information about the nodes.
 ///
 /// TODO Add a way to use it
-pub trait GridNode {
+pub trait GridNode: Send + Sync {
     fn as_any(&self) -> &dyn Any;
 }
 
@@ -86,3 +173,69 @@ pub struct GridNodeID {
     pub(super) flow_id: RenderFlowBuilderID,
     pub(super) own_id: u32,
 }
+
+/// An identifier and 'handle' for a buffer node of a *RenderFlow(Builder)*. A
+/// buffer node represents the content of a storage buffer at a single moment
+/// during the *RenderFlow*, similar to how a grid node represents the content of
+/// a grid. Buffer nodes are used to track the order in which compute nodes and
+/// drawing nodes read and write storage buffers.
+///
+/// Instances of this struct will be returned by the *add_buffer_node* method of
+/// *RenderFlowBuilder*. There is no other way to obtain an instance of this
+/// struct.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BufferNodeID {
+    pub(super) flow_id: RenderFlowBuilderID,
+    pub(super) own_id: u32,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    fn create_node(viewport: Option<Viewport>, scissor: Option<GridRegion>) -> DrawingNodeBuilder {
+        let mut node = create_drawing_node(create_pipeline(Vec::new()), Vec::new(), Vec::new());
+        node.viewport = viewport;
+        node.scissor = scissor;
+        node
+    }
+
+    #[test]
+    fn test_check_bounds() {
+        let level = DebugLevel::Basic;
+        assert!(create_node(None, None).check_bounds(100, 50, level).is_ok());
+
+        // The left half of the grid
+        let node = create_node(
+            Some(Viewport::new(0.0, 0.0, 50.0, 50.0)),
+            Some(GridRegion::new(0, 0, 50, 50)),
+        );
+        assert!(node.check_bounds(100, 50, level).is_ok());
+        assert!(matches!(
+            node.check_bounds(40, 50, level),
+            Err(ViewportError::ViewportOutOfBounds { .. })
+        ));
+
+        let node = create_node(None, Some(GridRegion::new(60, 0, 50, 50)));
+        assert!(matches!(
+            node.check_bounds(100, 50, level),
+            Err(ViewportError::ScissorOutOfBounds { .. })
+        ));
+        assert!(node.check_bounds(100, 50, DebugLevel::Minimal).is_ok());
+
+        let mut viewport = Viewport::full(100, 50);
+        viewport.max_depth = 1.5;
+        assert!(matches!(
+            create_node(Some(viewport), None).check_bounds(100, 50, level),
+            Err(ViewportError::InvalidDepthRange { .. })
+        ));
+
+        let viewport = Viewport::new(10.0, 10.0, 0.0, 5.0);
+        assert!(matches!(
+            create_node(Some(viewport), None).check_bounds(100, 50, level),
+            Err(ViewportError::EmptyViewport { .. })
+        ));
+    }
+}
@@ -0,0 +1,105 @@
+/// Determines what an *OcclusionQuery* counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OcclusionQueryKind {
+    /// The query counts the exact number of samples that passed the depth and
+    /// stencil tests.
+    SamplesPassed,
+
+    /// The query only determines whether *any* sample passed the depth and
+    /// stencil tests. This can be cheaper than *SamplesPassed* on some GPUs.
+    AnySamplesPassed,
+}
+
+/// Counts the samples of a group of draws that pass the depth and stencil tests,
+/// which can be used for occlusion culling: if none of the samples of a bounding
+/// box passed, the object inside it is not visible.
+///
+/// To obtain an *OcclusionQuery*, use the *create_occlusion_query* method of the
+/// *Gateway*. The draws that should be counted must be recorded between the
+/// *begin_occlusion_query* and *end_occlusion_query* methods of a
+/// *DrawCommandRecorder*.
+///
+/// The result is only available after the GPU has executed the draws, which is
+/// typically one or more frames later. Like *VertexBuffer*, this trait has an
+/// *is_ready* and an *await_ready* method to check when that happens. When the
+/// query is used again, the result of the previous use stays available until
+/// the new result is ready.
+pub trait OcclusionQuery: Send + Sync {
+    /// Gets the *OcclusionQueryKind* of this query.
+    fn get_kind(&self) -> OcclusionQueryKind;
+
+    /// Checks if the result of this query is available. This will return
+    /// *false* until the GPU has executed the draws of the first use of this
+    /// query.
+    fn is_ready(&self) -> bool;
+
+    /// Blocks the current thread until the result of this query is available.
+    /// Note that this can take a whole frame, so it's usually better to check
+    /// *is_ready* and use the result of an older frame in the meantime.
+    fn await_ready(&self);
+
+    /// Gets the number of samples that passed, or *None* if the result is not
+    /// available yet. For *AnySamplesPassed* queries, the result is 0 when no
+    /// sample passed, and some positive number otherwise.
+    fn get_samples_passed(&self) -> Option<u64>;
+
+    /// Checks whether any sample passed, or returns *None* if the result is not
+    /// available yet.
+    fn any_samples_passed(&self) -> Option<bool> {
+        self.get_samples_passed().map(|samples| samples > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+    use crate::*;
+
+    use std::sync::Arc;
+
+    #[test]
+    fn test_occlusion_query() {
+        let query = create_query();
+        let other_query = create_query();
+        let mut recorder =
+            DrawCommandRecorder::new(create_pipeline(Vec::new()), DebugLevel::Low, 128);
+
+        assert!(matches!(
+            recorder.end_occlusion_query(),
+            Err(DrawCommandError::NoOcclusionQuery)
+        ));
+        assert!(recorder.begin_occlusion_query(query.clone()).is_ok());
+        assert!(matches!(
+            recorder.begin_occlusion_query(other_query.clone()),
+            Err(DrawCommandError::OcclusionQueryActive)
+        ));
+        assert!(recorder.end_occlusion_query().is_ok());
+        assert!(matches!(
+            recorder.begin_occlusion_query(query.clone()),
+            Err(DrawCommandError::OcclusionQueryReused)
+        ));
+        assert!(recorder.begin_occlusion_query(other_query).is_ok());
+        assert!(matches!(
+            recorder.finish(),
+            Err(DrawCommandError::OcclusionQueryNotEnded)
+        ));
+
+        // At debug level Minimal, the active query is ended by finish
+        let mut recorder =
+            DrawCommandRecorder::new(create_pipeline(Vec::new()), DebugLevel::Minimal, 128);
+        assert!(recorder.begin_occlusion_query(query.clone()).is_ok());
+        let commands = recorder.finish().unwrap();
+        assert_eq!(2, commands.len());
+        assert!(matches!(commands[1], DrawCommand::EndOcclusionQuery));
+
+        assert!(!query.is_ready());
+        assert_eq!(None, query.any_samples_passed());
+        let gpu_query = Arc::clone(&query);
+        let gpu = std::thread::spawn(move || gpu_query.finish(0));
+        query.await_ready();
+        assert_eq!(Some(false), query.any_samples_passed());
+        gpu.join().unwrap();
+    }
+}
@@ -5,8 +5,8 @@ use crate::*;
 /// and the name of the shader variable from which the data will come.
 #[derive(Clone)]
 pub struct DrawingNodeOutput {
-    destination: DrawingNodeOutputDestination,
-    shader_variable_name: StringRef,
+    pub destination: DrawingNodeOutputDestination,
+    pub shader_variable_name: StringRef,
 }
 
 /// This enum specifies what to do with the output data of a drawing node. This can
@@ -0,0 +1,389 @@
+use crate::*;
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+
+/// Collects the *DrawCommand*s of a single execution of a *RenderFlow*, which
+/// can be recorded by multiple threads at the same time.
+///
+/// Every thread should use its own *DrawCommandRecorder*s, and give them to the
+/// *submit* method once they are done. Every submitted recorder gets the index
+/// of the drawing node it recorded commands for (its position in the
+/// *get_drawing_nodes* of the *RenderFlowBuilder*), and a *sequence* number
+/// that determines its position among the other recorders of the same node.
+/// The recorder must use the *GraphicsPipeline* of that drawing node.
+/// The *merge* method sorts the recorded batches by node index and sequence
+/// number, so the merged order doesn't depend on which thread finished first.
+///
+/// For instance, a scene with 10000 objects could be split into 8 chunks, where
+/// chunk *i* is recorded on its own thread and submitted with sequence number
+/// *i*.
+///
+/// Since every *DrawCommandRecorder* starts without any bindings, every batch
+/// must bind its own vertex buffer and variables. Griphin implementations
+/// should execute the batches in the order returned by *merge*.
+pub struct ParallelCommandRecording {
+    node_pipelines: Vec<Arc<dyn GraphicsPipeline>>,
+    debug_level: DebugLevel,
+    batches: Mutex<Vec<DrawCommandBatch>>,
+}
+
+impl ParallelCommandRecording {
+    /// Constructs a new *ParallelCommandRecording* for an execution of a
+    /// *RenderFlow* whose drawing nodes use the given *node_pipelines* (in
+    /// order). If the *debug_level* is at least *Low*, *submit* and *merge* will
+    /// check the node indices, pipelines, and sequence numbers of the submitted
+    /// recorders.
+    pub fn new(node_pipelines: Vec<Arc<dyn GraphicsPipeline>>, debug_level: DebugLevel) -> Self {
+        Self {
+            node_pipelines,
+            debug_level,
+            batches: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Constructs a new *ParallelCommandRecording* for an execution of a
+    /// *RenderFlow* that was created from the given *builder*.
+    pub fn for_flow(builder: &RenderFlowBuilder, debug_level: DebugLevel) -> Self {
+        let node_pipelines = builder
+            .get_drawing_nodes()
+            .iter()
+            .map(|node| Arc::clone(&node.pipeline))
+            .collect();
+        Self::new(node_pipelines, debug_level)
+    }
+
+    /// Gets the *DebugLevel* of this recording.
+    pub fn get_debug_level(&self) -> DebugLevel {
+        self.debug_level
+    }
+
+    /// Submits the commands of the given *recorder* for the drawing node with
+    /// the given *node_index*. This method can be called from any thread. See
+    /// the documentation of this struct for the meaning of *sequence*. If the
+    /// debug level is at least *Low*, this will return an error when the node
+    /// doesn't exist, or when the recorder uses another pipeline than the node.
+    /// It will also return an error when the *finish* method of the recorder
+    /// does.
+    pub fn submit(
+        &self,
+        node_index: usize,
+        sequence: u32,
+        recorder: DrawCommandRecorder,
+    ) -> Result<(), ParallelRecordingError> {
+        if self.debug_level >= DebugLevel::Low {
+            let node_pipeline = match self.node_pipelines.get(node_index) {
+                Some(pipeline) => pipeline,
+                None => {
+                    return Err(ParallelRecordingError::UnknownNode {
+                        node_index,
+                        num_drawing_nodes: self.node_pipelines.len(),
+                    })
+                }
+            };
+            if !Arc::ptr_eq(node_pipeline, recorder.get_pipeline()) {
+                return Err(ParallelRecordingError::PipelineMismatch { node_index });
+            }
+        }
+
+        let commands = recorder
+            .finish()
+            .map_err(|error| ParallelRecordingError::Recorder { node_index, error })?;
+        let batch = DrawCommandBatch {
+            node_index,
+            sequence,
+            commands,
+        };
+        self.batches.lock().unwrap().push(batch);
+        Ok(())
+    }
+
+    /// Gets the number of batches that have been submitted so far.
+    pub fn get_num_batches(&self) -> usize {
+        self.batches.lock().unwrap().len()
+    }
+
+    /// Stops the recording and returns all submitted batches, sorted by node
+    /// index and sequence number. If the *debug_level* is at least *Low*, this
+    /// will return an error when multiple batches have the same node index and
+    /// sequence number (since their order would be ambiguous), or when multiple
+    /// batches use the same *OcclusionQuery* (since a query can be used at most
+    /// once per *RenderFlow* execution).
+    pub fn merge(self) -> Result<Vec<DrawCommandBatch>, ParallelRecordingError> {
+        let mut batches = self.batches.into_inner().unwrap();
+        batches.sort_by_key(|batch| (batch.node_index, batch.sequence));
+
+        if self.debug_level >= DebugLevel::Low {
+            for pair in batches.windows(2) {
+                if pair[0].node_index == pair[1].node_index && pair[0].sequence == pair[1].sequence
+                {
+                    return Err(ParallelRecordingError::DuplicateSequence {
+                        node_index: pair[0].node_index,
+                        sequence: pair[0].sequence,
+                    });
+                }
+            }
+
+            // The recorders already check that they don't reuse their own queries
+            let mut used_queries: Vec<&Arc<dyn OcclusionQuery>> = Vec::new();
+            for batch in &batches {
+                let batch_queries: Vec<_> = batch
+                    .commands
+                    .iter()
+                    .filter_map(|command| match command {
+                        DrawCommand::BeginOcclusionQuery(query) => Some(query),
+                        _ => None,
+                    })
+                    .collect();
+                for query in &batch_queries {
+                    if used_queries.iter().any(|used| Arc::ptr_eq(used, query)) {
+                        return Err(ParallelRecordingError::OcclusionQueryReused {
+                            node_index: batch.node_index,
+                            sequence: batch.sequence,
+                        });
+                    }
+                }
+                used_queries.extend(batch_queries);
+            }
+        }
+
+        Ok(batches)
+    }
+}
+
+/// The commands that were recorded by a single *DrawCommandRecorder* and
+/// submitted to a *ParallelCommandRecording*.
+#[derive(Clone)]
+pub struct DrawCommandBatch {
+    node_index: usize,
+    sequence: u32,
+    commands: Vec<DrawCommand>,
+}
+
+impl DrawCommandBatch {
+    /// Gets the index of the drawing node for which these commands were
+    /// recorded.
+    pub fn get_node_index(&self) -> usize {
+        self.node_index
+    }
+
+    /// Gets the sequence number with which these commands were submitted.
+    pub fn get_sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Gets the recorded commands.
+    pub fn get_commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+}
+
+/// The errors that can be returned by the methods of *ParallelCommandRecording*.
+#[derive(Debug)]
+pub enum ParallelRecordingError {
+    /// The commands were submitted for a drawing node that doesn't exist.
+    UnknownNode {
+        node_index: usize,
+        num_drawing_nodes: usize,
+    },
+
+    /// Multiple batches were submitted for the same drawing node with the same
+    /// sequence number.
+    DuplicateSequence { node_index: usize, sequence: u32 },
+
+    /// The commands were recorded with another *GraphicsPipeline* than the
+    /// pipeline of the drawing node.
+    PipelineMismatch { node_index: usize },
+
+    /// The *finish* method of the recorder that was submitted for the drawing
+    /// node returned an error.
+    Recorder {
+        node_index: usize,
+        error: DrawCommandError,
+    },
+
+    /// The batch with the given node index and sequence number uses an
+    /// *OcclusionQuery* that is also used by a preceding batch.
+    OcclusionQueryReused { node_index: usize, sequence: u32 },
+}
+
+impl Display for ParallelRecordingError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::UnknownNode {
+                node_index,
+                num_drawing_nodes,
+            } => write!(
+                f,
+                "Drawing node {} doesn't exist: the render flow has only {} drawing nodes",
+                node_index, num_drawing_nodes
+            ),
+            Self::DuplicateSequence {
+                node_index,
+                sequence,
+            } => write!(
+                f,
+                "Multiple batches were submitted for drawing node {} with sequence {}",
+                node_index, sequence
+            ),
+            Self::PipelineMismatch { node_index } => write!(
+                f,
+                "The commands for drawing node {} were recorded with another pipeline",
+                node_index
+            ),
+            Self::Recorder { node_index, error } => write!(
+                f,
+                "The recorder for drawing node {} couldn't be finished: {}",
+                node_index, error
+            ),
+            Self::OcclusionQueryReused {
+                node_index,
+                sequence,
+            } => write!(
+                f,
+                "The batch of drawing node {} with sequence {} uses an occlusion query \
+                that was already used by another batch",
+                node_index, sequence
+            ),
+        }
+    }
+}
+
+impl Error for ParallelRecordingError {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    use std::sync::Arc;
+
+    #[test]
+    fn test_parallel_recording() {
+        let pipeline = create_pipeline(Vec::new());
+        let vertex_buffer = create_vertex_buffer(300);
+        let mut builder = RenderFlowBuilder::new(0);
+        for _ in 0..2 {
+            builder.add_drawing_node(create_drawing_node(
+                Arc::clone(&pipeline),
+                Vec::new(),
+                Vec::new(),
+            ));
+        }
+        let recording = ParallelCommandRecording::for_flow(&builder, DebugLevel::Basic);
+
+        // Every thread draws 10 triangles of node 1. Thread 0 also draws on node 0.
+        std::thread::scope(|scope| {
+            for thread_index in 0..4u32 {
+                let pipeline = Arc::clone(&pipeline);
+                let vertex_buffer = Arc::clone(&vertex_buffer);
+                let recording = &recording;
+                scope.spawn(move || {
+                    let mut recorder =
+                        DrawCommandRecorder::new(Arc::clone(&pipeline), DebugLevel::Basic, 128);
+                    recorder.bind_vertex_buffer(vertex_buffer);
+                    for triangle in 0..10 {
+                        let first_vertex = 3 * (10 * thread_index + triangle);
+                        recorder.draw(first_vertex..first_vertex + 3, 0..1).unwrap();
+                    }
+                    let sequence = 3 - thread_index;
+                    recording.submit(1, sequence, recorder).unwrap();
+
+                    if thread_index == 0 {
+                        let recorder = DrawCommandRecorder::new(pipeline, DebugLevel::Basic, 128);
+                        recording.submit(0, 5, recorder).unwrap();
+                    }
+                });
+            }
+        });
+
+        let batches = recording.merge().unwrap();
+        let keys: Vec<_> = batches
+            .iter()
+            .map(|batch| (batch.get_node_index(), batch.get_sequence()))
+            .collect();
+        assert_eq!(vec![(0, 5), (1, 0), (1, 1), (1, 2), (1, 3)], keys);
+
+        // Sequence 0 was recorded by thread 3, so its first draw starts at vertex 90
+        assert!(matches!(
+            &batches[1].get_commands()[1],
+            DrawCommand::Draw { vertices, .. } if vertices.start == 90
+        ));
+        assert_eq!(11, batches[4].get_commands().len());
+    }
+
+    #[test]
+    fn test_validation() {
+        let pipeline = create_pipeline(Vec::new());
+        let new_recorder = || DrawCommandRecorder::new(Arc::clone(&pipeline), DebugLevel::Low, 128);
+
+        let recording = ParallelCommandRecording::new(vec![Arc::clone(&pipeline)], DebugLevel::Low);
+        assert!(matches!(
+            recording.submit(1, 0, new_recorder()),
+            Err(ParallelRecordingError::UnknownNode { .. })
+        ));
+
+        // An equal pipeline is not enough: it must be the same pipeline
+        let other_recorder =
+            DrawCommandRecorder::new(create_pipeline(Vec::new()), DebugLevel::Low, 128);
+        assert!(matches!(
+            recording.submit(0, 0, other_recorder),
+            Err(ParallelRecordingError::PipelineMismatch { node_index: 0 })
+        ));
+        recording.submit(0, 2, new_recorder()).unwrap();
+        recording.submit(0, 2, new_recorder()).unwrap();
+        assert_eq!(2, recording.get_num_batches());
+        assert!(matches!(
+            recording.merge(),
+            Err(ParallelRecordingError::DuplicateSequence {
+                node_index: 0,
+                sequence: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_occlusion_queries() {
+        let pipeline = create_pipeline(Vec::new());
+        let query = create_query();
+        let new_recorder = |query: Option<Arc<DummyQuery>>, end: bool| {
+            let mut recorder =
+                DrawCommandRecorder::new(Arc::clone(&pipeline), DebugLevel::Low, 128);
+            if let Some(query) = query {
+                recorder.begin_occlusion_query(query).unwrap();
+                if end {
+                    recorder.end_occlusion_query().unwrap();
+                }
+            }
+            recorder
+        };
+
+        let recording = ParallelCommandRecording::new(vec![Arc::clone(&pipeline)], DebugLevel::Low);
+        assert!(matches!(
+            recording.submit(0, 0, new_recorder(Some(query.clone()), false)),
+            Err(ParallelRecordingError::Recorder {
+                node_index: 0,
+                error: DrawCommandError::OcclusionQueryNotEnded
+            })
+        ));
+        recording
+            .submit(0, 1, new_recorder(Some(query.clone()), true))
+            .unwrap();
+        recording
+            .submit(0, 2, new_recorder(Some(create_query()), true))
+            .unwrap();
+        recording.submit(0, 3, new_recorder(None, false)).unwrap();
+        recording
+            .submit(0, 4, new_recorder(Some(query), true))
+            .unwrap();
+        assert!(matches!(
+            recording.merge(),
+            Err(ParallelRecordingError::OcclusionQueryReused {
+                node_index: 0,
+                sequence: 4
+            })
+        ));
+    }
+}
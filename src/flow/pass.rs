@@ -0,0 +1,432 @@
+use crate::*;
+
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// A single operation of a *RenderFlow* that accesses grids: a drawing node, a
+/// compute node, or a resolve operation. The index is the position of the node
+/// or resolve operation in the corresponding list of the *RenderFlowBuilder*.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FlowPass {
+    Drawing(usize),
+    Compute(usize),
+    Resolve(usize),
+}
+
+impl RenderFlowBuilder {
+    fn get_default_pass_order(&self) -> Vec<FlowPass> {
+        let drawing = (0..self.get_drawing_nodes().len()).map(FlowPass::Drawing);
+        let compute = (0..self.get_compute_nodes().len()).map(FlowPass::Compute);
+        let resolves = (0..self.get_resolves().len()).map(FlowPass::Resolve);
+        drawing.chain(compute).chain(resolves).collect()
+    }
+
+    pub(super) fn get_written_nodes(&self, pass: FlowPass) -> (Vec<GridNodeID>, Vec<BufferNodeID>) {
+        let internal = |destination: &DrawingNodeOutputDestination| match destination {
+            DrawingNodeOutputDestination::Internal(node) => Some(*node),
+            DrawingNodeOutputDestination::External(_) => None,
+        };
+        match pass {
+            FlowPass::Drawing(index) => {
+                let node = &self.get_drawing_nodes()[index];
+                let grid_nodes = node
+                    .outputs
+                    .iter()
+                    .filter_map(|output| internal(&output.destination));
+                (grid_nodes.collect(), Vec::new())
+            }
+            FlowPass::Compute(index) => {
+                let node = &self.get_compute_nodes()[index];
+                let buffer_nodes = node.outputs.iter().map(|output| output.destination);
+                (Vec::new(), buffer_nodes.collect())
+            }
+            FlowPass::Resolve(index) => {
+                let resolve = &self.get_resolves()[index];
+                (
+                    internal(&resolve.destination).into_iter().collect(),
+                    Vec::new(),
+                )
+            }
+        }
+    }
+
+    pub(super) fn get_read_nodes(&self, pass: FlowPass) -> (Vec<GridNodeID>, Vec<BufferNodeID>) {
+        let mut grid_nodes = Vec::new();
+        let mut buffer_nodes = Vec::new();
+        match pass {
+            FlowPass::Drawing(index) => {
+                for input in &self.get_drawing_nodes()[index].inputs {
+                    match input.source {
+                        DrawingNodeInputSource::Internal(node) => grid_nodes.push(node),
+                        DrawingNodeInputSource::BufferNode(node) => buffer_nodes.push(node),
+                        _ => {}
+                    };
+                }
+            }
+            FlowPass::Compute(index) => {
+                for input in &self.get_compute_nodes()[index].inputs {
+                    match input.source {
+                        ComputeNodeInputSource::GridNode(node) => grid_nodes.push(node),
+                        ComputeNodeInputSource::BufferNode(node) => buffer_nodes.push(node),
+                        _ => {}
+                    };
+                }
+            }
+            FlowPass::Resolve(index) => grid_nodes.push(self.get_resolves()[index].source),
+        };
+        (grid_nodes, buffer_nodes)
+    }
+
+    /// Gets all passes of this *RenderFlowBuilder* in an order in which they
+    /// can be executed: every pass comes after the passes that write the grid
+    /// nodes and buffer nodes it reads. Among the passes that don't depend on
+    /// each other, the drawing nodes come first, followed by the compute nodes
+    /// and the resolve operations, each in the order in which they were added.
+    ///
+    /// Griphin implementations that let grids share memory (see
+    /// *GridAliasingPlan*) must execute the passes in this order, since the
+    /// lifetimes of the grids are based on it. If the graph has a cycle (which
+    /// is not allowed), a *PassCycleError* is returned instead.
+    pub fn get_pass_order(&self) -> Result<Vec<FlowPass>, PassCycleError> {
+        let (order, num_ordered) = self.order_passes();
+        if num_ordered < order.len() {
+            return Err(PassCycleError {
+                passes: order[num_ordered..].to_vec(),
+            });
+        }
+        Ok(order)
+    }
+
+    /// Orders the passes like *get_pass_order*, but appends the passes that
+    /// can't be ordered because of a cycle in the default order. The second
+    /// element of the result is the number of passes that could be ordered.
+    pub(crate) fn order_passes(&self) -> (Vec<FlowPass>, usize) {
+        let passes = self.get_default_pass_order();
+        let written: Vec<_> = passes
+            .iter()
+            .map(|pass| self.get_written_nodes(*pass))
+            .collect();
+
+        let mut dependents = vec![Vec::new(); passes.len()];
+        let mut num_dependencies = vec![0; passes.len()];
+        for (reader, pass) in passes.iter().enumerate() {
+            let (grid_nodes, buffer_nodes) = self.get_read_nodes(*pass);
+            for (writer, (written_grids, written_buffers)) in written.iter().enumerate() {
+                let depends = grid_nodes.iter().any(|node| written_grids.contains(node))
+                    || buffer_nodes
+                        .iter()
+                        .any(|node| written_buffers.contains(node));
+                if depends && writer != reader {
+                    dependents[writer].push(reader);
+                    num_dependencies[reader] += 1;
+                }
+            }
+        }
+
+        let mut ready: BTreeSet<_> = (0..passes.len())
+            .filter(|index| num_dependencies[*index] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(passes.len());
+        let mut is_ordered = vec![false; passes.len()];
+        while let Some(next) = ready.iter().next().copied() {
+            ready.remove(&next);
+            is_ordered[next] = true;
+            order.push(passes[next]);
+            for dependent in &dependents[next] {
+                num_dependencies[*dependent] -= 1;
+                if num_dependencies[*dependent] == 0 {
+                    ready.insert(*dependent);
+                }
+            }
+        }
+
+        let num_ordered = order.len();
+        order.extend(
+            (0..passes.len())
+                .filter(|index| !is_ordered[*index])
+                .map(|index| passes[index]),
+        );
+        (order, num_ordered)
+    }
+
+    /// Checks that the grid nodes of this *RenderFlowBuilder* are linked
+    /// correctly:
+    ///
+    /// - Every grid node that is used must have been added to this builder.
+    /// - Every grid node must be written by at most 1 drawing node or resolve
+    ///   operation.
+    /// - The links must not form a cycle. In particular, a drawing node that
+    ///   reads and writes the same grid needs 2 different grid nodes.
+    ///
+    /// Griphin implementations should use this in their *create_render_flow*
+    /// method.
+    pub fn validate_grid_nodes(&self) -> Result<(), GridNodeError> {
+        let (passes, _) = self.order_passes();
+        for pass in &passes {
+            self.validate_pass_grid_nodes(*pass, &passes)?;
+        }
+        Ok(())
+    }
+
+    /// Does the checks of *validate_grid_nodes* for the grid nodes that are
+    /// read or written by a single *pass*. The *passes* must be the result of
+    /// *order_passes*.
+    pub(crate) fn validate_pass_grid_nodes(
+        &self,
+        pass: FlowPass,
+        passes: &[FlowPass],
+    ) -> Result<(), GridNodeError> {
+        let get_writers = |node: GridNodeID| -> Result<Vec<usize>, GridNodeError> {
+            if self.find_node_grid(node).is_none() {
+                return Err(GridNodeError::UnknownNode { node });
+            }
+            Ok((0..passes.len())
+                .filter(|index| self.get_written_nodes(passes[*index]).0.contains(&node))
+                .collect())
+        };
+        let pass_index = passes.iter().position(|other| *other == pass).unwrap();
+
+        // Only the passes after the first writer are blamed for writing it again
+        for node in self.get_written_nodes(pass).0 {
+            if get_writers(node)?[0] != pass_index {
+                return Err(GridNodeError::MultipleWriters { node });
+            }
+        }
+        for node in self.get_read_nodes(pass).0 {
+            if get_writers(node)?
+                .iter()
+                .any(|writer| *writer >= pass_index)
+            {
+                return Err(GridNodeError::Cycle { node });
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets all grids that are read or written by the given *pass*, including
+    /// the grids of the grid nodes it uses. A grid can occur more than once.
+    pub fn get_pass_grids(&self, pass: FlowPass) -> Vec<AbstractGridID> {
+        let mut grids = Vec::new();
+        match pass {
+            FlowPass::Drawing(index) => {
+                let node = &self.get_drawing_nodes()[index];
+                for input in &node.inputs {
+                    match input.source {
+                        DrawingNodeInputSource::Cleared(grid) => grids.push(grid),
+                        DrawingNodeInputSource::Internal(node) => {
+                            grids.push(self.get_node_grid(node))
+                        }
+                        DrawingNodeInputSource::External(grid) => grids.push(grid),
+                        DrawingNodeInputSource::SampledGrid(grid, _) => grids.push(grid),
+                        _ => {}
+                    };
+                }
+                for output in &node.outputs {
+                    grids.push(self.get_destination_grid(&output.destination));
+                }
+            }
+            FlowPass::Compute(index) => {
+                for input in &self.get_compute_nodes()[index].inputs {
+                    match input.source {
+                        ComputeNodeInputSource::GridNode(node) => {
+                            grids.push(self.get_node_grid(node))
+                        }
+                        ComputeNodeInputSource::ExternalGrid(grid) => grids.push(grid),
+                        _ => {}
+                    };
+                }
+            }
+            FlowPass::Resolve(index) => {
+                let resolve = &self.get_resolves()[index];
+                grids.push(self.get_node_grid(resolve.source));
+                grids.push(self.get_destination_grid(&resolve.destination));
+            }
+        };
+        grids
+    }
+}
+
+/// This error indicates that the passes of a *RenderFlowBuilder* can't be
+/// ordered, because the links between them form a cycle. See the
+/// *get_pass_order* method of *RenderFlowBuilder*.
+#[derive(Debug)]
+pub struct PassCycleError {
+    passes: Vec<FlowPass>,
+}
+
+impl PassCycleError {
+    /// Gets the passes that couldn't be ordered: the passes on the cycle, and
+    /// the passes that depend on them.
+    pub fn get_passes(&self) -> &[FlowPass] {
+        &self.passes
+    }
+}
+
+impl Display for PassCycleError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "The passes {:?} can't be ordered, because their links form a cycle",
+            self.passes
+        )
+    }
+}
+
+impl Error for PassCycleError {}
+
+/// This error indicates that the grid nodes of a *RenderFlowBuilder* are not
+/// linked correctly. See the *validate_grid_nodes* method of
+/// *RenderFlowBuilder*.
+#[derive(Debug)]
+pub enum GridNodeError {
+    /// The grid node was not added to the *RenderFlowBuilder*.
+    UnknownNode { node: GridNodeID },
+
+    /// More than 1 drawing node or resolve operation writes to the grid node.
+    MultipleWriters { node: GridNodeID },
+
+    /// The grid node is read by a pass that has to be executed before (or at
+    /// the same time as) the pass that writes to it.
+    Cycle { node: GridNodeID },
+}
+
+impl Display for GridNodeError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::UnknownNode { node } => write!(
+                f,
+                "Grid node {:?} doesn't belong to this RenderFlowBuilder",
+                node
+            ),
+            Self::MultipleWriters { node } => {
+                write!(f, "Grid node {:?} is written by more than 1 pass", node)
+            }
+            Self::Cycle { node } => write!(
+                f,
+                "Grid node {:?} is read before the pass that writes to it",
+                node
+            ),
+        }
+    }
+}
+
+impl Error for GridNodeError {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    fn create_node(
+        inputs: Vec<DrawingNodeInputSource>,
+        output: DrawingNodeOutputDestination,
+    ) -> DrawingNodeBuilder {
+        create_drawing_node(
+            create_pipeline(Vec::new()),
+            inputs.into_iter().map(|source| (source, "input")).collect(),
+            vec![(output, "outColor")],
+        )
+    }
+
+    #[test]
+    fn test_pass_order() {
+        let ids = GridIDAllocator::new().allocate_ids(3, 0);
+        let scene = ids.colors[0].get_abstract();
+        let multi = ids.colors[1].get_abstract();
+        let display = ids.colors[2].get_abstract();
+
+        // The nodes are added in the wrong order: the post-processing node comes
+        // first, and it reads the grid that is written by the resolve operation.
+        let mut builder = RenderFlowBuilder::new(0);
+        let scene_node = builder.add_grid_node(scene);
+        let multi_node = builder.add_grid_node(multi);
+        builder.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Internal(scene_node)],
+            DrawingNodeOutputDestination::External(display),
+        ));
+        builder.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Model],
+            DrawingNodeOutputDestination::Internal(multi_node),
+        ));
+        builder.add_resolve(ResolveBuilder {
+            source: multi_node,
+            destination: DrawingNodeOutputDestination::Internal(scene_node),
+        });
+
+        assert_eq!(
+            vec![
+                FlowPass::Drawing(1),
+                FlowPass::Resolve(0),
+                FlowPass::Drawing(0)
+            ],
+            builder.get_pass_order().unwrap()
+        );
+        assert!(builder.validate_grid_nodes().is_ok());
+        assert_eq!(
+            vec![multi, scene],
+            builder.get_pass_grids(FlowPass::Resolve(0))
+        );
+        assert_eq!(
+            vec![scene, display],
+            builder.get_pass_grids(FlowPass::Drawing(0))
+        );
+    }
+
+    #[test]
+    fn test_validate_grid_nodes() {
+        let ids = GridIDAllocator::new().allocate_ids(2, 0);
+        let scene = ids.colors[0].get_abstract();
+        let display = ids.colors[1].get_abstract();
+
+        let mut builder = RenderFlowBuilder::new(0);
+        let scene_node = builder.add_grid_node(scene);
+        builder.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Model],
+            DrawingNodeOutputDestination::Internal(scene_node),
+        ));
+        builder.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Model],
+            DrawingNodeOutputDestination::Internal(scene_node),
+        ));
+        assert!(matches!(
+            builder.validate_grid_nodes(),
+            Err(GridNodeError::MultipleWriters { node }) if node == scene_node
+        ));
+
+        // Two post-processing nodes that read each other's output
+        let mut builder = RenderFlowBuilder::new(0);
+        let first_node = builder.add_grid_node(scene);
+        let second_node = builder.add_grid_node(display);
+        builder.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Internal(first_node)],
+            DrawingNodeOutputDestination::Internal(second_node),
+        ));
+        builder.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Internal(second_node)],
+            DrawingNodeOutputDestination::Internal(first_node),
+        ));
+        assert!(matches!(
+            builder.validate_grid_nodes(),
+            Err(GridNodeError::Cycle { .. })
+        ));
+        let error = builder.get_pass_order().unwrap_err();
+        assert_eq!(
+            &[FlowPass::Drawing(0), FlowPass::Drawing(1)],
+            error.get_passes()
+        );
+
+        let mut other_builder = RenderFlowBuilder::new(1);
+        let foreign_node = other_builder.add_grid_node(scene);
+        let mut builder = RenderFlowBuilder::new(0);
+        builder.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Internal(foreign_node)],
+            DrawingNodeOutputDestination::External(display),
+        ));
+        assert!(matches!(
+            builder.validate_grid_nodes(),
+            Err(GridNodeError::UnknownNode { .. })
+        ));
+    }
+}
@@ -0,0 +1,208 @@
+use crate::*;
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Describes where the *PushConstant* variables of a *GraphicsPipeline* are
+/// stored in its push constant block. The variables are laid out according to
+/// the *std430* rules, in the order in which they appear in the external
+/// variables of the pipeline: every variable starts at the first offset after
+/// the previous variable that is a multiple of its *std430* alignment.
+///
+/// Griphin implementations should use this layout when they generate the
+/// push constant block of the shaders, and when they execute *SetPushConstant*
+/// commands.
+#[derive(Clone, Debug)]
+pub struct PushConstantLayout {
+    ranges: Vec<PushConstantRange>,
+    size: usize,
+}
+
+/// The location of a single *PushConstant* variable within a
+/// *PushConstantLayout*.
+#[derive(Clone, Debug)]
+pub struct PushConstantRange {
+    pub name: StringRef,
+    pub data_type: DataType,
+    pub offset: usize,
+}
+
+impl PushConstantLayout {
+    /// Computes the layout of the *PushConstant* variables among the given
+    /// external *variables*. All other variables are ignored.
+    pub fn new(variables: &[ExternalShaderVariable]) -> Self {
+        let mut ranges = Vec::new();
+        let mut size = 0usize;
+        for variable in variables {
+            if variable.get_variable_type() != ExternalShaderVariableType::PushConstant {
+                continue;
+            }
+
+            let shape = variable.get_data_type().get_shape();
+            let alignment = shape.get_std430_alignment();
+            let offset = size.div_ceil(alignment) * alignment;
+            ranges.push(PushConstantRange {
+                name: variable.get_name().clone(),
+                data_type: variable.get_data_type(),
+                offset,
+            });
+            size = offset + shape.get_std430_size();
+        }
+        Self { ranges, size }
+    }
+
+    /// Gets the locations of all *PushConstant* variables, in declaration order.
+    pub fn get_ranges(&self) -> &[PushConstantRange] {
+        &self.ranges
+    }
+
+    /// Gets the location of the *PushConstant* variable with the given *name*,
+    /// or *None* if there is no such variable.
+    pub fn get_range(&self, name: &str) -> Option<&PushConstantRange> {
+        self.ranges.iter().find(|range| range.name == name)
+    }
+
+    /// Gets the total size of the push constant block, in bytes.
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    /// Checks that the push constant block fits in *max_size* bytes. The
+    /// *DrawCommandRecorder* does this with the maximum push constant size that
+    /// was given to its *new* function when its debug level is at least *Low*.
+    pub fn check_size(&self, max_size: usize) -> Result<(), PushConstantSizeError> {
+        if self.size > max_size {
+            Err(PushConstantSizeError {
+                size: self.size,
+                max_size,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// This error indicates that the *PushConstant* variables of a pipeline need
+/// more space than the *Instance* supports. See the *check_size* method of
+/// *PushConstantLayout*.
+#[derive(Debug)]
+pub struct PushConstantSizeError {
+    pub size: usize,
+    pub max_size: usize,
+}
+
+impl Display for PushConstantSizeError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "The push constants need {} bytes, but at most {} bytes are supported",
+            self.size, self.max_size
+        )
+    }
+}
+
+impl Error for PushConstantSizeError {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    use cgmath::{Matrix4, SquareMatrix, Vector3};
+    use std::sync::Arc;
+
+    fn variables() -> Vec<ExternalShaderVariable> {
+        let push_constant =
+            |name, data_type| variable(name, data_type, ExternalShaderVariableType::PushConstant);
+        vec![
+            push_constant("materialIndex", DataType::new(INT, SINGLE)),
+            variable(
+                "tint",
+                DataType::new(FLOAT, VEC4),
+                ExternalShaderVariableType::UniformInput,
+            ),
+            push_constant("offset", DataType::new(FLOAT, VEC3)),
+            push_constant("scale", DataType::new(FLOAT, SINGLE)),
+            push_constant("model", DataType::new(FLOAT, MAT4)),
+        ]
+    }
+
+    #[test]
+    fn test_layout() {
+        let layout = PushConstantLayout::new(&variables());
+        let offsets: Vec<_> = layout
+            .get_ranges()
+            .iter()
+            .map(|range| (range.name.to_str(), range.offset))
+            .collect();
+        assert_eq!(
+            vec![
+                ("materialIndex", 0),
+                ("offset", 16),
+                ("scale", 28),
+                ("model", 32)
+            ],
+            offsets
+        );
+        assert_eq!(96, layout.get_size());
+        assert!(layout.get_range("tint").is_none());
+        assert!(layout.check_size(128).is_ok());
+        assert!(layout.check_size(64).is_err());
+    }
+
+    #[test]
+    fn test_set_push_constant() {
+        let pipeline = create_pipeline(variables());
+        let mut recorder = DrawCommandRecorder::new(pipeline, DebugLevel::Low, 128);
+        assert!(recorder
+            .set_push_constant(&str_ref("model"), Matrix4::identity())
+            .is_ok());
+        assert!(recorder
+            .set_push_constant(&str_ref("offset"), Vector3::new(1.0, 2.0, 3.0))
+            .is_ok());
+        assert!(matches!(
+            recorder.set_push_constant(&str_ref("materialIndex"), 1.0),
+            Err(DrawCommandError::DataTypeMismatch(_))
+        ));
+        assert!(matches!(
+            recorder.set_push_constant(&str_ref("tint"), ColorF32::WHITE),
+            Err(DrawCommandError::WrongVariableType { .. })
+        ));
+
+        match &recorder.get_commands()[1] {
+            DrawCommand::SetPushConstant { offset, value, .. } => {
+                assert_eq!(16, *offset);
+                assert_eq!(12, value.to_std430_bytes().len());
+            }
+            _ => panic!("Expected a SetPushConstant command"),
+        }
+
+        let identity_bytes = UniformValue::from(Matrix4::<f32>::identity()).to_std430_bytes();
+        assert_eq!(64, identity_bytes.len());
+        assert_eq!(1.0f32.to_ne_bytes(), identity_bytes[20..24]);
+    }
+
+    #[test]
+    fn test_max_push_constant_size() {
+        let pipeline = create_pipeline(variables());
+        let mut recorder = DrawCommandRecorder::new(Arc::clone(&pipeline), DebugLevel::Low, 64);
+        recorder.bind_vertex_buffer(create_vertex_buffer(3));
+        assert!(matches!(
+            recorder.set_push_constant(&str_ref("scale"), 2.0),
+            Err(DrawCommandError::PushConstantSize(PushConstantSizeError {
+                size: 96,
+                max_size: 64
+            }))
+        ));
+        assert!(matches!(
+            recorder.draw(0..3, 0..1),
+            Err(DrawCommandError::PushConstantSize(_))
+        ));
+
+        // The size is not checked at debug level Minimal
+        let mut recorder = DrawCommandRecorder::new(pipeline, DebugLevel::Minimal, 64);
+        recorder.bind_vertex_buffer(create_vertex_buffer(3));
+        assert!(recorder.draw(0..3, 0..1).is_ok());
+    }
+}
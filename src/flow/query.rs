@@ -0,0 +1,397 @@
+use crate::*;
+
+use std::collections::BTreeMap;
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::time::Duration;
+
+/// Specifies which queries should be done during an execution of a *RenderFlow*.
+/// Queries are disabled by default, since they can have a small performance
+/// cost. The results are delivered in a *FlowQueryReport*.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct FlowQueryOptions {
+    /// Whether the GPU should record a timestamp at the start and end of every
+    /// drawing node.
+    pub timestamps: bool,
+
+    /// Whether the GPU should count the *PipelineStatistics* of every drawing
+    /// node.
+    pub pipeline_statistics: bool,
+}
+
+impl FlowQueryOptions {
+    /// The *FlowQueryOptions* that enable all queries.
+    pub const ALL: FlowQueryOptions = FlowQueryOptions {
+        timestamps: true,
+        pipeline_statistics: true,
+    };
+
+    /// Checks whether any query is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.timestamps || self.pipeline_statistics
+    }
+}
+
+/// The time at which the GPU started and finished a drawing node. The values
+/// are in nanoseconds, relative to some implementation-specific moment, so
+/// only differences between them are meaningful.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeTiming {
+    pub start_nanos: u64,
+    pub end_nanos: u64,
+}
+
+impl NodeTiming {
+    /// Gets the time the GPU spent between the start and end of the node.
+    pub fn get_duration(&self) -> Duration {
+        Duration::from_nanos(self.end_nanos.saturating_sub(self.start_nanos))
+    }
+}
+
+/// Counters of the work that the GPU did during a drawing node (or during an
+/// entire *RenderFlow* execution). Every counter is *None* when the Griphin
+/// implementation can't determine it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct PipelineStatistics {
+    /// The number of vertices that were read by the input assembler. For
+    /// indexed draws, this is the number of indices.
+    pub input_vertices: Option<u64>,
+
+    /// The number of primitives (points, lines, or triangles) that were
+    /// assembled from the input vertices.
+    pub input_primitives: Option<u64>,
+
+    /// The number of times the vertex shader was invoked. This can be smaller
+    /// than *input_vertices*, since GPUs can reuse the results of indexed
+    /// vertices.
+    pub vertex_shader_invocations: Option<u64>,
+
+    /// The number of times the fragment shader was invoked.
+    pub fragment_shader_invocations: Option<u64>,
+}
+
+impl PipelineStatistics {
+    /// Computes the counters that follow directly from the given *commands*
+    /// (which must have been recorded for a pipeline with the given *topology*):
+    /// *input_vertices* and (when the topology allows it) *input_primitives*.
+    /// The other counters will be *None*. Software implementations can use this
+    /// to fill in the exact counters, and fill in the remaining counters
+    /// themselves.
+    ///
+    /// Indirect draws can't be counted, so *input_vertices* and
+    /// *input_primitives* will be *None* if *commands* contains any. The
+    /// counters saturate at *u64::MAX* rather than overflowing.
+    pub fn from_commands(commands: &[DrawCommand], topology: PrimitiveTopology) -> Self {
+        let mut input_vertices = Some(0u64);
+        let mut input_primitives = Some(0u64);
+        for command in commands {
+            let (num_vertices, num_instances, indexed) = match command {
+                DrawCommand::Draw {
+                    vertices,
+                    instances,
+                } => (vertices.len() as u64, instances.len() as u64, false),
+                DrawCommand::DrawIndexed {
+                    indices, instances, ..
+                } => (indices.len() as u64, instances.len() as u64, true),
+                DrawCommand::DrawIndirect(_) | DrawCommand::DrawIndexedIndirect(_) => {
+                    input_vertices = None;
+                    input_primitives = None;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            input_vertices = input_vertices
+                .map(|total| total.saturating_add(num_vertices.saturating_mul(num_instances)));
+            let num_primitives = count_primitives(num_vertices, topology, indexed);
+            input_primitives = match (input_primitives, num_primitives) {
+                (Some(total), Some(num_primitives)) => {
+                    Some(total.saturating_add(num_primitives.saturating_mul(num_instances)))
+                }
+                _ => None,
+            };
+        }
+
+        Self {
+            input_vertices,
+            input_primitives,
+            vertex_shader_invocations: None,
+            fragment_shader_invocations: None,
+        }
+    }
+
+    /// Adds the counters of *other* to the counters of this *PipelineStatistics*.
+    /// A counter becomes *None* if it is *None* in either of them, and saturates
+    /// at *u64::MAX* rather than overflowing.
+    pub fn add(&mut self, other: &PipelineStatistics) {
+        let add = |a: Option<u64>, b: Option<u64>| Some(a?.saturating_add(b?));
+        self.input_vertices = add(self.input_vertices, other.input_vertices);
+        self.input_primitives = add(self.input_primitives, other.input_primitives);
+        self.vertex_shader_invocations = add(
+            self.vertex_shader_invocations,
+            other.vertex_shader_invocations,
+        );
+        self.fragment_shader_invocations = add(
+            self.fragment_shader_invocations,
+            other.fragment_shader_invocations,
+        );
+    }
+}
+
+// The number of primitives of indexed strips can't be computed without reading
+// the indices, since they can contain primitive restarts.
+fn count_primitives(num_vertices: u64, topology: PrimitiveTopology, indexed: bool) -> Option<u64> {
+    match topology {
+        PrimitiveTopology::Points => Some(num_vertices),
+        PrimitiveTopology::Lines => Some(num_vertices / 2),
+        PrimitiveTopology::Triangles => Some(num_vertices / 3),
+        PrimitiveTopology::LineStrips if !indexed => Some(num_vertices.saturating_sub(1)),
+        PrimitiveTopology::TriangleStrips if !indexed => Some(num_vertices.saturating_sub(2)),
+        _ => None,
+    }
+}
+
+/// The results of the queries of a single *RenderFlow* execution. The results
+/// are keyed by the index of the drawing node (its position in the
+/// *get_drawing_nodes* of the *RenderFlowBuilder*). Nodes that were not
+/// executed, or whose queries were not enabled, don't have results.
+#[derive(Clone, Debug, Default)]
+pub struct FlowQueryReport {
+    timings: BTreeMap<usize, NodeTiming>,
+    statistics: BTreeMap<usize, PipelineStatistics>,
+}
+
+impl FlowQueryReport {
+    /// Constructs an empty *FlowQueryReport*. This should only be used by
+    /// Griphin implementations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the *NodeTiming* of the drawing node with the given index. This
+    /// should only be used by Griphin implementations.
+    pub fn set_node_timing(&mut self, node_index: usize, timing: NodeTiming) {
+        self.timings.insert(node_index, timing);
+    }
+
+    /// Sets the *PipelineStatistics* of the drawing node with the given index.
+    /// This should only be used by Griphin implementations.
+    pub fn set_node_statistics(&mut self, node_index: usize, statistics: PipelineStatistics) {
+        self.statistics.insert(node_index, statistics);
+    }
+
+    /// Gets the *NodeTiming* of the drawing node with the given index.
+    pub fn get_node_timing(&self, node_index: usize) -> Option<NodeTiming> {
+        self.timings.get(&node_index).copied()
+    }
+
+    /// Gets the *PipelineStatistics* of the drawing node with the given index.
+    pub fn get_node_statistics(&self, node_index: usize) -> Option<PipelineStatistics> {
+        self.statistics.get(&node_index).copied()
+    }
+
+    /// Gets the timings of all drawing nodes that have one, ordered by node
+    /// index.
+    pub fn get_node_timings(&self) -> impl Iterator<Item = (usize, NodeTiming)> + '_ {
+        self.timings.iter().map(|(index, timing)| (*index, *timing))
+    }
+
+    /// Gets the time between the start of the first timed node and the end of
+    /// the last timed node, or *None* if no node was timed.
+    pub fn get_total_duration(&self) -> Option<Duration> {
+        let start = self
+            .timings
+            .values()
+            .map(|timing| timing.start_nanos)
+            .min()?;
+        let end = self.timings.values().map(|timing| timing.end_nanos).max()?;
+        Some(
+            NodeTiming {
+                start_nanos: start,
+                end_nanos: end,
+            }
+            .get_duration(),
+        )
+    }
+
+    /// Gets the sum of the *PipelineStatistics* of all drawing nodes, or *None*
+    /// if pipeline statistics were not queried.
+    pub fn get_total_statistics(&self) -> Option<PipelineStatistics> {
+        let mut values = self.statistics.values();
+        let mut total = *values.next()?;
+        for statistics in values {
+            total.add(statistics);
+        }
+        Some(total)
+    }
+}
+
+/// Creates a *FlowQueryReportSender* and the corresponding
+/// *PendingFlowQueryReport*. Griphin implementations should give the pending
+/// report to the user when a *RenderFlow* is executed with queries, and use the
+/// sender once the GPU has finished the execution.
+pub fn create_flow_query_report() -> (FlowQueryReportSender, PendingFlowQueryReport) {
+    let (sender, receiver) = channel();
+    (
+        FlowQueryReportSender { sender },
+        PendingFlowQueryReport {
+            receiver,
+            report: None,
+        },
+    )
+}
+
+/// The sending side of a *PendingFlowQueryReport*. See
+/// *create_flow_query_report*.
+pub struct FlowQueryReportSender {
+    sender: Sender<FlowQueryReport>,
+}
+
+impl FlowQueryReportSender {
+    /// Delivers the *report* to the *PendingFlowQueryReport*. If the pending
+    /// report was already dropped, the report is silently discarded.
+    pub fn send(self, report: FlowQueryReport) {
+        let _ = self.sender.send(report);
+    }
+}
+
+/// A *FlowQueryReport* that may not be available yet, because the GPU has not
+/// finished the *RenderFlow* execution. Use *poll* to check whether it is
+/// available without blocking, or *wait* to block until it is available.
+pub struct PendingFlowQueryReport {
+    receiver: Receiver<FlowQueryReport>,
+    report: Option<FlowQueryReport>,
+}
+
+impl PendingFlowQueryReport {
+    /// Gets the report if it is available, or *None* if the GPU is not done yet.
+    /// Also returns *None* if the Griphin implementation dropped the sender
+    /// without sending a report (for instance because the device was lost).
+    pub fn poll(&mut self) -> Option<&FlowQueryReport> {
+        if self.report.is_none() {
+            match self.receiver.try_recv() {
+                Ok(report) => self.report = Some(report),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => {}
+            }
+        }
+        self.report.as_ref()
+    }
+
+    /// Blocks until the report is available, and returns it. Returns *None* if
+    /// the Griphin implementation dropped the sender without sending a report.
+    pub fn wait(self) -> Option<FlowQueryReport> {
+        match self.report {
+            Some(report) => Some(report),
+            None => self.receiver.recv().ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_statistics_from_commands() {
+        let commands = vec![
+            DrawCommand::Draw {
+                vertices: 0..30,
+                instances: 0..2,
+            },
+            DrawCommand::DrawIndexed {
+                indices: 6..12,
+                instances: 0..1,
+                vertex_offset: 0,
+            },
+        ];
+        let statistics = PipelineStatistics::from_commands(&commands, PrimitiveTopology::Triangles);
+        assert_eq!(Some(66), statistics.input_vertices);
+        assert_eq!(Some(22), statistics.input_primitives);
+        assert_eq!(None, statistics.fragment_shader_invocations);
+
+        let strips =
+            PipelineStatistics::from_commands(&commands, PrimitiveTopology::TriangleStrips);
+        assert_eq!(Some(66), strips.input_vertices);
+        assert_eq!(None, strips.input_primitives);
+        let strips =
+            PipelineStatistics::from_commands(&commands[..1], PrimitiveTopology::TriangleStrips);
+        assert_eq!(Some(56), strips.input_primitives);
+
+        let huge_draw = DrawCommand::Draw {
+            vertices: 0..u32::MAX,
+            instances: 0..u32::MAX,
+        };
+        let mut statistics = PipelineStatistics::from_commands(
+            &[huge_draw.clone(), huge_draw],
+            PrimitiveTopology::Points,
+        );
+        assert_eq!(Some(u64::MAX), statistics.input_vertices);
+        assert_eq!(Some(u64::MAX), statistics.input_primitives);
+        statistics.add(&PipelineStatistics::from_commands(
+            &commands,
+            PrimitiveTopology::Points,
+        ));
+        assert_eq!(Some(u64::MAX), statistics.input_vertices);
+    }
+
+    #[test]
+    fn test_report() {
+        let (sender, mut pending) = create_flow_query_report();
+        assert!(pending.poll().is_none());
+
+        let mut report = FlowQueryReport::new();
+        report.set_node_timing(
+            2,
+            NodeTiming {
+                start_nanos: 5000,
+                end_nanos: 8000,
+            },
+        );
+        report.set_node_timing(
+            0,
+            NodeTiming {
+                start_nanos: 1000,
+                end_nanos: 4000,
+            },
+        );
+        let statistics = PipelineStatistics {
+            input_vertices: Some(30),
+            input_primitives: Some(10),
+            vertex_shader_invocations: None,
+            fragment_shader_invocations: Some(100),
+        };
+        report.set_node_statistics(0, statistics);
+        report.set_node_statistics(2, statistics);
+
+        std::thread::spawn(move || sender.send(report))
+            .join()
+            .unwrap();
+        let report = pending.poll().unwrap();
+        assert_eq!(
+            Duration::from_nanos(3000),
+            report.get_node_timing(2).unwrap().get_duration()
+        );
+        assert!(report.get_node_timing(1).is_none());
+        assert_eq!(
+            vec![0, 2],
+            report
+                .get_node_timings()
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(Duration::from_nanos(7000)),
+            report.get_total_duration()
+        );
+
+        let total = report.get_total_statistics().unwrap();
+        assert_eq!(Some(60), total.input_vertices);
+        assert_eq!(None, total.vertex_shader_invocations);
+        assert_eq!(Some(200), total.fragment_shader_invocations);
+        assert!(pending.wait().is_some());
+
+        let (sender, pending) = create_flow_query_report();
+        drop(sender);
+        assert!(pending.wait().is_none());
+    }
+}
@@ -0,0 +1,145 @@
+use crate::*;
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// This struct contains the information needed to add a resolve operation to a
+/// *RenderFlowBuilder*. A resolve operation combines the samples of every pixel
+/// of a multisampled color grid into a single color, and stores the result in a
+/// color grid with *SampleCount::One*.
+///
+/// The *source* must be a grid node of a multisampled color grid, and the
+/// *destination* must be a color grid with *SampleCount::One* and the same
+/// *ColorFormat* and *GridSize*. This is typically used to resolve a multisampled grid into a
+/// grid with *ColorPurpose::Display*, using an *External* destination.
+///
+/// To add the resolve operation, use the *add_resolve* method of a
+/// *RenderFlowBuilder*.
+#[derive(Clone)]
+pub struct ResolveBuilder {
+    pub source: GridNodeID,
+    pub destination: DrawingNodeOutputDestination,
+}
+
+/// This error indicates that the sample counts of the pipelines, grids, and
+/// resolve operations of a *RenderFlowBuilder* don't match. See the
+/// *validate_sample_counts* method of *RenderFlowBuilder*.
+#[derive(Debug)]
+pub enum SampleCountError {
+    /// A drawing node uses a *GraphicsPipeline* with a different *SampleCount*
+    /// than one of the grids it draws on.
+    PipelineMismatch {
+        pipeline_sample_count: SampleCount,
+        grid: AbstractGridID,
+        grid_sample_count: SampleCount,
+    },
+
+    /// The source grid of a resolve operation is not multisampled.
+    SourceNotMultisampled { grid: AbstractGridID },
+
+    /// The destination grid of a resolve operation is multisampled.
+    DestinationMultisampled {
+        grid: AbstractGridID,
+        sample_count: SampleCount,
+    },
+
+    /// The source grid or destination grid of a resolve operation is not a color
+    /// grid.
+    NotColorGrid { grid: AbstractGridID },
+
+    /// The source grid and destination grid of a resolve operation have a
+    /// different *ColorFormat*.
+    FormatMismatch {
+        source: AbstractGridID,
+        source_format: ColorFormat,
+        destination: AbstractGridID,
+        destination_format: ColorFormat,
+    },
+
+    /// A drawing node draws on two grids with a different *GridSize*, or the
+    /// source grid and destination grid of a resolve operation have a different
+    /// *GridSize*.
+    SizeMismatch {
+        first_grid: AbstractGridID,
+        first_size: GridSize,
+        second_grid: AbstractGridID,
+        second_size: GridSize,
+    },
+
+    /// A grid node doesn't belong to the *RenderFlowBuilder*.
+    UnknownGridNode { node: GridNodeID },
+
+    /// A color grid with the *Display* purpose is multisampled. See the
+    /// *validate_sample_counts* method of *AbstractGridGroupBuilder*.
+    DisplayMultisampled {
+        grid: AbstractGridID,
+        sample_count: SampleCount,
+    },
+}
+
+impl Display for SampleCountError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::PipelineMismatch {
+                pipeline_sample_count,
+                grid,
+                grid_sample_count,
+            } => write!(
+                f,
+                "A pipeline with {} samples per pixel can't draw on grid {:?} with {} samples per pixel",
+                pipeline_sample_count.get_num_samples(),
+                grid,
+                grid_sample_count.get_num_samples()
+            ),
+            Self::SourceNotMultisampled { grid } => write!(
+                f,
+                "The source grid {:?} of a resolve operation is not multisampled",
+                grid
+            ),
+            Self::DestinationMultisampled { grid, sample_count } => write!(
+                f,
+                "The destination grid {:?} of a resolve operation has {} samples per pixel, but it must have 1",
+                grid,
+                sample_count.get_num_samples()
+            ),
+            Self::NotColorGrid { grid } => write!(
+                f,
+                "Grid {:?} of a resolve operation is not a color grid",
+                grid
+            ),
+            Self::FormatMismatch {
+                source,
+                source_format,
+                destination,
+                destination_format,
+            } => write!(
+                f,
+                "Can't resolve grid {:?} with format {:?} into grid {:?} with format {:?}",
+                source, source_format, destination, destination_format
+            ),
+            Self::SizeMismatch {
+                first_grid,
+                first_size,
+                second_grid,
+                second_size,
+            } => write!(
+                f,
+                "Grid {:?} with size {:?} and grid {:?} with size {:?} must have the same size",
+                first_grid, first_size, second_grid, second_size
+            ),
+            Self::UnknownGridNode { node } => write!(
+                f,
+                "The grid node {:?} doesn't belong to this RenderFlowBuilder",
+                node
+            ),
+            Self::DisplayMultisampled { grid, sample_count } => write!(
+                f,
+                "Grid {:?} has the Display purpose and {} samples per pixel, but it must have 1",
+                grid,
+                sample_count.get_num_samples()
+            ),
+        }
+    }
+}
+
+impl Error for SampleCountError {}
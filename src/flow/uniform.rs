@@ -0,0 +1,149 @@
+use crate::*;
+
+use cgmath::{Matrix2, Matrix3, Matrix4, Vector2, Vector3, Vector4};
+
+/// A value that can be bound to a shader variable of type *UniformInput*, using
+/// the *bind_uniform* method of a *DrawCommandRecorder*. Every possibility
+/// corresponds to exactly one *DataType* (see *get_data_type*), which must match
+/// the *DataType* of the shader variable.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UniformValue {
+    Int(i32),
+    Uint(u32),
+    Float(f32),
+    Bool(bool),
+    Vec2(Vector2<f32>),
+    Vec3(Vector3<f32>),
+    Vec4(Vector4<f32>),
+    IntVec2(Vector2<i32>),
+    IntVec3(Vector3<i32>),
+    IntVec4(Vector4<i32>),
+    Mat2(Matrix2<f32>),
+    Mat3(Matrix3<f32>),
+    Mat4(Matrix4<f32>),
+}
+
+impl UniformValue {
+    /// Gets the *DataType* of this value. This must be the *DataType* of the
+    /// shader variable that this value is bound to.
+    pub fn get_data_type(&self) -> DataType {
+        match self {
+            Self::Int(_) => DataType::new(INT, SINGLE),
+            Self::Uint(_) => DataType::new(UINT, SINGLE),
+            Self::Float(_) => DataType::new(FLOAT, SINGLE),
+            Self::Bool(_) => DataType::new(BOOL, SINGLE),
+            Self::Vec2(_) => DataType::new(FLOAT, VEC2),
+            Self::Vec3(_) => DataType::new(FLOAT, VEC3),
+            Self::Vec4(_) => DataType::new(FLOAT, VEC4),
+            Self::IntVec2(_) => DataType::new(INT, VEC2),
+            Self::IntVec3(_) => DataType::new(INT, VEC3),
+            Self::IntVec4(_) => DataType::new(INT, VEC4),
+            Self::Mat2(_) => DataType::new(FLOAT, MAT2),
+            Self::Mat3(_) => DataType::new(FLOAT, MAT3),
+            Self::Mat4(_) => DataType::new(FLOAT, MAT4),
+        }
+    }
+
+    /// Converts this value to bytes, laid out according to the *std430* rules
+    /// (like the elements of a *StorageBufferStore*). The length of the result
+    /// is the *std430* size of the *DataShape* of this value.
+    pub fn to_std430_bytes(&self) -> Vec<u8> {
+        let columns: Vec<Vec<[u8; 4]>> = match *self {
+            Self::Int(value) => vec![vec![value.to_ne_bytes()]],
+            Self::Uint(value) => vec![vec![value.to_ne_bytes()]],
+            Self::Float(value) => vec![vec![value.to_ne_bytes()]],
+            Self::Bool(value) => vec![vec![(value as i32).to_ne_bytes()]],
+            Self::Vec2(value) => vec![float_column(value.into())],
+            Self::Vec3(value) => vec![float_column(value.into())],
+            Self::Vec4(value) => vec![float_column(value.into())],
+            Self::IntVec2(value) => vec![int_column(value.into())],
+            Self::IntVec3(value) => vec![int_column(value.into())],
+            Self::IntVec4(value) => vec![int_column(value.into())],
+            Self::Mat2(matrix) => {
+                let columns: [[f32; 2]; 2] = matrix.into();
+                columns.iter().map(|column| float_column(*column)).collect()
+            }
+            Self::Mat3(matrix) => {
+                let columns: [[f32; 3]; 3] = matrix.into();
+                columns.iter().map(|column| float_column(*column)).collect()
+            }
+            Self::Mat4(matrix) => {
+                let columns: [[f32; 4]; 4] = matrix.into();
+                columns.iter().map(|column| float_column(*column)).collect()
+            }
+        };
+
+        let shape = self.get_data_type().get_shape();
+        let column_stride = shape.get_std430_alignment();
+        let mut bytes = vec![0; shape.get_std430_size()];
+        for (column_index, column) in columns.iter().enumerate() {
+            for (row_index, value) in column.iter().enumerate() {
+                let offset = column_index * column_stride + 4 * row_index;
+                bytes[offset..offset + 4].copy_from_slice(value);
+            }
+        }
+        bytes
+    }
+}
+
+fn float_column<const N: usize>(values: [f32; N]) -> Vec<[u8; 4]> {
+    values.iter().map(|value| value.to_ne_bytes()).collect()
+}
+
+fn int_column<const N: usize>(values: [i32; N]) -> Vec<[u8; 4]> {
+    values.iter().map(|value| value.to_ne_bytes()).collect()
+}
+
+impl From<i32> for UniformValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<u32> for UniformValue {
+    fn from(value: u32) -> Self {
+        Self::Uint(value)
+    }
+}
+
+impl From<f32> for UniformValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<bool> for UniformValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<Vector2<f32>> for UniformValue {
+    fn from(value: Vector2<f32>) -> Self {
+        Self::Vec2(value)
+    }
+}
+
+impl From<Vector3<f32>> for UniformValue {
+    fn from(value: Vector3<f32>) -> Self {
+        Self::Vec3(value)
+    }
+}
+
+impl From<Vector4<f32>> for UniformValue {
+    fn from(value: Vector4<f32>) -> Self {
+        Self::Vec4(value)
+    }
+}
+
+impl From<Matrix4<f32>> for UniformValue {
+    fn from(value: Matrix4<f32>) -> Self {
+        Self::Mat4(value)
+    }
+}
+
+impl From<ColorF32> for UniformValue {
+    fn from(color: ColorF32) -> Self {
+        Self::Vec4(color.into())
+    }
+}
@@ -0,0 +1,112 @@
+use crate::*;
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The viewport of a drawing node: the rectangle of the grids into which the
+/// normalized device coordinates of the vertex shader are mapped, and the range
+/// into which the depth values are mapped. This can be used to draw into a part
+/// of a grid, for instance for split-screen rendering, minimaps, and UI panels.
+///
+/// The coordinates are in pixels, where (0, 0) is the top-left corner of the grid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+
+    /// The depth value that a normalized depth of 0 will be mapped to. This must
+    /// be between 0 and 1.
+    pub min_depth: f32,
+
+    /// The depth value that a normalized depth of 1 will be mapped to. This must
+    /// be between 0 and 1, but it can be smaller than *min_depth* (which will
+    /// reverse the depth values).
+    pub max_depth: f32,
+}
+
+impl Viewport {
+    /// Constructs a new *Viewport* with the given position and size, and the full
+    /// depth range (0 to 1).
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        }
+    }
+
+    /// Constructs the *Viewport* that covers an entire grid with the given width
+    /// and height, with the full depth range. This is the viewport that drawing
+    /// nodes use when their *viewport* is *None*.
+    pub fn full(grid_width: u32, grid_height: u32) -> Self {
+        Self::new(0.0, 0.0, grid_width as f32, grid_height as f32)
+    }
+}
+
+/// This error indicates that the *viewport* or *scissor* of a drawing node is
+/// invalid for the grids it draws on. See the *check_bounds* method of
+/// *DrawingNodeBuilder*.
+#[derive(Debug)]
+pub enum ViewportError {
+    /// The width or height of the viewport is not positive.
+    EmptyViewport { viewport: Viewport },
+
+    /// The *min_depth* or *max_depth* of the viewport is not between 0 and 1.
+    InvalidDepthRange { viewport: Viewport },
+
+    /// The viewport doesn't lie within the bounds of the grid.
+    ViewportOutOfBounds {
+        viewport: Viewport,
+        grid_width: u32,
+        grid_height: u32,
+    },
+
+    /// The scissor region doesn't lie within the bounds of the grid.
+    ScissorOutOfBounds {
+        scissor: GridRegion,
+        grid_width: u32,
+        grid_height: u32,
+    },
+}
+
+impl Display for ViewportError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::EmptyViewport { viewport } => write!(
+                f,
+                "The size of the viewport must be positive, but it is {}x{}",
+                viewport.width, viewport.height
+            ),
+            Self::InvalidDepthRange { viewport } => write!(
+                f,
+                "The depth range of the viewport must be between 0 and 1, but it is {} to {}",
+                viewport.min_depth, viewport.max_depth
+            ),
+            Self::ViewportOutOfBounds {
+                viewport,
+                grid_width,
+                grid_height,
+            } => write!(
+                f,
+                "The viewport {:?} doesn't fit in a grid of {}x{} pixels",
+                viewport, grid_width, grid_height
+            ),
+            Self::ScissorOutOfBounds {
+                scissor,
+                grid_width,
+                grid_height,
+            } => write!(
+                f,
+                "The scissor region {:?} doesn't fit in a grid of {}x{} pixels",
+                scissor, grid_width, grid_height
+            ),
+        }
+    }
+}
+
+impl Error for ViewportError {}
@@ -5,7 +5,7 @@ use std::sync::Arc;
 /// The *Gateway* between the CPU and GPU. This gateway should be used to transfer vertex data, index
 /// data, and textures to the GPU. Use the *get_gateway* method of the *Instance* to get a reference
 /// to the gateway.
-pub trait Gateway {
+pub trait Gateway: Send + Sync {
 
     /// Transfers the given vertex data (in *vertices*) from the CPU to the GPU. The *usage* parameter
     /// indices what you are planning to do with these vertices and may be used later on to report
@@ -16,4 +16,32 @@ pub trait Gateway {
     /// the implementation must make sure that the transfer is completed before these vertices are
     /// used (but might stall the first draw call in the worst case).
     fn transfer_vertices(&self, vertices: &VertexStore, usage: VertexBufferUsage) -> Arc<dyn VertexBuffer>;
+
+    /// Transfers the given vertex *indices* from the CPU to the GPU, and returns an *IndexBuffer*
+    /// that holds them. Like *transfer_vertices*, this method may or may not block.
+    fn transfer_indices(&self, indices: &[u32]) -> Arc<dyn IndexBuffer>;
+
+    /// Transfers the given *pixels* from the CPU to the GPU, and returns a *Texture* that holds
+    /// them. The *pixels* must be stored like the raw data of a *ColorGridDump* with the given
+    /// *format*, *width*, and *height* (see its *from_raw* function). Like *transfer_vertices*,
+    /// this method may or may not block.
+    fn transfer_texture(&self, format: ColorFormat, width: u32, height: u32, pixels: &[u8]) -> Arc<dyn Texture>;
+
+    /// Transfers the elements in the given *StorageBufferStore* from the CPU to the GPU, and
+    /// returns a *StorageBuffer* that holds them. Shaders can both read and write the elements of
+    /// the returned buffer.
+    ///
+    /// Like *transfer_vertices*, this method may or may not block, but the implementation must make
+    /// sure that the transfer is completed before the buffer is used.
+    fn transfer_storage_buffer(&self, elements: &StorageBufferStore) -> Arc<dyn StorageBuffer>;
+
+    /// Creates a new *StorageBuffer* with *num_elements* elements of the given *element_type*,
+    /// without transferring any data from the CPU. All elements will initially be zero. This is
+    /// useful for buffers whose content will be written by shaders (for instance by a compute
+    /// node).
+    fn create_storage_buffer(&self, element_type: DataType, num_elements: usize) -> Arc<dyn StorageBuffer>;
+
+    /// Creates a new *OcclusionQuery* of the given *kind*. The same query can be used during many
+    /// frames (but at most once per *RenderFlow* execution).
+    fn create_occlusion_query(&self, kind: OcclusionQueryKind) -> Arc<dyn OcclusionQuery>;
 }
\ No newline at end of file
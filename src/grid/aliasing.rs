@@ -0,0 +1,545 @@
+use crate::*;
+
+/// Decides which transient grids of an *AbstractGridGroup* can share their
+/// memory. A grid is transient when its content is not needed before or after
+/// a *RenderFlow* (see the *is_transient* method of *AbstractGridGroup*), so it
+/// only needs memory from the first pass that uses it until the last pass that
+/// uses it. Transient grids of the same *GridKind* whose lifetimes don't
+/// overlap in any of the *RenderFlow*s of the group are put in the same
+/// *GridMemorySlot*, and Griphin implementations should let the grids in the
+/// same slot share their memory.
+///
+/// The lifetimes are based on the *get_pass_order* of the *RenderFlowBuilder*s,
+/// so the passes must be executed in that order. Since the plan depends on all
+/// *RenderFlow*s of the group, it must be recomputed whenever a new
+/// *RenderFlow* is created.
+///
+/// When the *DebugLevel* is at least *High*, the plan also contains
+/// *GarbageFill*s: every grid that shares its memory should be filled with
+/// *GARBAGE_BYTE* right before its lifetime starts, so that shaders that read
+/// it before writing it get obviously wrong results instead of the leftovers of
+/// another grid.
+pub struct GridAliasingPlan {
+    grids: Vec<PlannedGrid>,
+    slots: Vec<GridMemorySlot>,
+    garbage_fills: Vec<GarbageFill>,
+}
+
+struct PlannedGrid {
+    id: AbstractGridID,
+    size: u64,
+    lifetimes: Vec<Option<GridLifetime>>,
+    slot: usize,
+}
+
+impl PlannedGrid {
+    fn conflicts_with(&self, other: &PlannedGrid) -> bool {
+        self.lifetimes
+            .iter()
+            .zip(&other.lifetimes)
+            .any(|pair| match pair {
+                (Some(lifetime), Some(other_lifetime)) => lifetime.overlaps(other_lifetime),
+                _ => false,
+            })
+    }
+}
+
+/// The passes during which a grid is used in a single *RenderFlow*. Both pass
+/// indices are positions in the *get_pass_order* of the *RenderFlowBuilder*,
+/// and both are inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GridLifetime {
+    pub first_pass: usize,
+    pub last_pass: usize,
+}
+
+impl GridLifetime {
+    /// Checks if this lifetime has at least one pass in common with *other*.
+    pub fn overlaps(&self, other: &GridLifetime) -> bool {
+        self.first_pass <= other.last_pass && other.first_pass <= self.last_pass
+    }
+}
+
+/// A piece of memory that is shared by one or more transient grids of the same
+/// *GridKind*. See *GridAliasingPlan*.
+#[derive(Clone, Debug)]
+pub struct GridMemorySlot {
+    kind: GridKind,
+    size: u64,
+    grids: Vec<AbstractGridID>,
+}
+
+impl GridMemorySlot {
+    /// Gets the kind of the grids in this slot.
+    pub fn get_kind(&self) -> GridKind {
+        self.kind
+    }
+
+    /// Gets the (estimated) size of this slot, in bytes, which is the size of
+    /// its largest grid.
+    pub fn get_size(&self) -> u64 {
+        self.size
+    }
+
+    /// Gets the grids that share this slot.
+    pub fn get_grids(&self) -> &[AbstractGridID] {
+        &self.grids
+    }
+}
+
+/// Indicates that the given *grid* should be filled with
+/// *GridAliasingPlan::GARBAGE_BYTE* right before the pass at *pass_index* in
+/// the *get_pass_order* of the *RenderFlow* at *flow_index*.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GarbageFill {
+    pub flow_index: usize,
+    pub pass_index: usize,
+    pub grid: AbstractGridID,
+}
+
+fn get_lifetime(
+    flow: &RenderFlowBuilder,
+    order: &[FlowPass],
+    grid: AbstractGridID,
+) -> Option<GridLifetime> {
+    let mut passes = order
+        .iter()
+        .enumerate()
+        .filter(|(_, pass)| flow.get_pass_grids(**pass).contains(&grid))
+        .map(|(index, _)| index);
+    let first_pass = passes.next()?;
+    let last_pass = passes.next_back().unwrap_or(first_pass);
+    Some(GridLifetime {
+        first_pass,
+        last_pass,
+    })
+}
+
+impl GridAliasingPlan {
+    /// The value of every byte of a grid after a *GarbageFill*. As 32-bit float,
+    /// the pattern is a large negative number, and as 8-bit color, it is a
+    /// light gray that is easy to recognize.
+    pub const GARBAGE_BYTE: u8 = 0xCD;
+
+    /// Estimates the number of bytes that the grid with the given id of the
+    /// given *group* needs when the concrete *GridGroup* has the given size.
+    /// Depth stencil grids are assumed to use 4 bytes per sample (like a format
+    /// with 24 depth bits and 8 stencil bits). Griphin implementations may need
+    /// a bit more memory because of alignment.
+    pub fn estimate_size(
+        group: &dyn AbstractGridGroup,
+        id: AbstractGridID,
+        group_width: u32,
+        group_height: u32,
+    ) -> u64 {
+        let (width, height) = group.get_grid_size(id).resolve(group_width, group_height);
+        let bytes_per_sample = match id.as_color() {
+            Some(color_id) => {
+                let format = group.get_color_format(color_id);
+                format.get_num_components() as u64 * format.get_component_size() as u64
+            }
+            None => 4,
+        };
+        let num_samples = group.get_sample_count(id).get_num_samples() as u64;
+        width as u64 * height as u64 * num_samples * bytes_per_sample
+    }
+
+    /// Computes the plan for the given *group*, whose grids have the given *ids*,
+    /// and the given *flows* (which should be all *RenderFlow*s of the group).
+    /// The sizes of the slots are estimated for a concrete *GridGroup* with the
+    /// given size. See the documentation of this struct for the *debug_level*.
+    ///
+    /// Returns a *PassCycleError* if the passes of one of the *flows* can't be
+    /// ordered (see the *get_pass_order* method of *RenderFlowBuilder*).
+    pub fn new(
+        group: &dyn AbstractGridGroup,
+        ids: &GridGroupIDs,
+        flows: &[&RenderFlowBuilder],
+        group_width: u32,
+        group_height: u32,
+        debug_level: DebugLevel,
+    ) -> Result<Self, PassCycleError> {
+        let orders = flows
+            .iter()
+            .map(|flow| flow.get_pass_order())
+            .collect::<Result<Vec<_>, _>>()?;
+        let color_ids = ids.colors.iter().map(|id| id.get_abstract());
+        let depth_stencil_ids = ids.depth_stencils.iter().map(|id| id.get_abstract());
+        let mut grids: Vec<_> = color_ids
+            .chain(depth_stencil_ids)
+            .filter(|id| group.is_transient(*id))
+            .map(|id| PlannedGrid {
+                id,
+                size: Self::estimate_size(group, id, group_width, group_height),
+                lifetimes: flows
+                    .iter()
+                    .zip(&orders)
+                    .map(|(flow, order)| get_lifetime(flow, order, id))
+                    .collect(),
+                slot: 0,
+            })
+            .collect();
+
+        // Place the largest grids first, so the first grid of every slot
+        // determines its size
+        let mut placement_order: Vec<_> = (0..grids.len()).collect();
+        placement_order.sort_by_key(|index| std::cmp::Reverse(grids[*index].size));
+
+        let mut slots: Vec<GridMemorySlot> = Vec::new();
+        let mut slot_members: Vec<Vec<usize>> = Vec::new();
+        for index in placement_order {
+            let grid = &grids[index];
+            let kind = grid.id.get_kind();
+            let existing_slot = (0..slots.len()).find(|slot| {
+                slots[*slot].kind == kind
+                    && slot_members[*slot]
+                        .iter()
+                        .all(|member| !grids[*member].conflicts_with(grid))
+            });
+            let slot = match existing_slot {
+                Some(slot) => slot,
+                None => {
+                    slots.push(GridMemorySlot {
+                        kind,
+                        size: grid.size,
+                        grids: Vec::new(),
+                    });
+                    slot_members.push(Vec::new());
+                    slots.len() - 1
+                }
+            };
+            slots[slot].grids.push(grid.id);
+            slot_members[slot].push(index);
+            grids[index].slot = slot;
+        }
+
+        let mut garbage_fills = Vec::new();
+        if debug_level >= DebugLevel::High {
+            for grid in &grids {
+                if slots[grid.slot].grids.len() < 2 {
+                    continue;
+                }
+                for (flow_index, lifetime) in grid.lifetimes.iter().enumerate() {
+                    if let Some(lifetime) = lifetime {
+                        garbage_fills.push(GarbageFill {
+                            flow_index,
+                            pass_index: lifetime.first_pass,
+                            grid: grid.id,
+                        });
+                    }
+                }
+            }
+            garbage_fills.sort_by_key(|fill| (fill.flow_index, fill.pass_index));
+        }
+
+        Ok(Self {
+            grids,
+            slots,
+            garbage_fills,
+        })
+    }
+
+    /// Gets the memory slots of the transient grids.
+    pub fn get_slots(&self) -> &[GridMemorySlot] {
+        &self.slots
+    }
+
+    /// Gets the index of the slot of the grid with the given id, or *None* if
+    /// the grid is not transient.
+    pub fn get_slot_index(&self, grid: AbstractGridID) -> Option<usize> {
+        self.find_grid(grid).map(|planned| planned.slot)
+    }
+
+    /// Gets the lifetime of the transient grid with the given id during the
+    /// *RenderFlow* at *flow_index*, or *None* if the grid is not transient or
+    /// not used by that flow.
+    pub fn get_lifetime(&self, flow_index: usize, grid: AbstractGridID) -> Option<GridLifetime> {
+        self.find_grid(grid)?
+            .lifetimes
+            .get(flow_index)
+            .copied()
+            .flatten()
+    }
+
+    /// Gets the grids that should be filled with garbage, sorted by flow index
+    /// and pass index. This is empty unless the *DebugLevel* was at least
+    /// *High*.
+    pub fn get_garbage_fills(&self) -> &[GarbageFill] {
+        &self.garbage_fills
+    }
+
+    /// Gets the (estimated) number of bytes the transient grids would need if
+    /// they didn't share memory.
+    pub fn get_unaliased_size(&self) -> u64 {
+        self.grids.iter().map(|grid| grid.size).sum()
+    }
+
+    /// Gets the (estimated) number of bytes the transient grids need according
+    /// to this plan.
+    pub fn get_aliased_size(&self) -> u64 {
+        self.slots.iter().map(|slot| slot.size).sum()
+    }
+
+    /// Gets the (estimated) number of bytes that are saved by this plan.
+    pub fn get_memory_savings(&self) -> u64 {
+        self.get_unaliased_size() - self.get_aliased_size()
+    }
+
+    fn find_grid(&self, id: AbstractGridID) -> Option<&PlannedGrid> {
+        self.grids.iter().find(|grid| grid.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_util::*;
+
+    fn create_group() -> DummyGroup {
+        let grid = |start_operation, purpose, format| {
+            color_grid(start_operation, purpose, format, SampleCount::One)
+        };
+        let transient = |format| grid(ColorStartOperation::DontCare, ColorPurpose::Nothing, format);
+        DummyGroup::new(AbstractGridGroupBuilder {
+            color_grids: vec![
+                transient(ColorFormat::RGBA16Float),
+                transient(ColorFormat::RGBA8Unorm),
+                transient(ColorFormat::RGBA8Unorm),
+                grid(
+                    ColorStartOperation::DontCare,
+                    ColorPurpose::Display,
+                    ColorFormat::RGBA8Srgb,
+                ),
+                grid(
+                    ColorStartOperation::Clear,
+                    ColorPurpose::Nothing,
+                    ColorFormat::RGBA8Unorm,
+                ),
+            ],
+            depth_stencil_grids: Vec::new(),
+        })
+    }
+
+    fn create_node(
+        inputs: Vec<DrawingNodeInputSource>,
+        output: DrawingNodeOutputDestination,
+    ) -> DrawingNodeBuilder {
+        create_drawing_node(
+            create_pipeline(Vec::new()),
+            inputs.into_iter().map(|source| (source, "input")).collect(),
+            vec![(output, "outColor")],
+        )
+    }
+
+    // Draws the scene, blurs it horizontally and vertically, and tone maps it
+    fn create_blur_flow(group: &DummyGroup) -> RenderFlowBuilder {
+        let grids = group.get_color_ids();
+        let mut builder = group.create_render_flow_builder();
+        let scene = builder.add_grid_node(grids[0]);
+        let horizontal = builder.add_grid_node(grids[1]);
+        let vertical = builder.add_grid_node(grids[2]);
+        builder.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Cleared(grids[0])],
+            DrawingNodeOutputDestination::Internal(scene),
+        ));
+        builder.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Internal(scene)],
+            DrawingNodeOutputDestination::Internal(horizontal),
+        ));
+        builder.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Internal(horizontal)],
+            DrawingNodeOutputDestination::Internal(vertical),
+        ));
+        builder.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Internal(vertical)],
+            DrawingNodeOutputDestination::External(grids[3]),
+        ));
+        builder
+    }
+
+    #[test]
+    fn test_aliasing() {
+        let group = create_group();
+        let grids = group.get_color_ids();
+        let flow = create_blur_flow(&group);
+        let plan = GridAliasingPlan::new(&group, &group.ids, &[&flow], 100, 50, DebugLevel::Basic)
+            .unwrap();
+
+        // The scene grid needs 8 bytes per pixel, the blur grids need 4
+        assert_eq!(
+            40000,
+            GridAliasingPlan::estimate_size(&group, grids[0], 100, 50)
+        );
+        assert_eq!(
+            Some(GridLifetime {
+                first_pass: 1,
+                last_pass: 2
+            }),
+            plan.get_lifetime(0, grids[1])
+        );
+        assert_eq!(None, plan.get_slot_index(grids[3]));
+        assert_eq!(None, plan.get_lifetime(1, grids[1]));
+
+        // The vertical blur grid can reuse the memory of the scene grid
+        assert_eq!(2, plan.get_slots().len());
+        assert_eq!(plan.get_slot_index(grids[0]), plan.get_slot_index(grids[2]));
+        assert_ne!(plan.get_slot_index(grids[0]), plan.get_slot_index(grids[1]));
+        assert_eq!(80000, plan.get_unaliased_size());
+        assert_eq!(60000, plan.get_aliased_size());
+        assert_eq!(20000, plan.get_memory_savings());
+        assert!(plan.get_garbage_fills().is_empty());
+
+        let plan =
+            GridAliasingPlan::new(&group, &group.ids, &[&flow], 100, 50, DebugLevel::High).unwrap();
+        assert_eq!(
+            vec![
+                GarbageFill {
+                    flow_index: 0,
+                    pass_index: 0,
+                    grid: grids[0]
+                },
+                GarbageFill {
+                    flow_index: 0,
+                    pass_index: 2,
+                    grid: grids[2]
+                }
+            ],
+            plan.get_garbage_fills()
+        );
+    }
+
+    #[test]
+    fn test_multiple_flows() {
+        let group = create_group();
+        let grids = group.get_color_ids();
+        let blur_flow = create_blur_flow(&group);
+
+        // This flow uses the scene grid and the vertical blur grid at the same time
+        let mut other_flow = group.create_render_flow_builder();
+        other_flow.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Cleared(grids[0])],
+            DrawingNodeOutputDestination::External(grids[2]),
+        ));
+
+        let plan = GridAliasingPlan::new(
+            &group,
+            &group.ids,
+            &[&blur_flow, &other_flow],
+            100,
+            50,
+            DebugLevel::Minimal,
+        )
+        .unwrap();
+        assert_eq!(None, plan.get_lifetime(1, grids[1]));
+        assert_eq!(
+            Some(GridLifetime {
+                first_pass: 0,
+                last_pass: 0
+            }),
+            plan.get_lifetime(1, grids[2])
+        );
+
+        // The vertical blur grid can't share the memory of the scene grid anymore
+        assert_eq!(3, plan.get_slots().len());
+        assert_ne!(plan.get_slot_index(grids[0]), plan.get_slot_index(grids[2]));
+        assert_eq!(0, plan.get_memory_savings());
+    }
+
+    #[test]
+    fn test_clear_grid_is_not_aliased() {
+        let group = create_group();
+        let grids = group.get_color_ids();
+
+        // The last grid is only used after the horizontal blur grid, but it is
+        // cleared when the flow starts, which would destroy the blur grid
+        let mut flow = group.create_render_flow_builder();
+        let horizontal = flow.add_grid_node(grids[1]);
+        flow.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Cleared(grids[1])],
+            DrawingNodeOutputDestination::Internal(horizontal),
+        ));
+        flow.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Internal(horizontal)],
+            DrawingNodeOutputDestination::External(grids[3]),
+        ));
+        flow.add_drawing_node(create_node(
+            Vec::new(),
+            DrawingNodeOutputDestination::External(grids[4]),
+        ));
+
+        let plan =
+            GridAliasingPlan::new(&group, &group.ids, &[&flow], 100, 50, DebugLevel::High).unwrap();
+        assert_eq!(None, plan.get_slot_index(grids[4]));
+        assert_eq!(None, plan.get_lifetime(0, grids[4]));
+        assert!(plan
+            .get_slots()
+            .iter()
+            .all(|slot| !slot.get_grids().contains(&grids[4])));
+        assert!(plan
+            .get_garbage_fills()
+            .iter()
+            .all(|fill| fill.grid != grids[4]));
+    }
+
+    #[test]
+    fn test_transient_rule() {
+        let start_operations = [
+            (
+                ColorStartOperation::Preserve,
+                DepthStencilStartOperation::Preserve,
+            ),
+            (
+                ColorStartOperation::Clear,
+                DepthStencilStartOperation::Clear,
+            ),
+            (
+                ColorStartOperation::DontCare,
+                DepthStencilStartOperation::DontCare,
+            ),
+        ];
+        for (color_start, depth_stencil_start) in start_operations.iter().copied() {
+            let is_dont_care = color_start == ColorStartOperation::DontCare;
+            let color = |purpose| {
+                color_grid(
+                    color_start,
+                    purpose,
+                    ColorFormat::RGBA8Unorm,
+                    SampleCount::One,
+                )
+            };
+            let depth_stencil = |purpose| AbstractDepthStencilGridBuilder {
+                start_operation: depth_stencil_start,
+                purpose,
+                sample_count: SampleCount::One,
+                size: GridSize::FULL,
+            };
+
+            assert_eq!(is_dont_care, color(ColorPurpose::Nothing).is_transient());
+            assert_eq!(
+                is_dont_care,
+                depth_stencil(DepthStencilPurpose::Nothing).is_transient()
+            );
+            assert!(!color(ColorPurpose::ShaderRead).is_transient());
+            assert!(!depth_stencil(DepthStencilPurpose::ShaderRead).is_transient());
+        }
+    }
+
+    #[test]
+    fn test_cyclic_flow() {
+        let group = create_group();
+        let mut flow = create_blur_flow(&group);
+
+        // The scene node is also written by a node that reads the vertical blur
+        let scene = flow.get_grid_nodes()[0].get_id();
+        let vertical = flow.get_grid_nodes()[2].get_id();
+        flow.add_drawing_node(create_node(
+            vec![DrawingNodeInputSource::Internal(vertical)],
+            DrawingNodeOutputDestination::Internal(scene),
+        ));
+        let error = GridAliasingPlan::new(&group, &group.ids, &[&flow], 100, 50, DebugLevel::Basic)
+            .err()
+            .unwrap();
+        assert!(error.get_passes().contains(&FlowPass::Drawing(4)));
+    }
+}
@@ -3,7 +3,7 @@ use crate::*;
 use std::any::Any;
 
 // TODO Make this trait actually usable
-pub trait ColorGrid {
+pub trait ColorGrid: Send + Sync {
     fn as_any(&self) -> &dyn Any;
 
     fn debug_dump(&self, region: GridRegion) -> ColorGridDump;
@@ -23,7 +23,7 @@ pub enum ColorStartOperation {
 
     /// The colors of the grid should be cleared to some fixed value when
     /// its *RenderFlow* starts. This value can be chosen right before the
-    /// render flow starts.
+    /// render flow starts (see *ColorClearValue*).
     Clear, // Use undefined image layout
 
     /// It doesn't matter which colors the grid has before the start of the
@@ -42,11 +42,13 @@ pub enum ColorPurpose {
     /// is over.
     Nothing,
 
-    /// The colors in the grid are going to be displayed to the screen.
+    /// The colors in the grid are going to be displayed to the screen (or more
+    /// generally, presented to a *Surface*).
     Display,
 
     /// The colors in the grid are going to be read by a shader in a different
-    /// *RenderFlow*.
+    /// *RenderFlow*. Grids that are used as (sampled) input of a drawing node or
+    /// compute node *must* have this purpose.
     ShaderRead,
 
     /// The colors are going to be copied to some other color grid.
@@ -60,7 +62,8 @@ pub enum ColorPurpose {
 /// *AbstractColorGrid*. They have a *ColorStartOperation* and a *ColorPurpose*,
 /// which define what should happen with the color grid at the beginning and
 /// the end of *RenderFlow*s. See the documentation of these two types for more
-/// information.
+/// information. Furthermore, they have a *ColorFormat* that determines how the
+/// pixels of the grid are stored.
 ///
 /// To use an instance of this struct, add it to the *color_grids* of an
 /// *AbstractGridGroupBuilder*.
@@ -72,4 +75,34 @@ pub struct AbstractColorGridBuilder {
     /// What the colors in the grid will be used for after its *RenderFlow* has
     /// ended. See the documentation of *ColorPurpose* for more information.
     pub purpose: ColorPurpose,
+
+    /// The format of the pixels of the grid. The *ColorOutput* variables of the
+    /// fragment shaders that draw on the grid must match this format. See the
+    /// documentation of *ColorFormat* for more information.
+    pub format: ColorFormat,
+
+    /// The number of samples per pixel of the grid. Grids with the *Display*
+    /// purpose must have *SampleCount::One*: to display a multisampled grid,
+    /// resolve it into a grid with the *Display* purpose instead. See the
+    /// documentation of *SampleCount* for more information.
+    pub sample_count: SampleCount,
+
+    /// The size policy of the grid, which determines the size of the concrete
+    /// grids based on the size of their *GridGroup*. Use *GridSize::FULL* if the
+    /// grid should simply have the size of its group.
+    pub size: GridSize,
+}
+
+impl AbstractColorGridBuilder {
+    /// Checks if the grid is transient: its colors are not needed at the start
+    /// of its *RenderFlow* (the start operation is *DontCare*), and they are
+    /// not needed after the *RenderFlow* (the purpose is *Nothing*).
+    /// Intermediate blur targets are typical transient grids. Grids with the
+    /// *Clear* start operation are not transient, since they are cleared when
+    /// the *RenderFlow* starts rather than when they are first used. (Use a
+    /// *Cleared* input source to clear a transient grid.)
+    pub fn is_transient(&self) -> bool {
+        self.start_operation == ColorStartOperation::DontCare
+            && self.purpose == ColorPurpose::Nothing
+    }
 }
@@ -0,0 +1,321 @@
+use crate::*;
+
+use cgmath::Vector4;
+
+/// Represents a color with floating point red, green, blue, and alpha components
+/// in the *linear* color space. Unlike *Color*, the components are not limited to
+/// the range [0, 1], so this can also represent HDR colors (which can be stored
+/// in grids with a *Float* *ColorFormat*).
+///
+/// Use *from_srgb* and *to_srgb* to convert from and to 8-bit *Color*s in the
+/// sRGB color space (which is the color space of most color pickers and image
+/// files), or *from_unorm* and *to_unorm* to convert without changing the color
+/// space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorF32 {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+impl ColorF32 {
+    pub const BLACK: ColorF32 = ColorF32::new(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: ColorF32 = ColorF32::new(1.0, 1.0, 1.0, 1.0);
+    pub const TRANSPARENT: ColorF32 = ColorF32::new(0.0, 0.0, 0.0, 0.0);
+
+    /// Constructs a new *ColorF32* with the given (linear) components.
+    pub const fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    /// Converts the given *Color* from the sRGB color space to a linear
+    /// *ColorF32*. The alpha component is not affected by the color space, so it
+    /// is simply divided by 255.
+    pub fn from_srgb(color: Color) -> Self {
+        Self::new(
+            srgb_to_linear(color.red as f32 / 255.0),
+            srgb_to_linear(color.green as f32 / 255.0),
+            srgb_to_linear(color.blue as f32 / 255.0),
+            color.alpha as f32 / 255.0,
+        )
+    }
+
+    /// Converts this linear color to a *Color* in the sRGB color space. The
+    /// components are clamped to the range [0, 1] before they are converted.
+    pub fn to_srgb(&self) -> Color {
+        Color {
+            red: unorm_to_u8(linear_to_srgb(self.red.clamp(0.0, 1.0))),
+            green: unorm_to_u8(linear_to_srgb(self.green.clamp(0.0, 1.0))),
+            blue: unorm_to_u8(linear_to_srgb(self.blue.clamp(0.0, 1.0))),
+            alpha: unorm_to_u8(self.alpha),
+        }
+    }
+
+    /// Converts the given *Color* to a *ColorF32* by dividing all components by
+    /// 255, without changing the color space.
+    pub fn from_unorm(color: Color) -> Self {
+        Self::new(
+            color.red as f32 / 255.0,
+            color.green as f32 / 255.0,
+            color.blue as f32 / 255.0,
+            color.alpha as f32 / 255.0,
+        )
+    }
+
+    /// Converts this color to a *Color* by clamping all components to the range
+    /// [0, 1] and multiplying them by 255, without changing the color space.
+    pub fn to_unorm(&self) -> Color {
+        Color {
+            red: unorm_to_u8(self.red),
+            green: unorm_to_u8(self.green),
+            blue: unorm_to_u8(self.blue),
+            alpha: unorm_to_u8(self.alpha),
+        }
+    }
+
+    /// Gets the premultiplied version of this color: the red, green, and blue
+    /// components are multiplied by the alpha component. This is the kind of
+    /// color that *BlendState::PREMULTIPLIED_ALPHA* expects.
+    pub fn premultiply(&self) -> Self {
+        Self::new(
+            self.red * self.alpha,
+            self.green * self.alpha,
+            self.blue * self.alpha,
+            self.alpha,
+        )
+    }
+
+    /// The inverse of *premultiply*: the red, green, and blue components are
+    /// divided by the alpha component. If the alpha component is 0, the result
+    /// will be *TRANSPARENT*.
+    pub fn unpremultiply(&self) -> Self {
+        if self.alpha == 0.0 {
+            return Self::TRANSPARENT;
+        }
+        Self::new(
+            self.red / self.alpha,
+            self.green / self.alpha,
+            self.blue / self.alpha,
+            self.alpha,
+        )
+    }
+}
+
+impl From<Vector4<f32>> for ColorF32 {
+    fn from(vector: Vector4<f32>) -> Self {
+        Self::new(vector.x, vector.y, vector.z, vector.w)
+    }
+}
+
+impl From<ColorF32> for Vector4<f32> {
+    fn from(color: ColorF32) -> Self {
+        Vector4::new(color.red, color.green, color.blue, color.alpha)
+    }
+}
+
+impl From<[f32; 4]> for ColorF32 {
+    fn from(components: [f32; 4]) -> Self {
+        Self::new(components[0], components[1], components[2], components[3])
+    }
+}
+
+impl From<ColorF32> for [f32; 4] {
+    fn from(color: ColorF32) -> Self {
+        [color.red, color.green, color.blue, color.alpha]
+    }
+}
+
+/// Converts a color component from the (non-linear) sRGB color space to the
+/// linear color space. Both *value* and the result are in the range [0, 1].
+pub(crate) fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a color component from the linear color space to the (non-linear)
+/// sRGB color space. Both *value* and the result are in the range [0, 1].
+pub(crate) fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn unorm_to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// The value to which a color grid with *ColorStartOperation::Clear* should be
+/// cleared. This can be either an 8-bit *Color*, a *ColorF32*, or an integer
+/// value (for the *Uint* and *Sint* formats).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorClearValue {
+    /// An 8-bit color, which is stored as-is in grids with an 8-bit *ColorFormat*.
+    /// (So for the *Srgb* formats, this color is assumed to be in the sRGB color
+    /// space.) The *Uint* and *Sint* formats store the components as-is as
+    /// well. For the other formats, the components are divided by 255.
+    Color(Color),
+
+    /// A linear color. The *Srgb* formats will encode this color to the sRGB color
+    /// space, like they do with the outputs of fragment shaders.
+    Float(ColorF32),
+
+    /// An unsigned integer value, for the *Uint* formats.
+    Uint([u32; 4]),
+
+    /// A signed integer value, for the *Sint* formats.
+    Sint([i32; 4]),
+}
+
+impl ColorClearValue {
+    /// Gets the *PixelValue* that grids with the given *format* will have after
+    /// being cleared to this value, as shaders would read it. Griphin
+    /// implementations can use this to convert the clear value to the format of
+    /// the grid. Integer clear values are converted to floats for the float
+    /// formats, float clear values are rounded for the integer formats, and the
+    /// values are clamped to [0, 1] for the *Unorm* and *Srgb* formats.
+    pub fn to_pixel_value(&self, format: ColorFormat) -> PixelValue {
+        let kind = format.get_component_kind();
+        let floats = match *self {
+            Self::Color(color) => {
+                if kind == ColorComponentKind::Srgb {
+                    ColorF32::from_srgb(color).into()
+                } else {
+                    ColorF32::from_unorm(color).into()
+                }
+            }
+            Self::Float(color) => color.into(),
+            Self::Uint(values) => values.map(|value| value as f32),
+            Self::Sint(values) => values.map(|value| value as f32),
+        };
+        match kind {
+            ColorComponentKind::Uint => PixelValue::Uint(match *self {
+                Self::Uint(values) => values,
+                Self::Color(color) => {
+                    [color.red, color.green, color.blue, color.alpha].map(|value| value as u32)
+                }
+                _ => floats.map(|value| value.round().max(0.0) as u32),
+            }),
+            ColorComponentKind::Sint => PixelValue::Sint(match *self {
+                Self::Sint(values) => values,
+                Self::Color(color) => {
+                    [color.red, color.green, color.blue, color.alpha].map(|value| value as i32)
+                }
+                _ => floats.map(|value| value.round() as i32),
+            }),
+            ColorComponentKind::Unorm | ColorComponentKind::Srgb => {
+                PixelValue::Float(floats.map(|value| value.clamp(0.0, 1.0)))
+            }
+            ColorComponentKind::Float => PixelValue::Float(floats),
+        }
+    }
+}
+
+impl From<Color> for ColorClearValue {
+    fn from(color: Color) -> Self {
+        Self::Color(color)
+    }
+}
+
+impl From<ColorF32> for ColorClearValue {
+    fn from(color: ColorF32) -> Self {
+        Self::Float(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_srgb_round_trip() {
+        for value in 0..=255 {
+            let color = Color {
+                red: value,
+                green: 255 - value,
+                blue: value / 2,
+                alpha: value,
+            };
+            assert_eq!(color, ColorF32::from_srgb(color).to_srgb());
+            assert_eq!(color, ColorF32::from_unorm(color).to_unorm());
+        }
+
+        // sRGB 188 is approximately linear 0.5
+        let half = ColorF32::from_srgb(Color {
+            red: 188,
+            green: 0,
+            blue: 255,
+            alpha: 255,
+        });
+        assert!((half.red - 0.5).abs() < 0.01);
+        assert_eq!(0.0, half.green);
+        assert_eq!(1.0, half.blue);
+    }
+
+    #[test]
+    fn test_premultiply() {
+        let color = ColorF32::new(1.0, 0.5, 0.2, 0.5);
+        let premultiplied = color.premultiply();
+        assert_eq!(ColorF32::new(0.5, 0.25, 0.1, 0.5), premultiplied);
+        assert_eq!(color, premultiplied.unpremultiply());
+        assert_eq!(
+            ColorF32::TRANSPARENT,
+            ColorF32::new(1.0, 1.0, 1.0, 0.0).unpremultiply()
+        );
+    }
+
+    #[test]
+    fn test_vector_interop() {
+        let color = ColorF32::new(2.0, 0.5, 0.0, 1.0);
+        let vector: Vector4<f32> = color.into();
+        assert_eq!(Vector4::new(2.0, 0.5, 0.0, 1.0), vector);
+        assert_eq!(color, ColorF32::from(vector));
+    }
+
+    #[test]
+    fn test_clear_value() {
+        let color = Color {
+            red: 255,
+            green: 0,
+            blue: 51,
+            alpha: 255,
+        };
+        assert_eq!(
+            PixelValue::Float([1.0, 0.0, 0.2, 1.0]),
+            ColorClearValue::from(color).to_pixel_value(ColorFormat::RGBA8Unorm)
+        );
+        assert_eq!(
+            PixelValue::Float([1.0, 0.0, srgb_to_linear(0.2), 1.0]),
+            ColorClearValue::from(color).to_pixel_value(ColorFormat::BGRA8Srgb)
+        );
+        assert_eq!(
+            PixelValue::Float([4.0, 0.5, 0.0, 1.0]),
+            ColorClearValue::from(ColorF32::new(4.0, 0.5, 0.0, 1.0))
+                .to_pixel_value(ColorFormat::RGBA16Float)
+        );
+        assert_eq!(
+            PixelValue::Uint([255, 0, 51, 255]),
+            ColorClearValue::from(color).to_pixel_value(ColorFormat::R32Uint)
+        );
+        assert_eq!(
+            PixelValue::Uint([7, 0, 0, 1]),
+            ColorClearValue::Uint([7, 0, 0, 1]).to_pixel_value(ColorFormat::R32Uint)
+        );
+        assert_eq!(
+            PixelValue::Sint([-3, 0, 0, 0]),
+            ColorClearValue::Float(ColorF32::new(-3.2, 0.0, 0.0, 0.0))
+                .to_pixel_value(ColorFormat::R32Sint)
+        );
+    }
+}
@@ -1,7 +1,9 @@
+use crate::*;
+
 use std::any::Any;
 
 // TODO Make this trait usable
-pub trait DepthStencilGrid {
+pub trait DepthStencilGrid: Send + Sync {
     fn as_any(&self) -> &dyn Any;
 }
 
@@ -9,6 +11,7 @@ pub trait DepthStencilGrid {
 /// *DepthStencilStartOperation*. This operation determines what should be done
 /// with the depth/stencil grid when its *RenderFlow* starts. See the
 /// documentation of each of the possibilities for more information.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum DepthStencilStartOperation {
     /// The depth and stencil values that are stored before a *RenderFlow* starts,
     /// should be preserved during the render flow (until it is overwritten by
@@ -33,13 +36,15 @@ pub enum DepthStencilStartOperation {
 /// This purpose states what the depth stencil values in the grid are going
 /// to be used for after its *RenderFlow* has ended. See the documentation of
 /// the possibilities for more information.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum DepthStencilPurpose {
     /// The depth and stencil values in the grid won't be used (anymore) after
     /// its *RenderFlow* has ended.
     Nothing,
 
     /// The depth and/or stencil values in the grid will be read by a shader in
-    /// a different *RenderFlow*.
+    /// a different *RenderFlow*. Grids that are used as (sampled) input of a
+    /// drawing node or compute node *must* have this purpose.
     ShaderRead,
 
     /// The depth and stencil values in the grid will be copied to a different
@@ -68,4 +73,26 @@ pub struct AbstractDepthStencilGridBuilder {
     /// its *RenderFlow* has ended. See the documentation of *DepthStencilPurpose*
     /// for more information.
     pub purpose: DepthStencilPurpose,
+
+    /// The number of samples per pixel of the grid. This must be equal to the
+    /// *SampleCount* of the color grids that are drawn on together with this
+    /// grid. See the documentation of *SampleCount* for more information.
+    pub sample_count: SampleCount,
+
+    /// The size policy of the grid, which determines the size of the concrete
+    /// grids based on the size of their *GridGroup*. This is typically
+    /// *GridSize::FULL*, or an *Absolute* size for shadow maps.
+    pub size: GridSize,
+}
+
+impl AbstractDepthStencilGridBuilder {
+    /// Checks if the grid is transient: its depth and stencil values are not
+    /// needed at the start of its *RenderFlow* (the start operation is
+    /// *DontCare*), and they are not needed after the *RenderFlow* (the purpose
+    /// is *Nothing*). Like for color grids, the *Clear* start operation doesn't
+    /// count, since the values are cleared when the *RenderFlow* starts.
+    pub fn is_transient(&self) -> bool {
+        self.start_operation == DepthStencilStartOperation::DontCare
+            && self.purpose == DepthStencilPurpose::Nothing
+    }
 }
@@ -1,3 +1,7 @@
+use crate::*;
+
+use std::convert::TryInto;
+
 /// For debugging purposes, it can be very useful to see what a *ColorGrid*
 /// currently looks like. This struct represents a copy of the state of a
 /// color grid, made at a given moment.
@@ -6,11 +10,17 @@
 /// individual colors in the grid can be read easily (and can thus be used
 /// for debugging).
 ///
+/// A dump stores the raw pixel data in the *ColorFormat* of its grid. Use the
+/// *get_pixel* method to read the pixels of any format, the *get_f32* method to
+/// read them as linear *ColorF32*s, or the *get* method to read them as 8-bit
+/// *Color*s.
+///
 /// *Note: sending data from GPU memory to RAM is often very expensive, so
 /// this shouldn't be used every frame! Luckily, performance is normally not
 /// important during debugging.*
 pub struct ColorGridDump {
-    data: Vec<Color>,
+    format: ColorFormat,
+    data: Vec<u8>,
 
     width: u32,
 }
@@ -26,31 +36,185 @@ pub struct Color {
     pub alpha: u8,
 }
 
+/// The value of a single pixel of a *ColorGridDump*, as a shader would read
+/// it. The components are always in red, green, blue, alpha order. Components
+/// that the *ColorFormat* doesn't have are 0, except for the alpha component,
+/// which is 1 (or 1.0) when it is missing.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PixelValue {
+    /// The pixel value of a grid with a *Unorm*, *Srgb*, or *Float* format. The
+    /// values of *Srgb* formats are converted to the linear color space.
+    Float([f32; 4]),
+
+    /// The pixel value of a grid with a *Uint* format.
+    Uint([u32; 4]),
+
+    /// The pixel value of a grid with a *Sint* format.
+    Sint([i32; 4]),
+}
+
 impl ColorGridDump {
     /// Constructs a new *ColorGridDump* from a raw *Vec* of *Color*s with given
     /// width and height. The product of *width* and *height* must be equal to the
-    /// length of *data*.
+    /// length of *data*. The format of the dump will be *RGBA8Unorm*.
     ///
     /// This method should normally only be used by Griphin implementations.
     pub fn new(data: Vec<Color>, width: u32, height: u32) -> Self {
-        if width * height != data.len() as u32 {
+        let mut raw_data = Vec::with_capacity(4 * data.len());
+        for color in data {
+            raw_data.extend_from_slice(&[color.red, color.green, color.blue, color.alpha]);
+        }
+        Self::from_raw(ColorFormat::RGBA8Unorm, raw_data, width, height)
+    }
+
+    /// Constructs a new *ColorGridDump* from raw pixel data in the given *format*
+    /// with given width and height. The pixels must be stored row by row, without
+    /// any padding, and the length of *data* must be equal to *width * height *
+    /// format.get_pixel_size()*. Multi-byte components must be stored in native
+    /// byte order.
+    ///
+    /// This method should normally only be used by Griphin implementations.
+    pub fn from_raw(format: ColorFormat, data: Vec<u8>, width: u32, height: u32) -> Self {
+        let expected_size = width as usize * height as usize * format.get_pixel_size();
+        if expected_size != data.len() {
             panic!(
-                "Width * height must be equal to the size of data, but it's not: {} * {} != {}",
+                "Width * height * pixel size must be equal to the size of data, but it's not: {} * {} * {} != {}",
                 width,
                 height,
+                format.get_pixel_size(),
                 data.len()
             );
         }
 
-        Self { data, width }
+        Self {
+            format,
+            data,
+            width,
+        }
+    }
+
+    /// Gets the *ColorFormat* of this dump (and the associated *ColorGrid*).
+    pub fn get_format(&self) -> ColorFormat {
+        self.format
+    }
+
+    /// Gets the raw pixel data of this dump. See the *from_raw* function for the
+    /// layout of this data.
+    pub fn get_raw_data(&self) -> &Vec<u8> {
+        &self.data
+    }
+
+    fn get_raw_components(&self, x: u32, y: u32) -> Vec<&[u8]> {
+        let pixel_size = self.format.get_pixel_size();
+        let offset = (x + y * self.width) as usize * pixel_size;
+        let component_size = self.format.get_component_size() as usize;
+        let mut components: Vec<&[u8]> = self.data[offset..offset + pixel_size]
+            .chunks(component_size)
+            .collect();
+        if self.format.is_reversed() {
+            components.swap(0, 2);
+        }
+        components
+    }
+
+    /// Gets the *PixelValue* at the position (x,y) in this *ColorGridDump*. The
+    /// coordinates work the same as in the *get* method.
+    pub fn get_pixel(&self, x: u32, y: u32) -> PixelValue {
+        let components = self.get_raw_components(x, y);
+        match self.format.get_component_kind() {
+            ColorComponentKind::Uint => {
+                let mut result = [0, 0, 0, 1];
+                for (index, bytes) in components.iter().enumerate() {
+                    result[index] = u32::from_ne_bytes((*bytes).try_into().unwrap());
+                }
+                PixelValue::Uint(result)
+            }
+            ColorComponentKind::Sint => {
+                let mut result = [0, 0, 0, 1];
+                for (index, bytes) in components.iter().enumerate() {
+                    result[index] = i32::from_ne_bytes((*bytes).try_into().unwrap());
+                }
+                PixelValue::Sint(result)
+            }
+            kind => {
+                let mut result = [0.0, 0.0, 0.0, 1.0];
+                for (index, bytes) in components.iter().enumerate() {
+                    result[index] = match bytes.len() {
+                        1 => bytes[0] as f32 / 255.0,
+                        2 => half_to_f32(u16::from_ne_bytes((*bytes).try_into().unwrap())),
+                        _ => f32::from_ne_bytes((*bytes).try_into().unwrap()),
+                    };
+                    if kind == ColorComponentKind::Srgb && index < 3 {
+                        result[index] = srgb_to_linear(result[index]);
+                    }
+                }
+                PixelValue::Float(result)
+            }
+        }
     }
 
     /// Gets the *Color* at the position (x,y) in this *ColorGridDump*. The left
     /// border has an x-coordinate of 0 and the top border has a y-coordinate of 0.
     /// The right border has an x-coordinate of *self.get_width() - 1* and the
     /// bottom border has a y-coordinate of *self.get_height() - 1*.
+    ///
+    /// For the 8-bit formats, this simply returns the stored components (so the
+    /// components of *Srgb* formats will *not* be converted to linear colors).
+    /// For the other formats, the *PixelValue* is clamped to the range of a
+    /// *Color*: floats are mapped from [0, 1] to [0, 255] and integers are
+    /// clamped to [0, 255].
     pub fn get(&self, x: u32, y: u32) -> Color {
-        self.data[(x + y * self.width) as usize]
+        let components = if self.format.get_component_size() == 1 {
+            let mut result = [0, 0, 0, 255];
+            for (index, bytes) in self.get_raw_components(x, y).iter().enumerate() {
+                result[index] = bytes[0];
+            }
+            result
+        } else {
+            match self.get_pixel(x, y) {
+                PixelValue::Float(values) => {
+                    let mut result = [0; 4];
+                    for index in 0..4 {
+                        result[index] = (values[index].clamp(0.0, 1.0) * 255.0).round() as u8;
+                    }
+                    result
+                }
+                PixelValue::Uint(values) => {
+                    let mut result = [0; 4];
+                    for index in 0..4 {
+                        result[index] = values[index].min(255) as u8;
+                    }
+                    result
+                }
+                PixelValue::Sint(values) => {
+                    let mut result = [0; 4];
+                    for index in 0..4 {
+                        result[index] = values[index].clamp(0, 255) as u8;
+                    }
+                    result
+                }
+            }
+        };
+        Color {
+            red: components[0],
+            green: components[1],
+            blue: components[2],
+            alpha: components[3],
+        }
+    }
+
+    /// Gets the color at the position (x,y) in this *ColorGridDump* as a linear
+    /// *ColorF32*. The coordinates work the same as in the *get* method.
+    ///
+    /// Unlike *get*, this doesn't clamp anything, so this is the method to use
+    /// for HDR grids. The components of the *Uint* and *Sint* formats are
+    /// converted to floats.
+    pub fn get_f32(&self, x: u32, y: u32) -> ColorF32 {
+        match self.get_pixel(x, y) {
+            PixelValue::Float(values) => values.into(),
+            PixelValue::Uint(values) => values.map(|value| value as f32).into(),
+            PixelValue::Sint(values) => values.map(|value| value as f32).into(),
+        }
     }
 
     /// Gets the width of this *ColorGridDump* (and the associated *ColorGrid*).
@@ -62,6 +226,136 @@ impl ColorGridDump {
     /// Gets the height of this *ColorGridDump* (and the associated *ColorGrid*).
     /// That is, the number of rows in the grid.
     pub fn get_height(&self) -> u32 {
-        self.data.len() as u32 / self.width
+        (self.data.len() / self.format.get_pixel_size()) as u32 / self.width
+    }
+}
+
+fn half_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f32;
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        31 => {
+            if mantissa == 0.0 {
+                f32::INFINITY
+            } else {
+                f32::NAN
+            }
+        }
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rgba8() {
+        let dump = ColorGridDump::new(
+            vec![
+                Color {
+                    red: 1,
+                    green: 2,
+                    blue: 3,
+                    alpha: 4,
+                },
+                Color {
+                    red: 255,
+                    green: 0,
+                    blue: 51,
+                    alpha: 255,
+                },
+            ],
+            2,
+            1,
+        );
+        assert_eq!(2, dump.get_width());
+        assert_eq!(1, dump.get_height());
+        assert_eq!(
+            Color {
+                red: 1,
+                green: 2,
+                blue: 3,
+                alpha: 4
+            },
+            dump.get(0, 0)
+        );
+        assert_eq!(
+            PixelValue::Float([1.0, 0.0, 0.2, 1.0]),
+            dump.get_pixel(1, 0)
+        );
+    }
+
+    #[test]
+    fn test_bgra8_srgb() {
+        let dump = ColorGridDump::from_raw(ColorFormat::BGRA8Srgb, vec![0, 0, 255, 128], 1, 1);
+        assert_eq!(
+            Color {
+                red: 255,
+                green: 0,
+                blue: 0,
+                alpha: 128
+            },
+            dump.get(0, 0)
+        );
+        match dump.get_pixel(0, 0) {
+            PixelValue::Float(values) => {
+                assert_eq!(1.0, values[0]);
+                assert_eq!(0.0, values[2]);
+                assert!((values[3] - 128.0 / 255.0).abs() < 0.0001);
+            }
+            _ => panic!("Expected float pixel value"),
+        };
+    }
+
+    #[test]
+    fn test_rg16_float() {
+        // 0x3c00 is 1.0 and 0xc000 is -2.0 in half precision
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x3c00u16.to_ne_bytes());
+        data.extend_from_slice(&0xc000u16.to_ne_bytes());
+        let dump = ColorGridDump::from_raw(ColorFormat::RG16Float, data, 1, 1);
+        assert_eq!(
+            PixelValue::Float([1.0, -2.0, 0.0, 1.0]),
+            dump.get_pixel(0, 0)
+        );
+        assert_eq!(ColorF32::new(1.0, -2.0, 0.0, 1.0), dump.get_f32(0, 0));
+        assert_eq!(
+            Color {
+                red: 255,
+                green: 0,
+                blue: 0,
+                alpha: 255
+            },
+            dump.get(0, 0)
+        );
+    }
+
+    #[test]
+    fn test_r32_uint() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&12u32.to_ne_bytes());
+        data.extend_from_slice(&123_456u32.to_ne_bytes());
+        let dump = ColorGridDump::from_raw(ColorFormat::R32Uint, data, 1, 2);
+        assert_eq!(2, dump.get_height());
+        assert_eq!(PixelValue::Uint([123_456, 0, 0, 1]), dump.get_pixel(0, 1));
+        assert_eq!(
+            Color {
+                red: 12,
+                green: 0,
+                blue: 0,
+                alpha: 1
+            },
+            dump.get(0, 0)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_size() {
+        ColorGridDump::from_raw(ColorFormat::RGBA32Float, vec![0; 32], 1, 1);
     }
 }
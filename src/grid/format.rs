@@ -0,0 +1,265 @@
+use crate::*;
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The pixel format of a *ColorGrid*. The format determines which components
+/// (red, green, blue, alpha) every pixel has, how many bytes each component
+/// occupies, and how the shaders see these components.
+///
+/// The *Unorm* formats store unsigned integers that the shaders see as floats
+/// between 0 and 1. The *Srgb* formats do the same, but the colors are stored
+/// in the (non-linear) sRGB color space: shaders will read and write linear
+/// colors, and the conversion happens automatically. The *Float* formats store
+/// (half or single precision) floats, which is useful for HDR rendering. The
+/// *Uint* and *Sint* formats store integers that the shaders see as integers,
+/// which is useful for things like object ID buffers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorFormat {
+    R8Unorm,
+    RG8Unorm,
+    RGBA8Unorm,
+    RGBA8Srgb,
+    BGRA8Unorm,
+    BGRA8Srgb,
+    R16Float,
+    RG16Float,
+    RGBA16Float,
+    R32Float,
+    RG32Float,
+    RGBA32Float,
+    R32Uint,
+    RG32Uint,
+    RGBA32Uint,
+    R32Sint,
+}
+
+/// The way in which the components of a *ColorFormat* are stored and how they
+/// are seen by shaders. See the documentation of *ColorFormat*.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorComponentKind {
+    Unorm,
+    Srgb,
+    Float,
+    Uint,
+    Sint,
+}
+
+impl ColorFormat {
+    /// Gets the number of components every pixel of this format has.
+    pub fn get_num_components(&self) -> u8 {
+        match self {
+            Self::R8Unorm | Self::R16Float | Self::R32Float | Self::R32Uint | Self::R32Sint => 1,
+            Self::RG8Unorm | Self::RG16Float | Self::RG32Float | Self::RG32Uint => 2,
+            _ => 4,
+        }
+    }
+
+    /// Gets the number of bytes that every component of this format occupies.
+    pub fn get_component_size(&self) -> u8 {
+        match self {
+            Self::R8Unorm
+            | Self::RG8Unorm
+            | Self::RGBA8Unorm
+            | Self::RGBA8Srgb
+            | Self::BGRA8Unorm
+            | Self::BGRA8Srgb => 1,
+            Self::R16Float | Self::RG16Float | Self::RGBA16Float => 2,
+            _ => 4,
+        }
+    }
+
+    /// Gets the number of bytes that every pixel of this format occupies.
+    pub fn get_pixel_size(&self) -> usize {
+        self.get_num_components() as usize * self.get_component_size() as usize
+    }
+
+    /// Gets the *ColorComponentKind* of this format.
+    pub fn get_component_kind(&self) -> ColorComponentKind {
+        match self {
+            Self::R8Unorm | Self::RG8Unorm | Self::RGBA8Unorm | Self::BGRA8Unorm => {
+                ColorComponentKind::Unorm
+            }
+            Self::RGBA8Srgb | Self::BGRA8Srgb => ColorComponentKind::Srgb,
+            Self::R32Uint | Self::RG32Uint | Self::RGBA32Uint => ColorComponentKind::Uint,
+            Self::R32Sint => ColorComponentKind::Sint,
+            _ => ColorComponentKind::Float,
+        }
+    }
+
+    /// Checks if the components of this format are stored in blue, green, red,
+    /// alpha order rather than in red, green, blue, alpha order. Note that this
+    /// only affects the way the pixels are stored: shaders will not notice it.
+    pub fn is_reversed(&self) -> bool {
+        *self == Self::BGRA8Unorm || *self == Self::BGRA8Srgb
+    }
+
+    /// Gets the *DataKind* that fragment shader variables need in order to write
+    /// to grids of this format: *UINT* for the *Uint* formats, *INT* for the
+    /// *Sint* formats, and *FLOAT* for all other formats.
+    pub fn get_data_kind(&self) -> DataKind {
+        match self.get_component_kind() {
+            ColorComponentKind::Uint => UINT,
+            ColorComponentKind::Sint => INT,
+            _ => FLOAT,
+        }
+    }
+
+    /// Checks if a fragment shader *ColorOutput* variable with the given
+    /// *DataType* can write to a color grid with this format. This is the case
+    /// if its *DataKind* is the *get_data_kind()* of this format and it is a
+    /// single value or vector with at least *get_num_components()* components.
+    ///
+    /// Griphin implementations should use this to validate the outputs of the
+    /// drawing nodes of *RenderFlow*s.
+    pub fn validate_output(&self, output_type: DataType) -> Result<(), ColorFormatMismatch> {
+        let shape = output_type.get_shape();
+        if output_type.get_kind() != self.get_data_kind()
+            || shape.get_num_columns() != 1
+            || shape.get_num_rows() < self.get_num_components()
+        {
+            return Err(ColorFormatMismatch {
+                format: *self,
+                output_type,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// This error indicates that a fragment shader *ColorOutput* variable can't
+/// write to a color grid, because its *DataType* doesn't match the
+/// *ColorFormat* of that grid. See the *validate_output* method of
+/// *ColorFormat*.
+#[derive(Debug)]
+pub struct ColorFormatMismatch {
+    format: ColorFormat,
+    output_type: DataType,
+}
+
+impl ColorFormatMismatch {
+    /// Gets the *ColorFormat* of the color grid.
+    pub fn get_format(&self) -> ColorFormat {
+        self.format
+    }
+
+    /// Gets the *DataType* of the output variable.
+    pub fn get_output_type(&self) -> DataType {
+        self.output_type
+    }
+}
+
+impl Display for ColorFormatMismatch {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "A color output of type {} can't write to a color grid with format {:?}, \
+            which needs a {} value or vector with at least {} components",
+            self.output_type.get_glsl_name(),
+            self.format,
+            self.format.get_data_kind().get_long_name(),
+            self.format.get_num_components()
+        )
+    }
+}
+
+impl Error for ColorFormatMismatch {}
+
+/// This error indicates that an output of a drawing node of a
+/// *RenderFlowBuilder* doesn't match the grid it writes to. See the
+/// *validate_color_formats* method of *RenderFlowBuilder*.
+#[derive(Debug)]
+pub enum ColorOutputError {
+    /// A grid node doesn't belong to the *RenderFlowBuilder*.
+    UnknownGridNode { node: GridNodeID },
+
+    /// The fragment shader of the pipeline doesn't have a *ColorOutput* or
+    /// *DepthStencilOutput* variable with the name of the output.
+    UnknownOutput { name: StringRef },
+
+    /// A *ColorOutput* variable writes to a depth stencil grid, or a
+    /// *DepthStencilOutput* variable writes to a color grid.
+    GridKindMismatch {
+        name: StringRef,
+        grid: AbstractGridID,
+    },
+
+    /// The *DataType* of a *ColorOutput* variable doesn't match the
+    /// *ColorFormat* of the color grid it writes to.
+    FormatMismatch {
+        name: StringRef,
+        grid: AbstractGridID,
+        mismatch: ColorFormatMismatch,
+    },
+}
+
+impl Display for ColorOutputError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::UnknownGridNode { node } => write!(
+                f,
+                "The grid node {:?} doesn't belong to this RenderFlowBuilder",
+                node
+            ),
+            Self::UnknownOutput { name } => write!(
+                f,
+                "The fragment shader doesn't have an output variable named {}",
+                name
+            ),
+            Self::GridKindMismatch { name, grid } => write!(
+                f,
+                "The output variable {} can't write to grid {:?}, because it is \
+                a different kind of grid",
+                name, grid
+            ),
+            Self::FormatMismatch {
+                name,
+                grid,
+                mismatch,
+            } => write!(
+                f,
+                "The output variable {} can't write to grid {:?}: {}",
+                name, grid, mismatch
+            ),
+        }
+    }
+}
+
+impl Error for ColorOutputError {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_validate_output() {
+        let vec4 = DataType::new(FLOAT, VEC4);
+        assert!(ColorFormat::RGBA8Unorm.validate_output(vec4).is_ok());
+        assert!(ColorFormat::RGBA16Float.validate_output(vec4).is_ok());
+        assert!(ColorFormat::RG16Float.validate_output(vec4).is_ok());
+        assert!(ColorFormat::R32Uint.validate_output(vec4).is_err());
+
+        let vec2 = DataType::new(FLOAT, VEC2);
+        assert!(ColorFormat::RG16Float.validate_output(vec2).is_ok());
+        assert!(ColorFormat::RGBA32Float.validate_output(vec2).is_err());
+
+        assert!(ColorFormat::R32Uint
+            .validate_output(DataType::new(UINT, SINGLE))
+            .is_ok());
+        assert!(ColorFormat::R32Uint
+            .validate_output(DataType::new(INT, SINGLE))
+            .is_err());
+        assert!(ColorFormat::RGBA8Unorm
+            .validate_output(DataType::new(FLOAT, MAT2))
+            .is_err());
+    }
+
+    #[test]
+    fn test_sizes() {
+        assert_eq!(4, ColorFormat::RGBA8Srgb.get_pixel_size());
+        assert_eq!(4, ColorFormat::RG16Float.get_pixel_size());
+        assert_eq!(16, ColorFormat::RGBA32Float.get_pixel_size());
+        assert_eq!(4, ColorFormat::R32Uint.get_pixel_size());
+    }
+}
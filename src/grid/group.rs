@@ -10,28 +10,44 @@ use std::sync::Arc;
 /// *AbstractGridGroup*s are 'abstract' in the sense that they do *not* occupy any
 /// GPU memory and not much RAM either. Also, this trait doesn't even dictate the
 /// size of its concrete *GridGroup*s, so it's not required that they all have the
-/// same size.
+/// same size. (It only dictates the *GridSize* policy of each grid, which is
+/// relative to the size of the concrete *GridGroup* or a fixed size.)
 ///
 /// *AbstractGridGroup*s are very important because they are the 'entrypoint' from
 /// which you can create concrete *GridGroup*s, *GraphicsPipeline*s, and
 /// *RenderFlow(Builder)*s. (Use the methods of this trait for this.)
 ///
+/// Since abstract grid groups don't dictate the size of their concrete grid
+/// groups, the *GraphicsPipeline*s and *RenderFlow*s created by an abstract grid
+/// group stay valid for *all* of its concrete *GridGroup*s, regardless of their
+/// size. In particular, they stay valid when a *GridGroup* is resized (for
+/// instance because the window was resized): only the concrete grids need to be
+/// recreated. See the *resize* method of *GridGroup*.
+///
 /// To obtain an instance of *AbstractGridGroup*, use the
 /// *create_abstract_grid_group* method of the *Instance*.
-pub trait AbstractGridGroup {
+///
+/// All methods that take a grid id expect an id of a grid of this group. Griphin
+/// implementations should check this with the *check* methods of *GridGroupIDs*,
+/// and panic with the *GridIDError* if an id of another group or another
+/// *Instance* is used.
+pub trait AbstractGridGroup: Send + Sync {
     /// This method should be used by the Griphin implementation to get access to
     /// the implementation-specific struct that implements this trait.
     fn as_any(&self) -> &dyn Any;
 
     /// Creates a concrete *GridGroup* based on this *AbstractGridGroup*, with the
-    /// given *width* and *height*.
+    /// given *width* and *height*. The size of each of its grids is determined by
+    /// the *GridSize* of that grid (see its *resolve* method).
     ///
     /// Only *GridGroup*s created from this *AbstractGridGroup* can be used for
     /// the *GraphicsPipeline*s and *RenderFlow*s that are also created by this
     /// *AbstractGridGroup*.
     fn create_concrete(&self, width: u32, height: u32) -> Arc<dyn GridGroup>;
 
-    /// Creates a *GraphicsPipeline* using the given *ShaderPair* and *topology*.
+    /// Creates a *GraphicsPipeline* using the given *ShaderPair*, *topology*, and
+    /// fixed-function *state*. Use *PipelineStateBuilder::new()* if the default
+    /// state is good enough.
     ///
     /// The created *GraphicsPipeline* can only be used during *RenderFlow*s that
     /// are also created by this *AbstractGridGroup*, and the pipeline will only
@@ -40,8 +56,38 @@ pub trait AbstractGridGroup {
         &self,
         shaders: &ShaderPair,
         topology: PrimitiveTopology,
+        state: &PipelineStateBuilder,
     ) -> Arc<dyn GraphicsPipeline>;
 
+    /// Gets the *ColorFormat* of the abstract color grid with the given id. This
+    /// is the *format* of the *AbstractColorGridBuilder* from which it was
+    /// created.
+    fn get_color_format(&self, id: ColorGridID) -> ColorFormat;
+
+    /// Gets the *SampleCount* of the abstract (color or depth stencil) grid with
+    /// the given id. This is the *sample_count* of the builder from which it was
+    /// created.
+    fn get_sample_count(&self, id: AbstractGridID) -> SampleCount;
+
+    /// Gets the *GridSize* of the abstract (color or depth stencil) grid with the
+    /// given id. This is the *size* of the builder from which it was created.
+    fn get_grid_size(&self, id: AbstractGridID) -> GridSize;
+
+    /// Checks if the abstract (color or depth stencil) grid with the given id was
+    /// created with the *ShaderRead* purpose (*ColorPurpose::ShaderRead* or
+    /// *DepthStencilPurpose::ShaderRead*).
+    fn has_shader_read_purpose(&self, id: AbstractGridID) -> bool;
+
+    /// Checks if the abstract (color or depth stencil) grid with the given id is
+    /// transient: its content is not needed before or after a *RenderFlow*. See
+    /// the *is_transient* methods of *AbstractColorGridBuilder* and
+    /// *AbstractDepthStencilGridBuilder*.
+    ///
+    /// Transient grids with disjoint lifetimes can share their memory. Griphin
+    /// implementations can use *GridAliasingPlan* to decide which grids share
+    /// memory.
+    fn is_transient(&self, id: AbstractGridID) -> bool;
+
     /// Creates a new empty *RenderFlowBuilder* that you will need to 'fill in' and
     /// then pass to the *create_render_flow* method of this *AbstractGridGroup* to
     /// turn it into a usable *RenderFlow*.
@@ -50,6 +96,18 @@ pub trait AbstractGridGroup {
     /// Creates a *RenderFlow* that will use the graph from the given
     /// *RenderFlowBuilder*.
     ///
+    /// The Griphin implementation should check that the *ColorOutput* variables
+    /// of the drawing nodes match the *ColorFormat* of the grids they write to
+    /// (see the *validate_color_formats* method of *RenderFlowBuilder*), and
+    /// that the sample counts of the pipelines, grids, and resolves match (see
+    /// the *validate_sample_counts* method of *RenderFlowBuilder*). It should also
+    /// check that all grids that are read by shaders have the *ShaderRead*
+    /// purpose (see the *validate_grid_inputs* method of *RenderFlowBuilder*),
+    /// that the grid nodes and buffer nodes are linked correctly (see the
+    /// *validate_grid_nodes* and *validate_buffer_nodes* methods of
+    /// *RenderFlowBuilder*), and that the compute nodes match their shaders
+    /// (see the *validate_compute_nodes* method of *RenderFlowBuilder*).
+    ///
     /// This *RenderFlow* can only be used on *GridGroup*s that are created by this
     /// same *AbstractGridGroup* and only *GraphicsPipeline*s created by this same
     /// *AbstractGridGroup* can be used during the *RenderFlow*.
@@ -65,13 +123,41 @@ pub trait AbstractGridGroup {
 /// Furthermore, the grids can do a couple of other operations, like copying its
 /// content to other grids (even to other *GridGroup*s), and they can be displayed
 /// to the screen.
-pub trait GridGroup {
+pub trait GridGroup: Send + Sync {
     fn as_any(&self) -> &dyn Any;
 
     // TODO Stabilize the use cases of individual grids
-    fn get_color_grid(&self, id: AbstractGridID) -> Arc<dyn ColorGrid>;
+    fn get_color_grid(&self, id: ColorGridID) -> Arc<dyn ColorGrid>;
 
-    fn get_depth_stencil_grid(&self, id: AbstractGridID) -> Arc<dyn DepthStencilGrid>;
+    fn get_depth_stencil_grid(&self, id: DepthStencilGridID) -> Arc<dyn DepthStencilGrid>;
+
+    /// Gets the width of this *GridGroup*, as given to the *create_concrete*
+    /// method of its *AbstractGridGroup* (or to the *resize* method).
+    fn get_width(&self) -> u32;
+
+    /// Gets the height of this *GridGroup*, as given to the *create_concrete*
+    /// method of its *AbstractGridGroup* (or to the *resize* method).
+    fn get_height(&self) -> u32;
+
+    /// Gets the (width, height) of the concrete grid with the given id. This is
+    /// the result of resolving its *GridSize* for the size of this group.
+    fn get_grid_dimensions(&self, id: AbstractGridID) -> (u32, u32);
+
+    /// Creates a new *GridGroup* with the same *AbstractGridGroup* as this one,
+    /// but with the given *width* and *height*. This is typically used when the
+    /// window is resized. The *content* determines whether the content of the
+    /// grids of this group should be copied to the new group (see the
+    /// documentation of *ResizeContent*).
+    ///
+    /// Only the concrete grids will be recreated: all *GraphicsPipeline*s and
+    /// *RenderFlow*s of the *AbstractGridGroup* can be used on the new group
+    /// without any changes. Grids with a *Relative* *GridSize* will get a new
+    /// size, whereas grids with an *Absolute* *GridSize* keep their size (and
+    /// their content will simply be copied unless *content* is *Discard*). This
+    /// *GridGroup* itself remains valid as well (and keeps its old size) until
+    /// it is dropped, so *RenderFlow*s that are still running on it can finish
+    /// normally.
+    fn resize(&self, width: u32, height: u32, content: ResizeContent) -> Arc<dyn GridGroup>;
 }
 
 /// *AbstractGridGroupBuilder* structs contain the information needed to create
@@ -93,22 +179,23 @@ pub struct AbstractGridGroupBuilder {
     pub depth_stencil_grids: Vec<AbstractDepthStencilGridBuilder>,
 }
 
-/// An instance of this struct is returned alongside its corresponding
-/// *AbstractGridGroup* by the *create_abstract_grid_group* method of the
-/// *Instance*.
-///
-/// This struct is needed by the user to find out which *AbstractGridID*s were
-/// assigned to which *AbstractColorGridBuilder*s and which
-/// *AbstractDepthStencilGridBuilder*s. This is the only reliable way in
-/// which the user can get the right *AbstractGridID*s, so these should *not*
-/// be forgotten.
-///
-/// The *colors* component of this struct corresponds to the *color_grids*
-/// component of the *AbstractGridGroupBuilder*: the id that got assigned
-/// to *color_grids[i]* is stored in *colors[i]* (for all 0 <= i <
-/// *color_grids.len()*). Similarly, the id that got assigned to
-/// *depth_stencil_grids[i]* is stored in *depth_stencils[i]*.
-pub struct GridGroupIDs {
-    pub colors: Vec<AbstractGridID>,
-    pub depth_stencils: Vec<AbstractGridID>,
+impl AbstractGridGroupBuilder {
+    /// Checks that every color grid with the *Display* purpose has
+    /// *SampleCount::One*. The *ids* must be the *GridGroupIDs* that were
+    /// allocated for this builder: they are used to report the grid in the
+    /// error.
+    ///
+    /// Griphin implementations should use this in their
+    /// *create_abstract_grid_group* method.
+    pub fn validate_sample_counts(&self, ids: &GridGroupIDs) -> Result<(), SampleCountError> {
+        for (grid, id) in self.color_grids.iter().zip(&ids.colors) {
+            if grid.purpose == ColorPurpose::Display && grid.sample_count.is_multisampled() {
+                return Err(SampleCountError::DisplayMultisampled {
+                    grid: id.get_abstract(),
+                    sample_count: grid.sample_count,
+                });
+            }
+        }
+        Ok(())
+    }
 }
@@ -1,3 +1,10 @@
+use crate::*;
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU32, Ordering};
+
 /// Represents the id and 'handle' of an abstract grid (can be either an
 /// *AbstractColorGrid* or an *AbstractDepthStencilGrid*). This handle has
 /// multiple purposes (currently only 2), which are listed below:
@@ -6,29 +13,45 @@
 /// *RenderFlowBuilder*s.
 ///
 /// (2) Getting an instance of a concrete *ColorGrid* or *DepthStencilGrid* from
-/// a concrete *GridGroup*.
+/// a concrete *GridGroup*. (For this purpose, you need the typed *ColorGridID*
+/// or *DepthStencilGridID* instead.)
 ///
 /// When creating an *AbstractGridGroup* via the *create_abstract_grid_group*
 /// method of the *Instance*, the second element of the returned pair are the
 /// *GridGroupIDs* of the abstract grid group. You need to get all the
 /// *AbstractGridID*s you need from there.
 ///
-/// Only Griphin implementations should create instances of this struct.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Every id remembers the *Instance* and the *AbstractGridGroup* that created it,
+/// so Griphin implementations can detect ids that are used with the wrong group
+/// (see the *check* method of *GridGroupIDs*). Ids can only be created by a
+/// *GridIDAllocator*, so they can't be forged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct AbstractGridID {
+    instance_id: u32,
     group_id: u32,
     local_id: u16,
+    kind: GridKind,
+}
+
+/// The kind of grid that an *AbstractGridID* refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GridKind {
+    Color,
+    DepthStencil,
 }
 
 impl AbstractGridID {
-    /// Creates a new *AbstractGridID*. This should only be used by Griphin
-    /// implementations.
-    pub fn new(group_id: u32, local_id: u16) -> Self {
-        Self { group_id, local_id }
+    /// Gets the id of the *Instance* that created this *AbstractGridID* (more
+    /// precisely, the id of its *GridIDAllocator*).
+    ///
+    /// This should only be used by implementations of Griphin.
+    pub fn get_instance_id(&self) -> u32 {
+        self.instance_id
     }
 
-    /// Gets the *group* id of this *AbstractGridID*. This should be a unique
-    /// identifier for the *AbstractGridGroup* that owns this abstract grid.
+    /// Gets the *group* id of this *AbstractGridID*. This is a unique identifier
+    /// (within its *Instance*) for the *AbstractGridGroup* that owns this abstract
+    /// grid.
     ///
     /// This should only be used by implementations of Griphin.
     pub fn get_group_id(&self) -> u32 {
@@ -36,11 +59,381 @@ impl AbstractGridID {
     }
 
     /// Gets the *local* id of this *AbstractGridID*. Every abstract grid in
-    /// the same *AbstractGridGroup* should have a distinct local id, but
-    /// abstract grids in different groups can share the same local id.
+    /// the same *AbstractGridGroup* has a distinct local id, but abstract grids
+    /// in different groups can share the same local id. The color grids get the
+    /// local ids 0, 1, 2, ..., and the depth stencil grids get the next ones.
     ///
     /// This should only be used by implementations of Griphin.
     pub fn get_local_id(&self) -> u16 {
         self.local_id
     }
+
+    /// Gets the *GridKind* of the grid this id refers to.
+    pub fn get_kind(&self) -> GridKind {
+        self.kind
+    }
+
+    /// Gets the typed *ColorGridID* of this id, or *None* if this id refers to a
+    /// depth stencil grid.
+    pub fn as_color(&self) -> Option<ColorGridID> {
+        ColorGridID::try_from(*self).ok()
+    }
+
+    /// Gets the typed *DepthStencilGridID* of this id, or *None* if this id refers
+    /// to a color grid.
+    pub fn as_depth_stencil(&self) -> Option<DepthStencilGridID> {
+        DepthStencilGridID::try_from(*self).ok()
+    }
+}
+
+/// The id of an abstract color grid. This is simply an *AbstractGridID* whose
+/// *GridKind* is *Color*. Use *into()* or *get_abstract()* to get the
+/// *AbstractGridID*.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColorGridID {
+    id: AbstractGridID,
+}
+
+impl ColorGridID {
+    /// Gets the *AbstractGridID* of this color grid id.
+    pub fn get_abstract(&self) -> AbstractGridID {
+        self.id
+    }
+}
+
+impl From<ColorGridID> for AbstractGridID {
+    fn from(id: ColorGridID) -> Self {
+        id.id
+    }
+}
+
+impl TryFrom<AbstractGridID> for ColorGridID {
+    type Error = GridIDError;
+
+    fn try_from(id: AbstractGridID) -> Result<Self, GridIDError> {
+        if id.kind == GridKind::Color {
+            Ok(Self { id })
+        } else {
+            Err(GridIDError::WrongKind {
+                id,
+                expected: GridKind::Color,
+            })
+        }
+    }
+}
+
+/// The id of an abstract depth stencil grid. This is simply an *AbstractGridID*
+/// whose *GridKind* is *DepthStencil*. Use *into()* or *get_abstract()* to get
+/// the *AbstractGridID*.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DepthStencilGridID {
+    id: AbstractGridID,
+}
+
+impl DepthStencilGridID {
+    /// Gets the *AbstractGridID* of this depth stencil grid id.
+    pub fn get_abstract(&self) -> AbstractGridID {
+        self.id
+    }
+}
+
+impl From<DepthStencilGridID> for AbstractGridID {
+    fn from(id: DepthStencilGridID) -> Self {
+        id.id
+    }
+}
+
+impl TryFrom<AbstractGridID> for DepthStencilGridID {
+    type Error = GridIDError;
+
+    fn try_from(id: AbstractGridID) -> Result<Self, GridIDError> {
+        if id.kind == GridKind::DepthStencil {
+            Ok(Self { id })
+        } else {
+            Err(GridIDError::WrongKind {
+                id,
+                expected: GridKind::DepthStencil,
+            })
+        }
+    }
+}
+
+static NEXT_INSTANCE_ID: AtomicU32 = AtomicU32::new(0);
+
+/// Allocates the *AbstractGridID*s of the *AbstractGridGroup*s of an *Instance*.
+/// Every *Instance* owns its own *GridIDAllocator* (see the *create_instance*
+/// method), and uses it in its *create_abstract_grid_group* method.
+///
+/// Every allocator gets a unique instance id (within the process), and every
+/// group that is allocated by an allocator gets a unique group id (within that
+/// allocator). This guarantees that ids of different groups and different
+/// instances will never be equal.
+#[derive(Debug)]
+pub struct GridIDAllocator {
+    instance_id: u32,
+    next_group_id: AtomicU32,
+}
+
+impl GridIDAllocator {
+    /// Creates a new *Instance* that owns a new *GridIDAllocator* with a unique
+    /// instance id. Griphin implementations should use this to construct their
+    /// *Instance* struct: *create_instance* gets the allocator, which the
+    /// instance should store and return from its *get_grid_id_allocator* method.
+    /// This is the only way to obtain a new *GridIDAllocator*, so every allocator
+    /// belongs to exactly 1 *Instance*.
+    pub fn create_instance<I: Instance>(create_instance: impl FnOnce(Self) -> I) -> I {
+        create_instance(Self::new())
+    }
+
+    /// Constructs a new *GridIDAllocator* with a unique instance id. Outside this
+    /// crate, allocators can only be created with *create_instance*.
+    pub(crate) fn new() -> Self {
+        Self {
+            instance_id: NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed),
+            next_group_id: AtomicU32::new(0),
+        }
+    }
+
+    /// Gets the unique instance id of this allocator.
+    pub fn get_instance_id(&self) -> u32 {
+        self.instance_id
+    }
+
+    /// Allocates the ids for a new *AbstractGridGroup* that is created from the
+    /// given *builder*.
+    ///
+    /// This method panics if the builder has more than 65536 grids in total.
+    pub fn allocate(&self, builder: &AbstractGridGroupBuilder) -> GridGroupIDs {
+        self.allocate_ids(builder.color_grids.len(), builder.depth_stencil_grids.len())
+    }
+
+    /// Allocates the ids for a new *AbstractGridGroup* with the given number of
+    /// color grids and depth stencil grids.
+    ///
+    /// This method panics if there are more than 65536 grids in total.
+    pub fn allocate_ids(
+        &self,
+        num_color_grids: usize,
+        num_depth_stencil_grids: usize,
+    ) -> GridGroupIDs {
+        let num_grids = num_color_grids + num_depth_stencil_grids;
+        if num_grids > u16::MAX as usize + 1 {
+            panic!(
+                "An abstract grid group can have at most 65536 grids, but it has {}",
+                num_grids
+            );
+        }
+
+        let group_id = self.next_group_id.fetch_add(1, Ordering::Relaxed);
+        let create_id = |local_id: usize, kind: GridKind| AbstractGridID {
+            instance_id: self.instance_id,
+            group_id,
+            local_id: local_id as u16,
+            kind,
+        };
+        GridGroupIDs {
+            colors: (0..num_color_grids)
+                .map(|local_id| ColorGridID {
+                    id: create_id(local_id, GridKind::Color),
+                })
+                .collect(),
+            depth_stencils: (num_color_grids..num_grids)
+                .map(|local_id| DepthStencilGridID {
+                    id: create_id(local_id, GridKind::DepthStencil),
+                })
+                .collect(),
+            instance_id: self.instance_id,
+            group_id,
+        }
+    }
+}
+
+/// This error indicates that an *AbstractGridID* was used in a place where it
+/// doesn't belong. See the *check* method of *GridGroupIDs*.
+#[derive(Debug)]
+pub enum GridIDError {
+    /// The id was created by another *Instance*.
+    OtherInstance {
+        id: AbstractGridID,
+        expected_instance: u32,
+    },
+
+    /// The id belongs to another *AbstractGridGroup* of the same *Instance*.
+    OtherGroup {
+        id: AbstractGridID,
+        expected_group: u32,
+    },
+
+    /// The id refers to a color grid where a depth stencil grid was expected, or
+    /// vice versa.
+    WrongKind {
+        id: AbstractGridID,
+        expected: GridKind,
+    },
+}
+
+impl Display for GridIDError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::OtherInstance {
+                id,
+                expected_instance,
+            } => write!(
+                f,
+                "Grid {:?} was created by instance {}, but it was used with instance {}",
+                id, id.instance_id, expected_instance
+            ),
+            Self::OtherGroup { id, expected_group } => write!(
+                f,
+                "Grid {:?} belongs to grid group {}, but it was used with grid group {}",
+                id, id.group_id, expected_group
+            ),
+            Self::WrongKind { id, expected } => write!(
+                f,
+                "Grid {:?} is a {:?} grid, but a {:?} grid was expected",
+                id, id.kind, expected
+            ),
+        }
+    }
+}
+
+impl Error for GridIDError {}
+
+/// An instance of this struct is returned alongside its corresponding
+/// *AbstractGridGroup* by the *create_abstract_grid_group* method of the
+/// *Instance*.
+///
+/// This struct is needed by the user to find out which *ColorGridID*s were
+/// assigned to which *AbstractColorGridBuilder*s and which
+/// *DepthStencilGridID*s were assigned to which *AbstractDepthStencilGridBuilder*s.
+/// This is the only reliable way in which the user can get the right ids, so
+/// these should *not* be forgotten.
+///
+/// The *colors* component of this struct corresponds to the *color_grids*
+/// component of the *AbstractGridGroupBuilder*: the id that got assigned
+/// to *color_grids[i]* is stored in *colors[i]* (for all 0 <= i <
+/// *color_grids.len()*). Similarly, the id that got assigned to
+/// *depth_stencil_grids[i]* is stored in *depth_stencils[i]*.
+///
+/// Instances of this struct can only be created by a *GridIDAllocator*. Griphin
+/// implementations should keep a copy in their *AbstractGridGroup*, and use its
+/// *check* methods to validate the ids they are given.
+#[derive(Debug, Clone)]
+pub struct GridGroupIDs {
+    pub colors: Vec<ColorGridID>,
+    pub depth_stencils: Vec<DepthStencilGridID>,
+
+    instance_id: u32,
+    group_id: u32,
+}
+
+impl GridGroupIDs {
+    /// Gets the id of the *Instance* that created the group of these ids.
+    pub fn get_instance_id(&self) -> u32 {
+        self.instance_id
+    }
+
+    /// Gets the group id of the group of these ids.
+    pub fn get_group_id(&self) -> u32 {
+        self.group_id
+    }
+
+    /// Checks that the given *id* belongs to the group of these ids, and returns
+    /// a descriptive error if it doesn't.
+    pub fn check(&self, id: AbstractGridID) -> Result<(), GridIDError> {
+        if id.instance_id != self.instance_id {
+            return Err(GridIDError::OtherInstance {
+                id,
+                expected_instance: self.instance_id,
+            });
+        }
+        if id.group_id != self.group_id {
+            return Err(GridIDError::OtherGroup {
+                id,
+                expected_group: self.group_id,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that the given *id* belongs to the group of these ids and refers to
+    /// a color grid, and converts it to a *ColorGridID*.
+    pub fn check_color(&self, id: AbstractGridID) -> Result<ColorGridID, GridIDError> {
+        self.check(id)?;
+        ColorGridID::try_from(id)
+    }
+
+    /// Checks that the given *id* belongs to the group of these ids and refers to
+    /// a depth stencil grid, and converts it to a *DepthStencilGridID*.
+    pub fn check_depth_stencil(
+        &self,
+        id: AbstractGridID,
+    ) -> Result<DepthStencilGridID, GridIDError> {
+        self.check(id)?;
+        DepthStencilGridID::try_from(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_allocate() {
+        let allocator = GridIDAllocator::new();
+        let ids1 = allocator.allocate_ids(2, 1);
+        let ids2 = allocator.allocate_ids(1, 0);
+
+        assert_eq!(2, ids1.colors.len());
+        assert_eq!(1, ids1.depth_stencils.len());
+        assert_ne!(ids1.get_group_id(), ids2.get_group_id());
+        assert_ne!(ids1.colors[0].get_abstract(), ids2.colors[0].get_abstract());
+        assert_eq!(2, ids1.depth_stencils[0].get_abstract().get_local_id());
+        assert_eq!(
+            GridKind::DepthStencil,
+            ids1.depth_stencils[0].get_abstract().get_kind()
+        );
+
+        let other_allocator = GridIDAllocator::new();
+        assert_ne!(
+            allocator.get_instance_id(),
+            other_allocator.get_instance_id()
+        );
+        let other_ids = other_allocator.allocate_ids(1, 0);
+        assert_eq!(ids2.get_group_id(), other_ids.get_group_id() + 1);
+        assert_ne!(ids1.colors[0], other_ids.colors[0]);
+    }
+
+    #[test]
+    fn test_check() {
+        let allocator = GridIDAllocator::new();
+        let ids1 = allocator.allocate_ids(1, 1);
+        let ids2 = allocator.allocate_ids(1, 1);
+        let other_ids = GridIDAllocator::new().allocate_ids(1, 1);
+
+        let color = ids1.colors[0].get_abstract();
+        let depth = ids1.depth_stencils[0].get_abstract();
+        assert!(ids1.check(color).is_ok());
+        assert_eq!(ids1.colors[0], ids1.check_color(color).unwrap());
+        assert!(ids1.check_depth_stencil(depth).is_ok());
+
+        assert!(matches!(
+            ids1.check_color(depth),
+            Err(GridIDError::WrongKind {
+                expected: GridKind::Color,
+                ..
+            })
+        ));
+        assert!(matches!(
+            ids1.check(ids2.colors[0].into()),
+            Err(GridIDError::OtherGroup { .. })
+        ));
+        assert!(matches!(
+            ids1.check(other_ids.colors[0].into()),
+            Err(GridIDError::OtherInstance { .. })
+        ));
+        assert!(color.as_color().is_some());
+        assert!(color.as_depth_stencil().is_none());
+    }
 }
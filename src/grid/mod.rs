@@ -1,13 +1,29 @@
+mod aliasing;
 mod color;
+mod color_f32;
 mod depth_stencil;
 mod dump;
+mod format;
 mod group;
 mod id;
 mod region;
+mod region_set;
+mod resize;
+mod sample;
+mod sampler;
+mod size;
 
+pub use aliasing::*;
 pub use color::*;
+pub use color_f32::*;
 pub use depth_stencil::*;
 pub use dump::*;
+pub use format::*;
 pub use group::*;
 pub use id::*;
 pub use region::*;
+pub use region_set::*;
+pub use resize::*;
+pub use sample::*;
+pub use sampler::*;
+pub use size::*;
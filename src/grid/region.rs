@@ -2,8 +2,12 @@
 /// *DepthStencilGrid*. It is just a pair of (x,y,width,height) and
 /// doesn't store any data about any grid.
 ///
-/// TODO Add more use cases for this struct (besides debug dumps)
-#[derive(Clone, Copy, Debug)]
+/// Regions can be empty (when their width or height is 0), but the bounds of a
+/// region always fit in a *u32*: the *new* function checks this.
+///
+/// Regions are used for debug dumps, scissor rectangles, and dirty rectangle
+/// tracking (see *GridRegionSet*).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct GridRegion {
     min_x: u32,
     min_y: u32,
@@ -12,13 +16,50 @@ pub struct GridRegion {
 }
 
 impl GridRegion {
+    /// Constructs a new *GridRegion* with the given minimum coordinates and size.
+    ///
+    /// This function panics if *min_x + width* or *min_y + height* doesn't fit in
+    /// a *u32*. Use *try_new* if you want to handle that case yourself.
     pub fn new(min_x: u32, min_y: u32, width: u32, height: u32) -> Self {
-        Self {
+        match Self::try_new(min_x, min_y, width, height) {
+            Some(region) => region,
+            None => panic!(
+                "The bounds of the region ({}, {}, {}, {}) don't fit in a u32",
+                min_x, min_y, width, height
+            ),
+        }
+    }
+
+    /// Constructs a new *GridRegion* with the given minimum coordinates and size,
+    /// or returns *None* if *min_x + width* or *min_y + height* doesn't fit in a
+    /// *u32*.
+    pub fn try_new(min_x: u32, min_y: u32, width: u32, height: u32) -> Option<Self> {
+        min_x.checked_add(width)?;
+        min_y.checked_add(height)?;
+        Some(Self {
             min_x,
             min_y,
             width,
             height,
-        }
+        })
+    }
+
+    /// Constructs the *GridRegion* that covers an entire grid with the given
+    /// width and height.
+    pub fn full(grid_width: u32, grid_height: u32) -> Self {
+        Self::new(0, 0, grid_width, grid_height)
+    }
+
+    /// Constructs the *GridRegion* whose minimum coordinates are (min_x, min_y)
+    /// and whose bounds are (bound_x, bound_y). If a bound is smaller than the
+    /// corresponding minimum coordinate, the region will be empty.
+    pub fn from_bounds(min_x: u32, min_y: u32, bound_x: u32, bound_y: u32) -> Self {
+        Self::new(
+            min_x,
+            min_y,
+            bound_x.saturating_sub(min_x),
+            bound_y.saturating_sub(min_y),
+        )
     }
 
     /// Gets the smallest x-coordinate that is part of the region described by
@@ -34,15 +75,23 @@ impl GridRegion {
     }
 
     /// Gets the largest x-coordinate that is part of the region described by
-    /// this *GridRegion*.
-    pub fn get_max_x(&self) -> u32 {
-        self.min_x + self.width - 1
+    /// this *GridRegion*, or *None* if this region is empty.
+    pub fn get_max_x(&self) -> Option<u32> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.min_x + self.width - 1)
+        }
     }
 
     /// Gets the largest y-coordinate that is part of the region described by
-    /// this *GridRegion*.
-    pub fn get_max_y(&self) -> u32 {
-        self.min_y + self.height - 1
+    /// this *GridRegion*, or *None* if this region is empty.
+    pub fn get_max_y(&self) -> Option<u32> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.min_y + self.height - 1)
+        }
     }
 
     /// Gets the x-coordinate such that x - 1 is part of the region described by
@@ -66,4 +115,199 @@ impl GridRegion {
     pub fn get_height(&self) -> u32 {
         self.height
     }
+
+    /// Gets the number of pixels in this region.
+    pub fn get_area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+
+    /// Checks if this region doesn't contain any pixels (because its width or
+    /// height is 0).
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Checks if the pixel at (x, y) is part of this region.
+    pub fn contains_point(&self, x: u32, y: u32) -> bool {
+        x >= self.min_x && y >= self.min_y && x < self.get_bound_x() && y < self.get_bound_y()
+    }
+
+    /// Checks if every pixel of *other* is also part of this region. Empty
+    /// regions are contained in every region.
+    pub fn contains(&self, other: &GridRegion) -> bool {
+        other.is_empty()
+            || (other.min_x >= self.min_x
+                && other.min_y >= self.min_y
+                && other.get_bound_x() <= self.get_bound_x()
+                && other.get_bound_y() <= self.get_bound_y())
+    }
+
+    /// Checks if this region and *other* have at least 1 pixel in common.
+    pub fn intersects(&self, other: &GridRegion) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Gets the region of all pixels that are part of both this region and
+    /// *other*, or *None* if they don't have any pixels in common.
+    pub fn intersection(&self, other: &GridRegion) -> Option<GridRegion> {
+        let result = Self::from_bounds(
+            self.min_x.max(other.min_x),
+            self.min_y.max(other.min_y),
+            self.get_bound_x().min(other.get_bound_x()),
+            self.get_bound_y().min(other.get_bound_y()),
+        );
+        if result.is_empty() {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    /// Gets the smallest region that contains both this region and *other*. Note
+    /// that this region can also contain pixels that are in neither of them. Empty
+    /// regions are ignored, so the union of an empty region and another region is
+    /// simply that other region.
+    pub fn union(&self, other: &GridRegion) -> GridRegion {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        Self::from_bounds(
+            self.min_x.min(other.min_x),
+            self.min_y.min(other.min_y),
+            self.get_bound_x().max(other.get_bound_x()),
+            self.get_bound_y().max(other.get_bound_y()),
+        )
+    }
+
+    /// Gets the part of this region that lies within a grid with the given
+    /// *grid_width* and *grid_height*. The result is empty if this region lies
+    /// completely outside the grid.
+    pub fn clamp_to_grid(&self, grid_width: u32, grid_height: u32) -> GridRegion {
+        self.intersection(&Self::full(grid_width, grid_height))
+            .unwrap_or_else(|| Self::new(0, 0, 0, 0))
+    }
+
+    /// Gets an iterator over the (x, y) coordinates of all pixels in this region.
+    /// The pixels are visited row by row, from top to bottom and from left to
+    /// right.
+    pub fn pixels(&self) -> impl Iterator<Item = (u32, u32)> {
+        let region = *self;
+        (region.min_y..region.get_bound_y())
+            .flat_map(move |y| (region.min_x..region.get_bound_x()).map(move |x| (x, y)))
+    }
+
+    /// Splits this region into tiles of *tile_width* by *tile_height* pixels, and
+    /// gets an iterator over these tiles (row by row). The tiles at the right and
+    /// bottom border will be smaller if the size of this region is not a multiple
+    /// of the tile size.
+    ///
+    /// This method panics if *tile_width* or *tile_height* is 0.
+    pub fn tiles(&self, tile_width: u32, tile_height: u32) -> impl Iterator<Item = GridRegion> {
+        if tile_width == 0 || tile_height == 0 {
+            panic!(
+                "The tile size must be positive, but it is {}x{}",
+                tile_width, tile_height
+            );
+        }
+        let region = *self;
+        let num_columns = region.width.div_ceil(tile_width);
+        let num_rows = region.height.div_ceil(tile_height);
+        (0..num_rows).flat_map(move |row| {
+            (0..num_columns).map(move |column| {
+                let min_x = region.min_x + column * tile_width;
+                let min_y = region.min_y + row * tile_height;
+                GridRegion::new(
+                    min_x,
+                    min_y,
+                    tile_width.min(region.get_bound_x() - min_x),
+                    tile_height.min(region.get_bound_y() - min_y),
+                )
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_empty() {
+        let region = GridRegion::new(5, 3, 0, 10);
+        assert!(region.is_empty());
+        assert_eq!(None, region.get_max_x());
+        assert_eq!(None, region.get_max_y());
+        assert_eq!(0, region.get_area());
+        assert_eq!(0, region.pixels().count());
+        assert!(!region.contains_point(5, 3));
+
+        let region = GridRegion::new(5, 3, 2, 10);
+        assert_eq!(Some(6), region.get_max_x());
+        assert_eq!(Some(12), region.get_max_y());
+    }
+
+    #[test]
+    fn test_checked_construction() {
+        assert!(GridRegion::try_new(u32::MAX, 0, 1, 1).is_none());
+        assert!(GridRegion::try_new(u32::MAX - 1, 0, 1, 1).is_some());
+        assert!(GridRegion::try_new(0, 1, 1, u32::MAX).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_overflow() {
+        GridRegion::new(10, 0, u32::MAX - 5, 1);
+    }
+
+    #[test]
+    fn test_intersection_and_union() {
+        let a = GridRegion::new(0, 0, 10, 10);
+        let b = GridRegion::new(5, 8, 10, 10);
+        let c = GridRegion::new(10, 0, 5, 5);
+
+        assert_eq!(Some(GridRegion::new(5, 8, 5, 2)), a.intersection(&b));
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&c));
+        assert_eq!(None, a.intersection(&c));
+
+        assert_eq!(GridRegion::new(0, 0, 15, 18), a.union(&b));
+        assert_eq!(a, a.union(&GridRegion::new(100, 100, 0, 0)));
+
+        assert!(a.contains(&GridRegion::new(2, 3, 8, 7)));
+        assert!(!a.contains(&b));
+        assert!(a.contains(&GridRegion::new(100, 100, 0, 0)));
+    }
+
+    #[test]
+    fn test_clamp_to_grid() {
+        let region = GridRegion::new(5, 5, 10, 10);
+        assert_eq!(GridRegion::new(5, 5, 3, 7), region.clamp_to_grid(8, 12));
+        assert!(region.clamp_to_grid(4, 4).is_empty());
+    }
+
+    #[test]
+    fn test_pixels() {
+        let pixels: Vec<(u32, u32)> = GridRegion::new(1, 2, 2, 2).pixels().collect();
+        assert_eq!(vec![(1, 2), (2, 2), (1, 3), (2, 3)], pixels);
+    }
+
+    #[test]
+    fn test_tiles() {
+        let tiles: Vec<GridRegion> = GridRegion::new(10, 20, 5, 3).tiles(2, 2).collect();
+        assert_eq!(
+            vec![
+                GridRegion::new(10, 20, 2, 2),
+                GridRegion::new(12, 20, 2, 2),
+                GridRegion::new(14, 20, 1, 2),
+                GridRegion::new(10, 22, 2, 1),
+                GridRegion::new(12, 22, 2, 1),
+                GridRegion::new(14, 22, 1, 1),
+            ],
+            tiles
+        );
+    }
 }
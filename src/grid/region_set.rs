@@ -0,0 +1,163 @@
+use crate::*;
+
+/// A set of *GridRegion*s, which is typically used to track the dirty
+/// rectangles of a grid (the parts of the grid that need to be redrawn).
+///
+/// When a region is added that overlaps or touches a region in the set, the two
+/// regions are merged into their *union* (which can also cover some pixels that
+/// were in neither of them). This keeps the number of regions small, and it
+/// guarantees that the regions in the set never overlap each other.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GridRegionSet {
+    regions: Vec<GridRegion>,
+}
+
+impl GridRegionSet {
+    /// Constructs a new empty *GridRegionSet*.
+    pub fn new() -> Self {
+        Self {
+            regions: Vec::new(),
+        }
+    }
+
+    /// Adds the given *region* to this set, and merges it with all regions in
+    /// this set that overlap or touch it. Empty regions are ignored.
+    pub fn add(&mut self, region: GridRegion) {
+        if region.is_empty() {
+            return;
+        }
+
+        let mut merged = region;
+        loop {
+            let num_regions = self.regions.len();
+            let mut remaining = Vec::with_capacity(num_regions);
+            for existing in self.regions.drain(..) {
+                if Self::overlaps_or_touches(&existing, &merged) {
+                    merged = merged.union(&existing);
+                } else {
+                    remaining.push(existing);
+                }
+            }
+            self.regions = remaining;
+
+            // The merged region may have grown into regions that were checked
+            // before it grew, so keep going until nothing changes anymore.
+            if self.regions.len() == num_regions {
+                break;
+            }
+        }
+        self.regions.push(merged);
+    }
+
+    fn overlaps_or_touches(a: &GridRegion, b: &GridRegion) -> bool {
+        a.get_min_x() <= b.get_bound_x()
+            && b.get_min_x() <= a.get_bound_x()
+            && a.get_min_y() <= b.get_bound_y()
+            && b.get_min_y() <= a.get_bound_y()
+    }
+
+    /// Gets the (non-overlapping) regions in this set.
+    pub fn get_regions(&self) -> &Vec<GridRegion> {
+        &self.regions
+    }
+
+    /// Checks if this set doesn't contain any regions.
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Removes all regions from this set.
+    pub fn clear(&mut self) {
+        self.regions.clear();
+    }
+
+    /// Checks if the pixel at (x, y) is part of any region in this set.
+    pub fn contains_point(&self, x: u32, y: u32) -> bool {
+        self.regions
+            .iter()
+            .any(|region| region.contains_point(x, y))
+    }
+
+    /// Gets the total number of pixels in the regions of this set.
+    pub fn get_area(&self) -> u64 {
+        self.regions.iter().map(|region| region.get_area()).sum()
+    }
+
+    /// Gets the smallest region that contains all regions of this set, or *None*
+    /// if this set is empty.
+    pub fn get_bounds(&self) -> Option<GridRegion> {
+        self.regions
+            .iter()
+            .copied()
+            .reduce(|bounds, region| bounds.union(&region))
+    }
+
+    /// Replaces every region in this set by its part that lies within a grid with
+    /// the given *grid_width* and *grid_height* (see the *clamp_to_grid* method
+    /// of *GridRegion*). Regions that lie completely outside the grid are removed.
+    pub fn clamp_to_grid(&mut self, grid_width: u32, grid_height: u32) {
+        self.regions = self
+            .regions
+            .iter()
+            .map(|region| region.clamp_to_grid(grid_width, grid_height))
+            .filter(|region| !region.is_empty())
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let mut set = GridRegionSet::new();
+        set.add(GridRegion::new(0, 0, 5, 5));
+        set.add(GridRegion::new(20, 20, 5, 5));
+        set.add(GridRegion::new(0, 0, 0, 0));
+        assert_eq!(2, set.get_regions().len());
+        assert_eq!(50, set.get_area());
+
+        // Touches the first region, so it will be merged with it
+        set.add(GridRegion::new(5, 0, 5, 5));
+        assert_eq!(2, set.get_regions().len());
+        assert!(set.get_regions().contains(&GridRegion::new(0, 0, 10, 5)));
+
+        // Overlaps both regions, so everything will be merged
+        set.add(GridRegion::new(8, 4, 13, 17));
+        assert_eq!(&vec![GridRegion::new(0, 0, 25, 25)], set.get_regions());
+    }
+
+    #[test]
+    fn test_chained_merge() {
+        let mut set = GridRegionSet::new();
+        set.add(GridRegion::new(0, 0, 2, 2));
+        set.add(GridRegion::new(2, 10, 2, 2));
+
+        // Only touches the second region, but their union touches the first region
+        set.add(GridRegion::new(4, 2, 5, 9));
+        assert_eq!(&vec![GridRegion::new(0, 0, 9, 12)], set.get_regions());
+    }
+
+    #[test]
+    fn test_bounds_and_clamp() {
+        let mut set = GridRegionSet::new();
+        assert_eq!(None, set.get_bounds());
+
+        set.add(GridRegion::new(1, 1, 2, 2));
+        set.add(GridRegion::new(10, 5, 10, 10));
+        assert_eq!(Some(GridRegion::new(1, 1, 19, 14)), set.get_bounds());
+        assert!(set.contains_point(2, 2));
+        assert!(!set.contains_point(5, 5));
+
+        set.clamp_to_grid(12, 8);
+        assert_eq!(
+            &vec![GridRegion::new(1, 1, 2, 2), GridRegion::new(10, 5, 2, 3)],
+            set.get_regions()
+        );
+
+        set.clamp_to_grid(1, 1);
+        assert!(set.is_empty());
+    }
+}
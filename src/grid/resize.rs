@@ -0,0 +1,91 @@
+use crate::*;
+
+/// Determines what should happen with the content of the grids of a *GridGroup*
+/// when it is resized (see the *resize* method of *GridGroup*).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResizeContent {
+    /// The content of the grids should not be preserved: the grids of the new
+    /// *GridGroup* will have undefined content. This is the cheapest option and
+    /// is fine when the grids will be redrawn anyway (which is often the case
+    /// after a window resize).
+    Discard,
+
+    /// The content of the color grids should be scaled to the new size, using
+    /// the nearest pixel of the old grids. The content of the depth stencil grids
+    /// will also be scaled this way.
+    ScaleNearest,
+
+    /// The content of the color grids should be scaled to the new size, using
+    /// linear interpolation between the pixels of the old grids. Since depth and
+    /// stencil values can't be interpolated sensibly, the content of the depth
+    /// stencil grids will be scaled like *ScaleNearest*. The same holds for the
+    /// color grids with a *Uint* or *Sint* *ColorFormat*, since integers can't
+    /// be interpolated linearly either.
+    ScaleLinear,
+}
+
+impl ResizeContent {
+    /// Gets the *SamplerFilter* that should be used to scale the content of a
+    /// color grid with the given *format*, or *None* if the content should be
+    /// discarded.
+    pub fn get_color_filter(&self, format: ColorFormat) -> Option<SamplerFilter> {
+        match self {
+            Self::Discard => None,
+            Self::ScaleNearest => Some(SamplerFilter::Nearest),
+            Self::ScaleLinear => match format.get_component_kind() {
+                ColorComponentKind::Uint | ColorComponentKind::Sint => Some(SamplerFilter::Nearest),
+                _ => Some(SamplerFilter::Linear),
+            },
+        }
+    }
+
+    /// Gets the *SamplerFilter* that should be used to scale the content of a
+    /// depth stencil grid, or *None* if the content should be discarded.
+    pub fn get_depth_stencil_filter(&self) -> Option<SamplerFilter> {
+        match self {
+            Self::Discard => None,
+            Self::ScaleNearest | Self::ScaleLinear => Some(SamplerFilter::Nearest),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_get_filters() {
+        let linear = ResizeContent::ScaleLinear;
+        assert_eq!(
+            Some(SamplerFilter::Linear),
+            linear.get_color_filter(ColorFormat::RGBA8Srgb)
+        );
+        assert_eq!(
+            Some(SamplerFilter::Nearest),
+            linear.get_color_filter(ColorFormat::R32Uint)
+        );
+        assert_eq!(
+            Some(SamplerFilter::Nearest),
+            linear.get_color_filter(ColorFormat::R32Sint)
+        );
+        assert_eq!(
+            Some(SamplerFilter::Nearest),
+            linear.get_depth_stencil_filter()
+        );
+
+        let nearest = ResizeContent::ScaleNearest;
+        assert_eq!(
+            Some(SamplerFilter::Nearest),
+            nearest.get_color_filter(ColorFormat::RGBA16Float)
+        );
+        assert_eq!(
+            Some(SamplerFilter::Nearest),
+            nearest.get_depth_stencil_filter()
+        );
+
+        let discard = ResizeContent::Discard;
+        assert_eq!(None, discard.get_color_filter(ColorFormat::RGBA8Unorm));
+        assert_eq!(None, discard.get_depth_stencil_filter());
+    }
+}
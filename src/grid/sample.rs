@@ -0,0 +1,39 @@
+/// The number of samples that every pixel of a grid has. Grids with more than 1
+/// sample per pixel are *multisampled*, which is used for multisample
+/// anti-aliasing (MSAA): the edges of triangles will look much smoother, at the
+/// cost of extra GPU memory and fill rate.
+///
+/// Multisampled grids can't be displayed or read by shaders directly. Instead,
+/// they should be *resolved* into a grid with *SampleCount::One* (see the
+/// *add_resolve* method of *RenderFlowBuilder*).
+///
+/// Every *GraphicsPipeline* also has a *SampleCount* (see the *sample_count* of
+/// *PipelineStateBuilder*), which must be equal to the *SampleCount* of the grids
+/// it draws on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SampleCount {
+    #[default]
+    One,
+    Two,
+    Four,
+    Eight,
+    Sixteen,
+}
+
+impl SampleCount {
+    /// Gets the number of samples per pixel as an integer.
+    pub fn get_num_samples(&self) -> u32 {
+        match self {
+            Self::One => 1,
+            Self::Two => 2,
+            Self::Four => 4,
+            Self::Eight => 8,
+            Self::Sixteen => 16,
+        }
+    }
+
+    /// Checks if this sample count is larger than 1.
+    pub fn is_multisampled(&self) -> bool {
+        *self != Self::One
+    }
+}
@@ -0,0 +1,67 @@
+use crate::*;
+
+/// The settings that determine how a shader samples a grid that is used as
+/// texture (see *DrawingNodeInputSource::SampledGrid*).
+///
+/// The *new* function (and the *Default* implementation) gives linear filtering,
+/// clamping to the edge, and no depth comparison.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SamplerState {
+    /// The filter that is used when the grid is displayed smaller than it is
+    /// (when multiple pixels of the grid cover a single fragment).
+    pub min_filter: SamplerFilter,
+
+    /// The filter that is used when the grid is displayed larger than it is
+    /// (when a single pixel of the grid covers multiple fragments).
+    pub mag_filter: SamplerFilter,
+
+    /// What happens when the shader samples outside the grid (at texture
+    /// coordinates smaller than 0 or larger than 1).
+    pub address_mode: SamplerAddressMode,
+
+    /// If this is not *None*, sampling a depth stencil grid will compare the
+    /// depth value given by the shader with the stored depth value, using this
+    /// operation, instead of returning the stored depth value. The result is 1
+    /// if the comparison passes and 0 otherwise. This is typically used for
+    /// shadow maps. This must be *None* for color grids.
+    pub compare_op: Option<CompareOp>,
+}
+
+impl SamplerState {
+    /// Constructs a new *SamplerState* with the default values. See the
+    /// documentation of this struct for the default values.
+    pub fn new() -> Self {
+        Self {
+            min_filter: SamplerFilter::Linear,
+            mag_filter: SamplerFilter::Linear,
+            address_mode: SamplerAddressMode::ClampToEdge,
+            compare_op: None,
+        }
+    }
+}
+
+impl Default for SamplerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The ways in which the pixels of a grid can be filtered when it is sampled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SamplerFilter {
+    /// Use the pixel that is closest to the texture coordinates.
+    Nearest,
+    /// Interpolate linearly between the pixels around the texture coordinates.
+    Linear,
+}
+
+/// Determines what happens when a grid is sampled outside its bounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SamplerAddressMode {
+    /// Use the pixel at the nearest edge of the grid.
+    ClampToEdge,
+    /// Repeat the grid (only the fractional part of the coordinates is used).
+    Repeat,
+    /// Repeat the grid, but mirror it every time it is repeated.
+    MirroredRepeat,
+}
@@ -0,0 +1,98 @@
+/// The size policy of an abstract grid: it determines the size of the concrete
+/// grids, based on the size of their concrete *GridGroup*.
+///
+/// Most grids simply have the same size as their *GridGroup* (*GridSize::FULL*),
+/// but some techniques need smaller grids alongside the full-size ones. For
+/// instance, bloom and SSAO are often done at half or quarter resolution, and
+/// shadow maps typically have a fixed size that doesn't depend on the window
+/// size at all.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GridSize {
+    /// The width and height of the grid are the width and height of its
+    /// *GridGroup*, multiplied by the given *scale* and rounded to the nearest
+    /// integer. The resulting width and height will always be at least 1.
+    Relative { scale: f32 },
+
+    /// The grid has the given width and height, regardless of the size of its
+    /// *GridGroup*. Resizing the *GridGroup* won't change the size of the grid.
+    Absolute { width: u32, height: u32 },
+}
+
+impl GridSize {
+    /// The grid has the same size as its *GridGroup*.
+    pub const FULL: GridSize = GridSize::Relative { scale: 1.0 };
+
+    /// The grid has half the width and height of its *GridGroup*.
+    pub const HALF: GridSize = GridSize::Relative { scale: 0.5 };
+
+    /// The grid has a quarter of the width and height of its *GridGroup*.
+    pub const QUARTER: GridSize = GridSize::Relative { scale: 0.25 };
+
+    /// Computes the (width, height) of a concrete grid with this size policy in a
+    /// *GridGroup* with the given width and height. Griphin implementations should
+    /// use this in their *create_concrete* and *resize* methods.
+    ///
+    /// This function panics if the *scale* of a *Relative* size is not positive,
+    /// or if the width or height of an *Absolute* size is 0.
+    pub fn resolve(&self, group_width: u32, group_height: u32) -> (u32, u32) {
+        match *self {
+            Self::Relative { scale } => {
+                if scale.is_nan() || scale <= 0.0 {
+                    panic!(
+                        "The scale of a relative grid size must be positive, but it is {}",
+                        scale
+                    );
+                }
+                let scale_dimension =
+                    |dimension: u32| ((dimension as f32 * scale).round() as u32).max(1);
+                (scale_dimension(group_width), scale_dimension(group_height))
+            }
+            Self::Absolute { width, height } => {
+                if width == 0 || height == 0 {
+                    panic!(
+                        "The width and height of an absolute grid size must be positive, but they are {} and {}",
+                        width, height
+                    );
+                }
+                (width, height)
+            }
+        }
+    }
+}
+
+impl Default for GridSize {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_relative() {
+        assert_eq!((800, 600), GridSize::FULL.resolve(800, 600));
+        assert_eq!((400, 300), GridSize::HALF.resolve(800, 600));
+        assert_eq!((200, 150), GridSize::QUARTER.resolve(800, 600));
+        assert_eq!((1, 1), GridSize::QUARTER.resolve(1, 1));
+        assert_eq!((2, 1), GridSize::HALF.resolve(3, 1));
+    }
+
+    #[test]
+    fn test_resolve_absolute() {
+        let size = GridSize::Absolute {
+            width: 1024,
+            height: 512,
+        };
+        assert_eq!((1024, 512), size.resolve(800, 600));
+        assert_eq!((1024, 512), size.resolve(1, 1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_scale() {
+        GridSize::Relative { scale: 0.0 }.resolve(10, 10);
+    }
+}
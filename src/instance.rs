@@ -35,11 +35,30 @@ pub trait Instance: Debug + Send + Sync + 'static {
     /// documentation of *AbstractGridGroup(Builder)* and *GridGroupIDs* for more
     /// information. The *builder* can be modified after this call returns, but
     /// changes made afterwards won't be reflected in the result of this method.
+    ///
+    /// Implementations should use the *allocate* method of their
+    /// *GridIDAllocator* (see *get_grid_id_allocator*) to create the
+    /// *GridGroupIDs*, which guarantees that every group gets unique ids. They
+    /// should also check the *builder* with its *validate_sample_counts*
+    /// method, and panic if it returns an error.
     fn create_abstract_grid_group(
         &self,
         builder: &AbstractGridGroupBuilder,
     ) -> (Arc<dyn AbstractGridGroup>, GridGroupIDs);
 
+    /// Gets the *GridIDAllocator* of this *Instance*, which allocates the ids of
+    /// the grids of its *AbstractGridGroup*s. Every *Instance* must have its own
+    /// allocator, which it gets when it is created with the *create_instance*
+    /// method of *GridIDAllocator*.
+    fn get_grid_id_allocator(&self) -> &GridIDAllocator;
+
+    /// Gets the maximum total size (in bytes) of the *PushConstant* variables of a
+    /// single *GraphicsPipeline*. The default implementation returns 128, which
+    /// is the minimum that every Vulkan implementation supports.
+    fn get_max_push_constant_size(&self) -> usize {
+        128
+    }
+
     /// This method should be used by the Griphin implementation to get access to
     /// the implementation-specific struct that implements this trait.
     fn as_any(&self) -> &dyn Any;
@@ -54,11 +73,15 @@ mod tests {
     #[derive(Debug)]
     struct DummyInstance {
         number: u8,
+        allocator: GridIDAllocator,
     }
 
     impl DummyInstance {
         fn new(number: u8) -> Arc<Self> {
-            Arc::new(Self { number })
+            Arc::new(GridIDAllocator::create_instance(|allocator| Self {
+                number,
+                allocator,
+            }))
         }
 
         fn downcast<R>(
@@ -85,6 +108,10 @@ mod tests {
             unimplemented!()
         }
 
+        fn get_grid_id_allocator(&self) -> &GridIDAllocator {
+            &self.allocator
+        }
+
         fn as_any(&self) -> &dyn Any {
             self
         }
@@ -105,6 +132,28 @@ mod tests {
         assert_eq!(expected, number);
     }
 
+    #[test]
+    fn test_grid_id_allocator() {
+        let first = create_dummy_instance(1);
+        let second = create_dummy_instance(2);
+        let first_allocator = first.get_grid_id_allocator();
+        assert_ne!(
+            first_allocator.get_instance_id(),
+            second.get_grid_id_allocator().get_instance_id()
+        );
+
+        let ids = first_allocator.allocate_ids(1, 0);
+        assert!(ids.check(ids.colors[0].get_abstract()).is_ok());
+        assert!(matches!(
+            ids.check(second.get_grid_id_allocator().allocate_ids(1, 0).colors[0].get_abstract()),
+            Err(GridIDError::OtherInstance { .. })
+        ));
+        assert!(matches!(
+            ids.check(first_allocator.allocate_ids(1, 0).colors[0].get_abstract()),
+            Err(GridIDError::OtherGroup { .. })
+        ));
+    }
+
     #[test]
     fn test_multithreading() {
         let original = create_dummy_instance(8);
@@ -1,22 +1,33 @@
 mod data;
 mod debug;
+mod definition;
 mod flow;
 mod gateway;
 mod grid;
 mod instance;
 mod pipeline;
+mod present;
 mod shader;
+mod storage;
+mod texture;
 mod util;
 mod vertex;
 
+#[cfg(test)]
+pub(crate) mod test_util;
+
 pub use data::*;
 pub use debug::*;
+pub use definition::*;
 pub use flow::*;
 pub use gateway::*;
 pub use grid::*;
 pub use instance::*;
 pub use pipeline::*;
+pub use present::*;
 pub use shader::*;
+pub use storage::*;
+pub use texture::*;
 pub use util::*;
 pub use vertex::*;
 
@@ -24,3 +35,46 @@ pub use vertex::*;
 #[allow(unused_imports)]
 #[macro_use]
 extern crate lazy_static;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use std::sync::Arc;
+
+    fn assert_send_sync<T: Send + Sync + ?Sized>() {}
+
+    // Every trait object that Griphin hands out can be shared between threads. This test
+    // won't compile if one of the traits loses its Send + Sync bound.
+    #[test]
+    fn test_send_sync() {
+        assert_send_sync::<Arc<dyn Instance>>();
+        assert_send_sync::<Arc<dyn ShaderManager>>();
+        assert_send_sync::<Arc<dyn VertexShader>>();
+        assert_send_sync::<Arc<dyn FragmentShader>>();
+        assert_send_sync::<Arc<dyn ComputeShader>>();
+        assert_send_sync::<Arc<dyn ShaderLibrary>>();
+        assert_send_sync::<Arc<dyn Gateway>>();
+        assert_send_sync::<Arc<dyn VertexBuffer>>();
+        assert_send_sync::<Arc<dyn IndexBuffer>>();
+        assert_send_sync::<Arc<dyn StorageBuffer>>();
+        assert_send_sync::<Arc<dyn Texture>>();
+        assert_send_sync::<Arc<dyn GraphicsPipeline>>();
+        assert_send_sync::<Arc<dyn AbstractGridGroup>>();
+        assert_send_sync::<Arc<dyn GridGroup>>();
+        assert_send_sync::<Arc<dyn ColorGrid>>();
+        assert_send_sync::<Arc<dyn DepthStencilGrid>>();
+        assert_send_sync::<Arc<dyn RenderFlow>>();
+        assert_send_sync::<Arc<dyn DrawingNode>>();
+        assert_send_sync::<Arc<dyn GridNode>>();
+        assert_send_sync::<Arc<dyn ComputeNode>>();
+        assert_send_sync::<Arc<dyn Surface>>();
+        assert_send_sync::<Arc<dyn OcclusionQuery>>();
+
+        assert_send_sync::<ShaderPair>();
+        assert_send_sync::<SurfaceFrame>();
+        assert_send_sync::<DrawCommandRecorder>();
+        assert_send_sync::<ParallelCommandRecording>();
+    }
+}
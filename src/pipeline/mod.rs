@@ -2,30 +2,57 @@ use crate::*;
 
 use std::any::Any;
 
+mod state;
 mod topology;
 
+pub use state::*;
 pub use topology::*;
 
-/// A combination of a *ShaderPair* and *PrimitiveTopology*, that is made to work
-/// on the concrete *GridGroup*s of a single previously chosen *AbstractGridGroup*.
+/// A combination of a *ShaderPair*, *PrimitiveTopology*, and *PipelineState*, that
+/// is made to work on the concrete *GridGroup*s of a single previously chosen
+/// *AbstractGridGroup*.
 ///
 /// The *ShaderPair* determines which vertex shader and which fragment shader will
 /// be used. The *PrimitiveTopology* determines what kind of structure the input
-/// vertices and indices are expected to have (triangles? lines?).
+/// vertices and indices are expected to have (triangles? lines?). The
+/// *PipelineState* determines the fixed-function state, like blending, depth
+/// testing, and face culling.
 ///
 /// The *AbstractGridGroup* determines on which concrete *GridGroup*s the pipeline
 /// can be used: it can only draw on the grids of these grid groups. Furthermore, it
 /// can only be used during *RenderFlow*s that are bound to the same
 /// *AbstractGridGroup*.
-pub trait GraphicsPipeline {
+pub trait GraphicsPipeline: Send + Sync {
     /// This method should be used by the Griphin implementation to get access to
     /// the implementation-specific struct that implements this trait.
     fn as_any(&self) -> &dyn Any;
 
+    /// Gets the debug name of this *GraphicsPipeline*. This is meant for error
+    /// messages and debugging tools (like the *to_dot* method of
+    /// *RenderFlowBuilder*). Implementations typically combine the debug names
+    /// of the vertex shader and fragment shader.
+    fn get_debug_name(&self) -> &StringRef;
+
     /// Gets a reference to a *Vec* containing all *ExternalShaderVariable*s of
     /// the *ShaderPair* of this *GraphicsPipeline*.
     ///
     /// See the *get_external_variables* method of *ShaderPair* for more
     /// information.
     fn get_external_variables(&self) -> &Vec<ExternalShaderVariable>;
+
+    /// Gets a reference to a *Vec* containing the *ColorOutput* and
+    /// *DepthStencilOutput* variables of the fragment shader of this
+    /// *GraphicsPipeline*.
+    ///
+    /// See the *get_output_variables* method of *ShaderPair* for more
+    /// information.
+    fn get_output_variables(&self) -> &Vec<FragmentShaderVariable>;
+
+    /// Gets the *PipelineState* of this *GraphicsPipeline*. This is the resolved
+    /// version of the *PipelineStateBuilder* that was used to create this
+    /// pipeline (see the documentation of *PipelineState*).
+    fn get_state(&self) -> &PipelineState;
+
+    /// Gets the *PrimitiveTopology* that was used to create this *GraphicsPipeline*.
+    fn get_topology(&self) -> PrimitiveTopology;
 }
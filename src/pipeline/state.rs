@@ -0,0 +1,421 @@
+use crate::*;
+
+/// This struct contains the fixed-function state that a *GraphicsPipeline*
+/// should use: everything that determines how the outputs of the shaders are
+/// combined with the grids, besides the shaders themselves.
+///
+/// The *new* function (and the *Default* implementation) gives sensible
+/// defaults for typical 3D rendering: depth testing with *Less* and depth
+/// writes enabled, no blending, no stencil test, back faces culled, counter
+/// clockwise front faces, filled polygons, a line width of 1, and 1 sample per
+/// pixel. Change the fields you need and pass the builder to the
+/// *create_graphics_pipeline* method of an *AbstractGridGroup*.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PipelineStateBuilder {
+    /// How the colors computed by the fragment shader should be combined with
+    /// the colors that are already in the color grids. If this is *None*, the
+    /// new colors will simply replace the old colors.
+    pub blend: Option<BlendState>,
+
+    /// The operation that is used to compare the depth of each fragment with
+    /// the depth stored in the depth stencil grid. Fragments for which the
+    /// comparison fails are discarded. If this is *None*, there will be no
+    /// depth test at all.
+    pub depth_compare_op: Option<CompareOp>,
+
+    /// Whether the depth of the fragments that pass the depth test should be
+    /// written to the depth stencil grid. This is ignored when there is no
+    /// depth test.
+    pub depth_write: bool,
+
+    /// The stencil test that should be done, or *None* if there shouldn't be a
+    /// stencil test.
+    pub stencil: Option<StencilState>,
+
+    /// Which faces of triangles should be culled (not drawn).
+    pub cull_mode: CullMode,
+
+    /// Which triangles are considered to be front-facing.
+    pub front_face: FrontFace,
+
+    /// Whether triangles should be filled, or only their edges or corners
+    /// should be drawn.
+    pub polygon_mode: PolygonMode,
+
+    /// The width of lines, in pixels. This is only used when the topology is
+    /// *Lines* or *LineStrips*, or when the *polygon_mode* is *Line*.
+    pub line_width: f32,
+
+    /// The number of samples per pixel of the grids the pipeline will draw on.
+    /// This must be equal to the *SampleCount* of these grids.
+    pub sample_count: SampleCount,
+}
+
+impl PipelineStateBuilder {
+    /// Constructs a new *PipelineStateBuilder* with the default values. See the
+    /// documentation of this struct for the default values.
+    pub fn new() -> Self {
+        Self {
+            blend: None,
+            depth_compare_op: Some(CompareOp::Less),
+            depth_write: true,
+            stencil: None,
+            cull_mode: CullMode::Back,
+            front_face: FrontFace::CounterClockwise,
+            polygon_mode: PolygonMode::Fill,
+            line_width: 1.0,
+            sample_count: SampleCount::One,
+        }
+    }
+}
+
+impl Default for PipelineStateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The fixed-function state of a *GraphicsPipeline*, after resolving a
+/// *PipelineStateBuilder*. Settings that have no effect are normalized, so two
+/// pipelines that behave the same will also have the same *PipelineState*:
+///
+/// - The *depth_write* is *false* when there is no depth test.
+/// - The *line_width* is 1 when no lines are drawn at all.
+/// - The *cull_mode* is *None*, the *front_face* is *CounterClockwise*, and the
+///   *polygon_mode* is *Fill* for topologies without triangles.
+///
+/// Use the *get_state* method of *GraphicsPipeline* to get the state of a
+/// pipeline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PipelineState {
+    blend: Option<BlendState>,
+    depth_compare_op: Option<CompareOp>,
+    depth_write: bool,
+    stencil: Option<StencilState>,
+    cull_mode: CullMode,
+    front_face: FrontFace,
+    polygon_mode: PolygonMode,
+    line_width: f32,
+    sample_count: SampleCount,
+}
+
+impl PipelineState {
+    /// Resolves the given *builder* for a pipeline with the given *topology*.
+    /// This function is meant for Griphin implementations, which should use it
+    /// in their *create_graphics_pipeline* method.
+    ///
+    /// This function panics if the *line_width* of the builder is not positive.
+    pub fn resolve(builder: &PipelineStateBuilder, topology: PrimitiveTopology) -> Self {
+        if builder.line_width.is_nan() || builder.line_width <= 0.0 {
+            panic!(
+                "The line width must be positive, but it is {}",
+                builder.line_width
+            );
+        }
+
+        let has_triangles = topology == PrimitiveTopology::Triangles
+            || topology == PrimitiveTopology::TriangleStrips;
+        let has_lines = topology == PrimitiveTopology::Lines
+            || topology == PrimitiveTopology::LineStrips
+            || (has_triangles && builder.polygon_mode == PolygonMode::Line);
+
+        Self {
+            blend: builder.blend,
+            depth_compare_op: builder.depth_compare_op,
+            depth_write: builder.depth_write && builder.depth_compare_op.is_some(),
+            stencil: builder.stencil,
+            cull_mode: if has_triangles {
+                builder.cull_mode
+            } else {
+                CullMode::None
+            },
+            front_face: if has_triangles {
+                builder.front_face
+            } else {
+                FrontFace::CounterClockwise
+            },
+            polygon_mode: if has_triangles {
+                builder.polygon_mode
+            } else {
+                PolygonMode::Fill
+            },
+            line_width: if has_lines { builder.line_width } else { 1.0 },
+            sample_count: builder.sample_count,
+        }
+    }
+
+    /// Gets the *BlendState*, or *None* if blending is disabled.
+    pub fn get_blend(&self) -> Option<BlendState> {
+        self.blend
+    }
+
+    /// Gets the depth compare operation, or *None* if depth testing is disabled.
+    pub fn get_depth_compare_op(&self) -> Option<CompareOp> {
+        self.depth_compare_op
+    }
+
+    /// Checks whether depth values will be written to the depth stencil grid.
+    pub fn get_depth_write(&self) -> bool {
+        self.depth_write
+    }
+
+    /// Gets the *StencilState*, or *None* if stencil testing is disabled.
+    pub fn get_stencil(&self) -> Option<StencilState> {
+        self.stencil
+    }
+
+    /// Gets the *CullMode*.
+    pub fn get_cull_mode(&self) -> CullMode {
+        self.cull_mode
+    }
+
+    /// Gets the *FrontFace*.
+    pub fn get_front_face(&self) -> FrontFace {
+        self.front_face
+    }
+
+    /// Gets the *PolygonMode*.
+    pub fn get_polygon_mode(&self) -> PolygonMode {
+        self.polygon_mode
+    }
+
+    /// Gets the line width, in pixels.
+    pub fn get_line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    /// Gets the *SampleCount*.
+    pub fn get_sample_count(&self) -> SampleCount {
+        self.sample_count
+    }
+}
+
+/// The operations that can be used to compare a new (depth or stencil) value
+/// with the value that is currently stored in a grid. The comparison passes
+/// if *new value <operation> stored value* holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompareOp {
+    Never,
+    Less,
+    Equal,
+    LessOrEqual,
+    Greater,
+    NotEqual,
+    GreaterOrEqual,
+    Always,
+}
+
+/// Determines which faces of triangles will be culled (not drawn). Whether a
+/// triangle faces the front or the back is determined by the *FrontFace*.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CullMode {
+    /// Don't cull any triangles.
+    None,
+    /// Cull the triangles that face the front.
+    Front,
+    /// Cull the triangles that face the back.
+    Back,
+    /// Cull all triangles (only points and lines will be drawn).
+    FrontAndBack,
+}
+
+/// Determines which triangles are considered to be front-facing, based on the
+/// order of their vertices on the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrontFace {
+    /// Triangles whose vertices appear in counter clockwise order are
+    /// front-facing.
+    CounterClockwise,
+    /// Triangles whose vertices appear in clockwise order are front-facing.
+    Clockwise,
+}
+
+/// Determines how triangles are rasterized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolygonMode {
+    /// The entire triangle is drawn.
+    Fill,
+    /// Only the edges of the triangle are drawn (as lines).
+    Line,
+    /// Only the corners of the triangle are drawn (as points).
+    Point,
+}
+
+/// The factors by which the new color (the *source*) and the stored color (the
+/// *destination*) can be multiplied before they are combined by a *BlendOp*.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendFactor {
+    Zero,
+    One,
+    SourceColor,
+    OneMinusSourceColor,
+    DestinationColor,
+    OneMinusDestinationColor,
+    SourceAlpha,
+    OneMinusSourceAlpha,
+    DestinationAlpha,
+    OneMinusDestinationAlpha,
+}
+
+/// The operation that combines the (multiplied) source and destination colors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendOp {
+    /// source + destination
+    Add,
+    /// source - destination
+    Subtract,
+    /// destination - source
+    ReverseSubtract,
+    /// The minimum of source and destination (the factors are ignored)
+    Min,
+    /// The maximum of source and destination (the factors are ignored)
+    Max,
+}
+
+/// Describes how the colors computed by the fragment shader (the source) are
+/// combined with the colors in the color grid (the destination). The red, green,
+/// and blue components use the *color* factors and operation, and the alpha
+/// component uses the *alpha* factors and operation.
+///
+/// The most common blend states are available as associated constants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlendState {
+    pub source_color_factor: BlendFactor,
+    pub destination_color_factor: BlendFactor,
+    pub color_op: BlendOp,
+    pub source_alpha_factor: BlendFactor,
+    pub destination_alpha_factor: BlendFactor,
+    pub alpha_op: BlendOp,
+}
+
+impl BlendState {
+    /// Standard alpha blending: the source is drawn on top of the destination,
+    /// weighted by its alpha value.
+    pub const ALPHA: BlendState = BlendState {
+        source_color_factor: BlendFactor::SourceAlpha,
+        destination_color_factor: BlendFactor::OneMinusSourceAlpha,
+        color_op: BlendOp::Add,
+        source_alpha_factor: BlendFactor::One,
+        destination_alpha_factor: BlendFactor::OneMinusSourceAlpha,
+        alpha_op: BlendOp::Add,
+    };
+
+    /// Alpha blending for colors whose red, green, and blue components are
+    /// already multiplied by their alpha value.
+    pub const PREMULTIPLIED_ALPHA: BlendState = BlendState {
+        source_color_factor: BlendFactor::One,
+        destination_color_factor: BlendFactor::OneMinusSourceAlpha,
+        color_op: BlendOp::Add,
+        source_alpha_factor: BlendFactor::One,
+        destination_alpha_factor: BlendFactor::OneMinusSourceAlpha,
+        alpha_op: BlendOp::Add,
+    };
+
+    /// Additive blending: the source is added to the destination.
+    pub const ADDITIVE: BlendState = BlendState {
+        source_color_factor: BlendFactor::One,
+        destination_color_factor: BlendFactor::One,
+        color_op: BlendOp::Add,
+        source_alpha_factor: BlendFactor::One,
+        destination_alpha_factor: BlendFactor::One,
+        alpha_op: BlendOp::Add,
+    };
+}
+
+/// The operations that can be done on a stored stencil value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StencilOp {
+    Keep,
+    Zero,
+    Replace,
+    IncrementAndClamp,
+    DecrementAndClamp,
+    Invert,
+    IncrementAndWrap,
+    DecrementAndWrap,
+}
+
+/// Describes the stencil test and stencil operations for either the front-facing
+/// or the back-facing primitives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StencilFaceState {
+    /// The operation to perform when the stencil test fails.
+    pub fail_op: StencilOp,
+    /// The operation to perform when both the stencil test and depth test pass.
+    pub pass_op: StencilOp,
+    /// The operation to perform when the stencil test passes, but the depth
+    /// test fails.
+    pub depth_fail_op: StencilOp,
+    /// The operation that compares the *reference* with the stored value.
+    pub compare_op: CompareOp,
+    /// The bits of the stencil values that take part in the comparison.
+    pub compare_mask: u8,
+    /// The bits of the stencil values that can be changed by the operations.
+    pub write_mask: u8,
+    /// The reference value used by the comparison and by *StencilOp::Replace*.
+    pub reference: u8,
+}
+
+/// Describes the stencil test for front-facing and back-facing primitives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StencilState {
+    pub front: StencilFaceState,
+    pub back: StencilFaceState,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_defaults() {
+        let state =
+            PipelineState::resolve(&PipelineStateBuilder::new(), PrimitiveTopology::Triangles);
+        assert_eq!(None, state.get_blend());
+        assert_eq!(Some(CompareOp::Less), state.get_depth_compare_op());
+        assert!(state.get_depth_write());
+        assert_eq!(CullMode::Back, state.get_cull_mode());
+        assert_eq!(1.0, state.get_line_width());
+    }
+
+    #[test]
+    fn test_resolve_normalization() {
+        let mut builder = PipelineStateBuilder::new();
+        builder.depth_compare_op = None;
+        builder.line_width = 3.0;
+
+        let triangles = PipelineState::resolve(&builder, PrimitiveTopology::Triangles);
+        assert!(!triangles.get_depth_write());
+        assert_eq!(1.0, triangles.get_line_width());
+
+        let lines = PipelineState::resolve(&builder, PrimitiveTopology::Lines);
+        assert_eq!(3.0, lines.get_line_width());
+        assert_eq!(CullMode::None, lines.get_cull_mode());
+
+        // The front face and polygon mode only matter for triangles
+        let mut point_builder = builder;
+        point_builder.front_face = FrontFace::Clockwise;
+        point_builder.polygon_mode = PolygonMode::Point;
+        let points = PipelineState::resolve(&point_builder, PrimitiveTopology::Points);
+        assert_eq!(FrontFace::CounterClockwise, points.get_front_face());
+        assert_eq!(PolygonMode::Fill, points.get_polygon_mode());
+        assert_eq!(
+            PipelineState::resolve(&builder, PrimitiveTopology::Points),
+            points
+        );
+
+        builder.polygon_mode = PolygonMode::Line;
+        let wireframe = PipelineState::resolve(&builder, PrimitiveTopology::TriangleStrips);
+        assert_eq!(3.0, wireframe.get_line_width());
+        assert_eq!(CullMode::Back, wireframe.get_cull_mode());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_resolve_bad_line_width() {
+        let mut builder = PipelineStateBuilder::new();
+        builder.line_width = 0.0;
+        PipelineState::resolve(&builder, PrimitiveTopology::Lines);
+    }
+}
@@ -15,7 +15,7 @@
 /// to find out what this means. If the topology is *Lines*, this means that
 /// it should draw a line between the first vertex and fourth vertex, and a
 /// line between the third vertex and sixth vertex.
-#[derive(Eq, PartialEq, Copy, Clone)]
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum PrimitiveTopology {
     /// A point should be drawn at the location of each vertex
     Points,
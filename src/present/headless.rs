@@ -0,0 +1,377 @@
+use crate::*;
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Determines what a *HeadlessSurface* does with the frames that are presented
+/// to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FrameSink {
+    /// The presented frames are stored as *ColorGridDump*s in an in-memory queue.
+    /// Use the *pop_frame* method of the *HeadlessSurface* to take them out.
+    Queue,
+
+    /// The presented frames are written to numbered image files in the given
+    /// directory: *frame_000000.pam*, *frame_000001.pam*, ... The files use the
+    /// (uncompressed) PAM format with 8-bit RGBA pixels, which can be opened by
+    /// most image viewers and converted by tools like ImageMagick.
+    Files { directory: PathBuf },
+}
+
+/// A *Surface* that doesn't need a window: the frames that are presented to it
+/// will be sent to a *FrameSink*. This is useful to test the presentation logic
+/// of applications, and to render frames on machines without a display.
+///
+/// The display grids of this surface are the color grids with the given id in
+/// the given *GridGroup*s. These should typically be created via the
+/// *create_concrete* method of the same *AbstractGridGroup*. Presenting a frame
+/// will make a *debug_dump* of its display grid, so this is *not* fast.
+///
+/// When the *PresentMode* is *Fifo*, every presented frame will be added to the
+/// queue of the *FrameSink::Queue*. For the other present modes, a newly presented
+/// frame replaces the frame that is still in the queue (if any). Frames are
+/// always written when the sink is *FrameSink::Files*.
+pub struct HeadlessSurface {
+    present_mode: PresentMode,
+    grid_groups: Vec<Arc<dyn GridGroup>>,
+    grid: ColorGridID,
+    sink: FrameSink,
+
+    width: u32,
+    height: u32,
+
+    state: Mutex<HeadlessState>,
+}
+
+struct HeadlessState {
+    acquired: Vec<bool>,
+    next_index: usize,
+    queue: VecDeque<ColorGridDump>,
+    num_presented_frames: u64,
+}
+
+impl HeadlessSurface {
+    /// Constructs a new *HeadlessSurface* whose display grids are the color grids
+    /// with id *grid* of the given *grid_groups*. This function panics if
+    /// *grid_groups* is empty, or if the display grids don't all have the same
+    /// size.
+    pub fn new(
+        present_mode: PresentMode,
+        grid_groups: Vec<Arc<dyn GridGroup>>,
+        grid: ColorGridID,
+        sink: FrameSink,
+    ) -> Self {
+        if grid_groups.is_empty() {
+            panic!("A HeadlessSurface needs at least 1 grid group");
+        }
+        let (width, height) = grid_groups[0].get_grid_dimensions(grid.into());
+        for grid_group in &grid_groups {
+            if grid_group.get_grid_dimensions(grid.into()) != (width, height) {
+                panic!("All display grids of a HeadlessSurface must have the same size");
+            }
+        }
+
+        let num_grids = grid_groups.len();
+        Self {
+            present_mode,
+            grid_groups,
+            grid,
+            sink,
+            width,
+            height,
+            state: Mutex::new(HeadlessState {
+                acquired: vec![false; num_grids],
+                next_index: 0,
+                queue: VecDeque::new(),
+                num_presented_frames: 0,
+            }),
+        }
+    }
+
+    /// Gets the *FrameSink* of this surface.
+    pub fn get_sink(&self) -> &FrameSink {
+        &self.sink
+    }
+
+    /// Takes the oldest frame out of the queue, or returns *None* if the queue is
+    /// empty (which is always the case when the sink is not *FrameSink::Queue*).
+    pub fn pop_frame(&self) -> Option<ColorGridDump> {
+        self.state.lock().unwrap().queue.pop_front()
+    }
+
+    /// Gets the number of frames that are currently in the queue.
+    pub fn get_num_queued_frames(&self) -> usize {
+        self.state.lock().unwrap().queue.len()
+    }
+
+    /// Gets the total number of frames that have been presented to this surface.
+    pub fn get_num_presented_frames(&self) -> u64 {
+        self.state.lock().unwrap().num_presented_frames
+    }
+
+    fn write_frame(directory: &Path, index: u64, dump: &ColorGridDump) -> std::io::Result<()> {
+        let path = directory.join(format!("frame_{:06}.pam", index));
+        let mut writer = BufWriter::new(File::create(path)?);
+        write!(
+            writer,
+            "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+            dump.get_width(),
+            dump.get_height()
+        )?;
+        for y in 0..dump.get_height() {
+            for x in 0..dump.get_width() {
+                let color = dump.get(x, y);
+                writer.write_all(&[color.red, color.green, color.blue, color.alpha])?;
+            }
+        }
+        writer.flush()
+    }
+}
+
+impl Surface for HeadlessSurface {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn get_present_mode(&self) -> PresentMode {
+        self.present_mode
+    }
+
+    fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    fn acquire(&self) -> Result<SurfaceFrame, PresentError> {
+        let mut state = self.state.lock().unwrap();
+        let num_grids = state.acquired.len();
+        for offset in 0..num_grids {
+            let index = (state.next_index + offset) % num_grids;
+            if !state.acquired[index] {
+                state.acquired[index] = true;
+                state.next_index = (index + 1) % num_grids;
+                return Ok(SurfaceFrame::new(
+                    Arc::clone(&self.grid_groups[index]),
+                    self.grid,
+                    index as u32,
+                ));
+            }
+        }
+        Err(PresentError::NoGridAvailable)
+    }
+
+    fn present(&self, frame: SurfaceFrame) -> Result<(), PresentError> {
+        let index = frame.get_image_index() as usize;
+        let mut state = self.state.lock().unwrap();
+        if frame.get_grid() != self.grid
+            || index >= state.acquired.len()
+            || !state.acquired[index]
+            || !Arc::ptr_eq(frame.get_grid_group(), &self.grid_groups[index])
+        {
+            return Err(PresentError::NotAcquired {
+                image_index: frame.get_image_index(),
+            });
+        }
+        state.acquired[index] = false;
+
+        let region = GridRegion::new(0, 0, self.width, self.height);
+        let dump = frame
+            .get_grid_group()
+            .get_color_grid(self.grid)
+            .debug_dump(region);
+        let frame_number = state.num_presented_frames;
+        state.num_presented_frames += 1;
+
+        match &self.sink {
+            FrameSink::Queue => {
+                if self.present_mode != PresentMode::Fifo {
+                    state.queue.clear();
+                }
+                state.queue.push_back(dump);
+                Ok(())
+            }
+            FrameSink::Files { directory } => {
+                Self::write_frame(directory, frame_number, &dump).map_err(PresentError::Io)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use std::fs;
+
+    // Every dump of this grid has a single color whose red component is *value*
+    struct DummyColorGrid {
+        value: u8,
+    }
+
+    impl ColorGrid for DummyColorGrid {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn debug_dump(&self, region: GridRegion) -> ColorGridDump {
+            let color = Color {
+                red: self.value,
+                green: 0,
+                blue: 0,
+                alpha: 255,
+            };
+            let num_pixels = (region.get_width() * region.get_height()) as usize;
+            ColorGridDump::new(
+                vec![color; num_pixels],
+                region.get_width(),
+                region.get_height(),
+            )
+        }
+    }
+
+    struct DummyGridGroup {
+        value: u8,
+    }
+
+    impl GridGroup for DummyGridGroup {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn get_color_grid(&self, _id: ColorGridID) -> Arc<dyn ColorGrid> {
+            Arc::new(DummyColorGrid { value: self.value })
+        }
+
+        fn get_depth_stencil_grid(&self, _id: DepthStencilGridID) -> Arc<dyn DepthStencilGrid> {
+            unimplemented!()
+        }
+
+        fn get_width(&self) -> u32 {
+            3
+        }
+
+        fn get_height(&self) -> u32 {
+            2
+        }
+
+        fn get_grid_dimensions(&self, _id: AbstractGridID) -> (u32, u32) {
+            (3, 2)
+        }
+
+        fn resize(&self, _width: u32, _height: u32, _content: ResizeContent) -> Arc<dyn GridGroup> {
+            unimplemented!()
+        }
+    }
+
+    fn create_surface(present_mode: PresentMode, sink: FrameSink) -> HeadlessSurface {
+        HeadlessSurface::new(
+            present_mode,
+            vec![
+                Arc::new(DummyGridGroup { value: 10 }),
+                Arc::new(DummyGridGroup { value: 20 }),
+            ],
+            GridIDAllocator::new().allocate_ids(1, 0).colors[0],
+            sink,
+        )
+    }
+
+    #[test]
+    fn test_acquire() {
+        let surface = create_surface(PresentMode::Fifo, FrameSink::Queue);
+        assert_eq!(3, surface.get_width());
+        assert_eq!(2, surface.get_height());
+
+        let frame1 = surface.acquire().unwrap();
+        let frame2 = surface.acquire().unwrap();
+        assert_eq!(0, frame1.get_image_index());
+        assert_eq!(1, frame2.get_image_index());
+        assert!(matches!(
+            surface.acquire(),
+            Err(PresentError::NoGridAvailable)
+        ));
+
+        surface.present(frame2).unwrap();
+        assert_eq!(1, surface.acquire().unwrap().get_image_index());
+    }
+
+    #[test]
+    fn test_fifo_queue() {
+        let surface = create_surface(PresentMode::Fifo, FrameSink::Queue);
+        for _counter in 0..3 {
+            let frame = surface.acquire().unwrap();
+            surface.present(frame).unwrap();
+        }
+        assert_eq!(3, surface.get_num_presented_frames());
+        assert_eq!(3, surface.get_num_queued_frames());
+        assert_eq!(10, surface.pop_frame().unwrap().get(2, 1).red);
+        assert_eq!(20, surface.pop_frame().unwrap().get(0, 0).red);
+        assert_eq!(10, surface.pop_frame().unwrap().get(1, 0).red);
+        assert!(surface.pop_frame().is_none());
+    }
+
+    #[test]
+    fn test_mailbox_queue() {
+        let surface = create_surface(PresentMode::Mailbox, FrameSink::Queue);
+        for _counter in 0..3 {
+            let frame = surface.acquire().unwrap();
+            surface.present(frame).unwrap();
+        }
+        assert_eq!(3, surface.get_num_presented_frames());
+        assert_eq!(1, surface.get_num_queued_frames());
+        assert_eq!(10, surface.pop_frame().unwrap().get(0, 0).red);
+    }
+
+    #[test]
+    fn test_present_twice() {
+        let surface = create_surface(PresentMode::Fifo, FrameSink::Queue);
+        let frame = surface.acquire().unwrap();
+        let copy = SurfaceFrame::new(
+            Arc::clone(frame.get_grid_group()),
+            frame.get_grid(),
+            frame.get_image_index(),
+        );
+        surface.present(frame).unwrap();
+        assert!(matches!(
+            surface.present(copy),
+            Err(PresentError::NotAcquired { image_index: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_files() {
+        let directory = std::env::temp_dir().join(format!(
+            "griphin_test_headless_surface_{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&directory).unwrap();
+        let surface = create_surface(
+            PresentMode::Fifo,
+            FrameSink::Files {
+                directory: directory.clone(),
+            },
+        );
+        for _counter in 0..2 {
+            let frame = surface.acquire().unwrap();
+            surface.present(frame).unwrap();
+        }
+        assert_eq!(0, surface.get_num_queued_frames());
+
+        let content = fs::read(directory.join("frame_000001.pam")).unwrap();
+        let header =
+            "P7\nWIDTH 3\nHEIGHT 2\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n".as_bytes();
+        assert_eq!(header, &content[0..header.len()]);
+        assert_eq!(header.len() + 24, content.len());
+        assert_eq!(&[20, 0, 0, 255], &content[header.len()..header.len() + 4]);
+        assert!(directory.join("frame_000000.pam").exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+use crate::*;
+
+use std::any::Any;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+mod headless;
+
+pub use headless::*;
+
+/// Determines how presented frames are synchronized with the display. See the
+/// documentation of the possibilities for more information.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PresentMode {
+    /// Presented frames are shown immediately, without waiting for the vertical
+    /// blank of the display. This gives the lowest latency, but can cause
+    /// tearing.
+    Immediate,
+
+    /// Presented frames are put in a queue, and the display takes one frame from
+    /// the queue during every vertical blank. This is also known as *vsync*: it
+    /// never causes tearing, but *acquire* will block when the queue is full.
+    Fifo,
+
+    /// Like *Fifo*, but the queue has only room for a single frame: a newly
+    /// presented frame replaces the frame that is waiting for the vertical
+    /// blank. This never causes tearing and *acquire* won't block, but frames
+    /// can be skipped.
+    Mailbox,
+}
+
+/// A *Surface* is a destination for the colors in *ColorPurpose::Display* grids,
+/// typically the content area of a window. It owns a small number of display
+/// grids (the grids of the swapchain, if you are familiar with Vulkan). Every
+/// frame, the application should:
+///
+/// (1) Use the *acquire* method to get a *SurfaceFrame*, which tells which
+/// display grid (of which *GridGroup*) should be drawn on.
+///
+/// (2) Draw on that grid (or resolve a multisampled grid into it), using the
+/// *RenderFlow*s of the *AbstractGridGroup* of the frame.
+///
+/// (3) Give the frame back to the *present* method, which will show the content
+/// of the display grid on the surface.
+///
+/// The way surfaces are created depends on the Griphin implementation, since it
+/// requires a window handle. The *HeadlessSurface* can be used to test the
+/// presentation logic of an application without a window.
+pub trait Surface: Send + Sync {
+    /// This method should be used by the Griphin implementation to get access to
+    /// the implementation-specific struct that implements this trait.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Gets the *PresentMode* of this *Surface*.
+    fn get_present_mode(&self) -> PresentMode;
+
+    /// Gets the width of the display grids of this *Surface*.
+    fn get_width(&self) -> u32;
+
+    /// Gets the height of the display grids of this *Surface*.
+    fn get_height(&self) -> u32;
+
+    /// Acquires the next display grid that the application should draw on. See
+    /// the documentation of this trait for more information.
+    fn acquire(&self) -> Result<SurfaceFrame, PresentError>;
+
+    /// Presents the given *frame*, which must have been acquired from this
+    /// *Surface* (and must not have been presented yet). See the documentation of
+    /// this trait for more information.
+    fn present(&self, frame: SurfaceFrame) -> Result<(), PresentError>;
+}
+
+/// Represents a display grid that was acquired from a *Surface*, but has not yet
+/// been presented. Use the *get_grid_group* and *get_grid* methods to find out
+/// on which grid you should draw.
+pub struct SurfaceFrame {
+    grid_group: Arc<dyn GridGroup>,
+    grid: ColorGridID,
+    image_index: u32,
+}
+
+impl SurfaceFrame {
+    /// Constructs a new *SurfaceFrame*. This should only be used by *Surface*
+    /// implementations.
+    pub fn new(grid_group: Arc<dyn GridGroup>, grid: ColorGridID, image_index: u32) -> Self {
+        Self {
+            grid_group,
+            grid,
+            image_index,
+        }
+    }
+
+    /// Gets the *GridGroup* that owns the display grid of this frame.
+    pub fn get_grid_group(&self) -> &Arc<dyn GridGroup> {
+        &self.grid_group
+    }
+
+    /// Gets the *ColorGridID* of the display grid of this frame.
+    pub fn get_grid(&self) -> ColorGridID {
+        self.grid
+    }
+
+    /// Gets the index of the display grid within its *Surface*. This is useful
+    /// for applications that keep some state for every display grid.
+    pub fn get_image_index(&self) -> u32 {
+        self.image_index
+    }
+}
+
+/// The errors that can occur while acquiring or presenting a *SurfaceFrame*.
+#[derive(Debug)]
+pub enum PresentError {
+    /// The *Surface* no longer matches the window (for instance because the
+    /// window was resized), so it needs to be recreated.
+    OutOfDate,
+
+    /// All display grids have been acquired, but none of them has been presented
+    /// yet.
+    NoGridAvailable,
+
+    /// The frame that was given to *present* was not acquired from that
+    /// *Surface*.
+    NotAcquired { image_index: u32 },
+
+    /// An IO error occurred (for instance while writing a frame to a file).
+    Io(std::io::Error),
+}
+
+impl Display for PresentError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::OutOfDate => write!(f, "The surface is out of date"),
+            Self::NoGridAvailable => write!(f, "All display grids have already been acquired"),
+            Self::NotAcquired { image_index } => write!(
+                f,
+                "The display grid with index {} was not acquired from this surface",
+                image_index
+            ),
+            Self::Io(error) => write!(f, "Failed to present frame: {}", error),
+        }
+    }
+}
+
+impl Error for PresentError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use crate::*;
 
 use std::any::Any;
-use std::fmt::Debug;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
 use std::sync::Arc;
 
 /// Every Griphin *Instance* has a *ShaderManager* that can be accessed using
@@ -124,6 +125,70 @@ pub trait ShaderManager: Debug + Send + Sync + 'static {
         libraries: Vec<Arc<dyn ShaderLibrary>>,
     ) -> Arc<dyn FragmentShader>;
 
+    /// Creates a new *ComputeShader* with the given functions, variables, and
+    /// libraries.
+    ///
+    /// The *debug_name*, *main_function_body*, *other_functions*, and *libraries*
+    /// have the same meaning as the parameters of *create_vertex_shader*. Since
+    /// all variables of a compute shader are external, the *variables* are
+    /// *ExternalShaderVariable*s.
+    ///
+    /// The *workgroup_size* determines how many invocations a single workgroup
+    /// of the compute shader will have. The number of workgroups is not chosen
+    /// here, but by the compute nodes that use this shader.
+    fn create_compute_shader(
+        &self,
+        debug_name: &StringRef,
+        main_function_body: &StringRef,
+        other_functions: &StringRef,
+        variables: Vec<ExternalShaderVariable>,
+        workgroup_size: WorkgroupSize,
+        libraries: Vec<Arc<dyn ShaderLibrary>>,
+    ) -> Arc<dyn ComputeShader>;
+
+    /// Does the same as *create_vertex_shader*, but returns a *ShaderCompileError*
+    /// instead of panicking (or reporting it in some other way) when the shader
+    /// can't be compiled.
+    ///
+    /// This is useful when the shader code is not known when the application is
+    /// compiled (for instance when it is loaded from a file). Griphin
+    /// implementations that can't detect compile errors right away can rely on
+    /// the default implementation, which simply calls *create_vertex_shader*.
+    fn try_create_vertex_shader(
+        &self,
+        debug_name: &StringRef,
+        main_function_body: &StringRef,
+        other_functions: &StringRef,
+        variables: Vec<VertexShaderVariable>,
+        libraries: Vec<Arc<dyn ShaderLibrary>>,
+    ) -> Result<Arc<dyn VertexShader>, ShaderCompileError> {
+        Ok(self.create_vertex_shader(
+            debug_name,
+            main_function_body,
+            other_functions,
+            variables,
+            libraries,
+        ))
+    }
+
+    /// The fragment shader counterpart of *try_create_vertex_shader*.
+    fn try_create_fragment_shader(
+        &self,
+        debug_name: &StringRef,
+        main_function: &StringRef,
+        other_functions: &StringRef,
+        variables: Vec<FragmentShaderVariable>,
+        libraries: Vec<Arc<dyn ShaderLibrary>>,
+    ) -> Result<Arc<dyn FragmentShader>, ShaderCompileError> {
+        Ok(self.create_fragment_shader(
+            debug_name,
+            main_function,
+            other_functions,
+            variables,
+            libraries,
+        ))
+    }
+
     /// Creates a new *ShaderLibrary* with the given debug name and functions.
     ///
     /// The *debug_name* is just a string that the Griphin implementations will
@@ -156,3 +221,44 @@ pub trait ShaderManager: Debug + Send + Sync + 'static {
         functions: &StringRef,
     ) -> Arc<dyn ShaderLibrary>;
 }
+
+/// This error indicates that the code of a shader couldn't be compiled. It is
+/// returned by the *try_create_vertex_shader* and *try_create_fragment_shader*
+/// methods of *ShaderManager*.
+#[derive(Debug, Clone)]
+pub struct ShaderCompileError {
+    debug_name: StringRef,
+    message: StringRef,
+}
+
+impl ShaderCompileError {
+    /// Constructs a new *ShaderCompileError* for the shader with the given debug
+    /// name. The *message* should describe what went wrong (this will usually be
+    /// the output of the shader compiler).
+    ///
+    /// This function should normally only be used by Griphin implementations.
+    pub fn new(debug_name: &StringRef, message: &StringRef) -> Self {
+        Self {
+            debug_name: debug_name.clone(),
+            message: message.clone(),
+        }
+    }
+
+    /// Gets the debug name of the shader that couldn't be compiled.
+    pub fn get_debug_name(&self) -> &StringRef {
+        &self.debug_name
+    }
+
+    /// Gets the message that describes why the shader couldn't be compiled.
+    pub fn get_message(&self) -> &StringRef {
+        &self.message
+    }
+}
+
+impl Display for ShaderCompileError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "Failed to compile {}: {}", self.debug_name, self.message)
+    }
+}
+
+impl Error for ShaderCompileError {}
@@ -6,11 +6,15 @@ use std::fmt::Debug;
 mod library;
 mod manager;
 mod pair;
+mod permutation;
+mod reload;
 mod variable;
 
 pub use library::*;
 pub use manager::*;
 pub use pair::*;
+pub use permutation::*;
+pub use reload::*;
 pub use variable::*;
 
 /// Represents a vertex shader (a very well-known part of the graphics pipeline).
@@ -41,3 +45,57 @@ pub trait FragmentShader: Debug + Send + Sync + 'static {
 
     fn get_variables(&self) -> &Vec<FragmentShaderVariable>;
 }
+
+/// Represents a compute shader: a shader that is not part of the graphics
+/// pipeline, but can do general-purpose computations on the GPU (like particle
+/// simulation or culling).
+///
+/// Unlike vertex and fragment shaders, compute shaders don't need to be paired:
+/// they can be used directly by the compute nodes of a *RenderFlow*. Every
+/// variable of a compute shader comes from an 'external' source, so its
+/// variables are *ExternalShaderVariable*s. Compute shaders will typically read
+/// from and write to variables of type *StorageBuffer*.
+///
+/// To create a *ComputeShader*, use the *create_compute_shader* method of the
+/// *ShaderManager*.
+pub trait ComputeShader: Debug + Send + Sync + 'static {
+    /// This method should be used by the Griphin implementation to get access to
+    /// the implementation-specific struct that implements this trait.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Gets the debug name that was used to create this compute shader.
+    fn get_debug_name(&self) -> &StringRef;
+
+    /// Gets a *Vec* containing all shader variables of this compute shader. These
+    /// will be the same as the *variables* passed to the *create_compute_shader*
+    /// call that created this *ComputeShader*.
+    fn get_variables(&self) -> &Vec<ExternalShaderVariable>;
+
+    /// Gets the *WorkgroupSize* that was used to create this compute shader.
+    fn get_workgroup_size(&self) -> WorkgroupSize;
+}
+
+/// The number of invocations in a single workgroup of a *ComputeShader*, in each
+/// dimension. Invocations in the same workgroup run together and can share data,
+/// so this size is part of the shader itself (unlike the number of workgroups,
+/// which is chosen by the compute node that uses the shader).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorkgroupSize {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+}
+
+impl WorkgroupSize {
+    /// Constructs a new *WorkgroupSize* with the given size in each dimension.
+    pub fn new(x: u32, y: u32, z: u32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Gets the total number of invocations in a single workgroup. That is, the
+    /// product of the sizes in all 3 dimensions. Returns *None* if that product
+    /// doesn't fit in a *u32*.
+    pub fn get_num_invocations(&self) -> Option<u32> {
+        self.x.checked_mul(self.y)?.checked_mul(self.z)
+    }
+}
@@ -18,11 +18,14 @@ use std::sync::Arc;
 /// Also, the *External* shader variables of the vertex shader and the fragment
 /// shader are collected upon creating a *ShaderPair*. This is done at this point
 /// because all serious Griphin implementations will need this information anyway.
+/// The same holds for the *ColorOutput* and *DepthStencilOutput* variables of the
+/// fragment shader.
 pub struct ShaderPair {
     vertex_shader: Arc<dyn VertexShader>,
     fragment_shader: Arc<dyn FragmentShader>,
 
     external_variables: Vec<ExternalShaderVariable>,
+    output_variables: Vec<FragmentShaderVariable>,
 }
 
 impl ShaderPair {
@@ -73,10 +76,23 @@ impl ShaderPair {
             };
         }
         external_variables.shrink_to_fit();
+        let output_variables = fragment_shader
+            .get_variables()
+            .iter()
+            .filter(|variable| {
+                matches!(
+                    variable.get_variable_type(),
+                    FragmentShaderVariableType::ColorOutput
+                        | FragmentShaderVariableType::DepthStencilOutput
+                )
+            })
+            .cloned()
+            .collect();
         Ok(Self {
             vertex_shader: Arc::clone(vertex_shader),
             fragment_shader: Arc::clone(fragment_shader),
             external_variables,
+            output_variables,
         })
     }
 
@@ -96,6 +112,12 @@ impl ShaderPair {
     pub fn get_external_variables(&self) -> &Vec<ExternalShaderVariable> {
         &self.external_variables
     }
+
+    /// Gets a reference to the *Vec* holding the *ColorOutput* and
+    /// *DepthStencilOutput* variables of the fragment shader.
+    pub fn get_output_variables(&self) -> &Vec<FragmentShaderVariable> {
+        &self.output_variables
+    }
 }
 
 /// This error indicates that a *VertexShader* couldn't be linked to a certain
@@ -0,0 +1,529 @@
+use crate::*;
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+
+/// The value of a single preprocessor define of a shader permutation. A define
+/// is either a boolean flag (like "has a normal map") or an integer (like "the
+/// number of bones that affect a vertex").
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShaderDefineValue {
+    /// A boolean define. If it is *true*, the define will be declared without a
+    /// value (so it can be checked with *#ifdef*). If it is *false*, the define
+    /// won't be declared at all.
+    Bool(bool),
+
+    /// An integer define. It will always be declared, with the given value.
+    Int(i32),
+}
+
+/// A set of named preprocessor defines, which is used to choose a permutation of
+/// a *VertexShaderTemplate* or a *FragmentShaderTemplate*.
+///
+/// The same set of defines will always give the same result, regardless of the
+/// order in which the defines were set.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ShaderDefines {
+    values: BTreeMap<StringRef, ShaderDefineValue>,
+}
+
+impl ShaderDefines {
+    /// Creates a new and empty set of defines.
+    pub fn new() -> Self {
+        Self {
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the boolean define with the given name to the given value. If a
+    /// define with the same name was already set, it will be replaced.
+    pub fn set_bool(&mut self, name: &StringRef, value: bool) {
+        self.values
+            .insert(name.clone(), ShaderDefineValue::Bool(value));
+    }
+
+    /// Sets the integer define with the given name to the given value. If a
+    /// define with the same name was already set, it will be replaced.
+    pub fn set_int(&mut self, name: &StringRef, value: i32) {
+        self.values
+            .insert(name.clone(), ShaderDefineValue::Int(value));
+    }
+
+    /// Gets the value of the define with the given name, or *None* if no such
+    /// define was set.
+    pub fn get(&self, name: &str) -> Option<ShaderDefineValue> {
+        self.values.get(name).copied()
+    }
+
+    /// Checks if the boolean define with the given name was set to *true*. This
+    /// is convenient for the *variables* functions of shader templates.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.get(name) == Some(ShaderDefineValue::Bool(true))
+    }
+
+    /// Gets the value of the integer define with the given name, or *None* if
+    /// there is no integer define with that name.
+    pub fn get_int(&self, name: &str) -> Option<i32> {
+        match self.get(name) {
+            Some(ShaderDefineValue::Int(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Gets an iterator over all defines in this set, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&StringRef, &ShaderDefineValue)> {
+        self.values.iter()
+    }
+
+    /// Gets the GLSL preprocessor lines that declare these defines. These lines
+    /// will be put in front of the *other_functions* of every shader permutation.
+    pub fn get_glsl_lines(&self) -> StringRef {
+        let mut result = String::new();
+        for (name, value) in &self.values {
+            match value {
+                ShaderDefineValue::Bool(true) => result += &format!("#define {}\n", name),
+                ShaderDefineValue::Bool(false) => {}
+                ShaderDefineValue::Int(value) => result += &format!("#define {} {}\n", name, value),
+            };
+        }
+        string_ref(result)
+    }
+
+    /// Creates the set of defines that should be used when the *requested*
+    /// defines are chosen for a template with the given *declared* defines.
+    fn resolve(
+        declared: &ShaderDefines,
+        requested: &ShaderDefines,
+    ) -> Result<Self, ShaderDefineError> {
+        let mut result = declared.clone();
+        for (name, value) in &requested.values {
+            match declared.values.get(name) {
+                Some(default) => {
+                    if std::mem::discriminant(default) != std::mem::discriminant(value) {
+                        return Err(ShaderDefineError::KindMismatch {
+                            name: name.clone(),
+                            declared: *default,
+                            requested: *value,
+                        });
+                    }
+                }
+                None => return Err(ShaderDefineError::Unknown { name: name.clone() }),
+            };
+            result.values.insert(name.clone(), *value);
+        }
+        Ok(result)
+    }
+
+    fn get_debug_suffix(&self) -> String {
+        let parts: Vec<String> = self
+            .values
+            .iter()
+            .filter_map(|(name, value)| match value {
+                ShaderDefineValue::Bool(true) => Some(name.to_string()),
+                ShaderDefineValue::Bool(false) => None,
+                ShaderDefineValue::Int(value) => Some(format!("{}={}", name, value)),
+            })
+            .collect();
+        format!("[{}]", parts.join(","))
+    }
+}
+
+/// This error indicates that a permutation of a shader template was requested
+/// with defines that don't match the defines declared by that template.
+#[derive(Debug)]
+pub enum ShaderDefineError {
+    /// A define was requested that the template didn't declare.
+    Unknown { name: StringRef },
+
+    /// A define was requested with a different kind of value than the template
+    /// declared (for instance an integer value for a boolean define).
+    KindMismatch {
+        name: StringRef,
+        declared: ShaderDefineValue,
+        requested: ShaderDefineValue,
+    },
+}
+
+impl Display for ShaderDefineError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::Unknown { name } => write!(
+                f,
+                "The define {} was requested, but the shader template doesn't declare it",
+                name
+            ),
+            Self::KindMismatch {
+                name,
+                declared,
+                requested,
+            } => write!(
+                f,
+                "The define {} was declared as {:?}, but requested as {:?}",
+                name, declared, requested
+            ),
+        }
+    }
+}
+
+impl Error for ShaderDefineError {}
+
+type VariableFunction<V> = Box<dyn Fn(&ShaderDefines) -> Vec<V> + Send + Sync>;
+
+/// A vertex shader whose code contains preprocessor defines, from which any
+/// number of *VertexShader* permutations can be created.
+///
+/// A template has the same properties as the parameters of the
+/// *create_vertex_shader* method of *ShaderManager*, except that its variables
+/// are given by a function that can depend on the defines of the permutation.
+/// Furthermore, a template declares all defines it supports, together with
+/// their default values.
+///
+/// Use the *get_permutation* method to obtain the *VertexShader* for a given
+/// set of defines. Permutations are cached, so asking for the same set of
+/// defines twice will give the same *Arc<dyn VertexShader>*.
+pub struct VertexShaderTemplate {
+    manager: Arc<dyn ShaderManager>,
+    debug_name: StringRef,
+    main_function_body: StringRef,
+    other_functions: StringRef,
+    variables: VariableFunction<VertexShaderVariable>,
+    libraries: Vec<Arc<dyn ShaderLibrary>>,
+    declared_defines: ShaderDefines,
+
+    permutations: Mutex<HashMap<ShaderDefines, Arc<dyn VertexShader>>>,
+}
+
+impl VertexShaderTemplate {
+    /// Creates a new *VertexShaderTemplate*. The *declared_defines* are all
+    /// defines the template supports, and their values are used as default
+    /// values. The *variables* function will be called once for every new
+    /// permutation, with the (complete) defines of that permutation. All other
+    /// parameters have the same meaning as in the *create_vertex_shader*
+    /// method of *ShaderManager*, which will be used to create the permutations.
+    pub fn new(
+        manager: Arc<dyn ShaderManager>,
+        debug_name: &StringRef,
+        main_function_body: &StringRef,
+        other_functions: &StringRef,
+        variables: impl Fn(&ShaderDefines) -> Vec<VertexShaderVariable> + Send + Sync + 'static,
+        libraries: Vec<Arc<dyn ShaderLibrary>>,
+        declared_defines: ShaderDefines,
+    ) -> Self {
+        Self {
+            manager,
+            debug_name: debug_name.clone(),
+            main_function_body: main_function_body.clone(),
+            other_functions: other_functions.clone(),
+            variables: Box::new(variables),
+            libraries,
+            declared_defines,
+            permutations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Gets the debug name of this template. The debug names of its
+    /// permutations will be this name, followed by their enabled defines.
+    pub fn get_debug_name(&self) -> &StringRef {
+        &self.debug_name
+    }
+
+    /// Gets the defines this template declares, with their default values.
+    pub fn get_declared_defines(&self) -> &ShaderDefines {
+        &self.declared_defines
+    }
+
+    /// Gets the *VertexShader* permutation for the given *defines*. All defines
+    /// that are not in *defines* will get their default value. If this
+    /// permutation was requested before, the same *VertexShader* will be
+    /// returned. Otherwise, a new one will be created and cached.
+    ///
+    /// An error will be returned if *defines* contains a define that this
+    /// template didn't declare, or a define with the wrong kind of value.
+    pub fn get_permutation(
+        &self,
+        defines: &ShaderDefines,
+    ) -> Result<Arc<dyn VertexShader>, ShaderDefineError> {
+        let defines = ShaderDefines::resolve(&self.declared_defines, defines)?;
+        let mut permutations = self.permutations.lock().unwrap();
+        if let Some(existing) = permutations.get(&defines) {
+            return Ok(Arc::clone(existing));
+        }
+
+        let shader = self.manager.create_vertex_shader(
+            &string_ref(self.debug_name.to_string() + &defines.get_debug_suffix()),
+            &self.main_function_body,
+            &(&defines.get_glsl_lines() + &self.other_functions),
+            (self.variables)(&defines),
+            self.libraries.clone(),
+        );
+        permutations.insert(defines, Arc::clone(&shader));
+        Ok(shader)
+    }
+}
+
+/// The fragment shader counterpart of *VertexShaderTemplate*. See the
+/// documentation of *VertexShaderTemplate* for more information.
+pub struct FragmentShaderTemplate {
+    manager: Arc<dyn ShaderManager>,
+    debug_name: StringRef,
+    main_function: StringRef,
+    other_functions: StringRef,
+    variables: VariableFunction<FragmentShaderVariable>,
+    libraries: Vec<Arc<dyn ShaderLibrary>>,
+    declared_defines: ShaderDefines,
+
+    permutations: Mutex<HashMap<ShaderDefines, Arc<dyn FragmentShader>>>,
+}
+
+impl FragmentShaderTemplate {
+    /// Creates a new *FragmentShaderTemplate*. See the *new* function of
+    /// *VertexShaderTemplate* for the meaning of the parameters.
+    pub fn new(
+        manager: Arc<dyn ShaderManager>,
+        debug_name: &StringRef,
+        main_function: &StringRef,
+        other_functions: &StringRef,
+        variables: impl Fn(&ShaderDefines) -> Vec<FragmentShaderVariable> + Send + Sync + 'static,
+        libraries: Vec<Arc<dyn ShaderLibrary>>,
+        declared_defines: ShaderDefines,
+    ) -> Self {
+        Self {
+            manager,
+            debug_name: debug_name.clone(),
+            main_function: main_function.clone(),
+            other_functions: other_functions.clone(),
+            variables: Box::new(variables),
+            libraries,
+            declared_defines,
+            permutations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Gets the debug name of this template.
+    pub fn get_debug_name(&self) -> &StringRef {
+        &self.debug_name
+    }
+
+    /// Gets the defines this template declares, with their default values.
+    pub fn get_declared_defines(&self) -> &ShaderDefines {
+        &self.declared_defines
+    }
+
+    /// Gets the *FragmentShader* permutation for the given *defines*. See the
+    /// *get_permutation* method of *VertexShaderTemplate* for more information.
+    pub fn get_permutation(
+        &self,
+        defines: &ShaderDefines,
+    ) -> Result<Arc<dyn FragmentShader>, ShaderDefineError> {
+        let defines = ShaderDefines::resolve(&self.declared_defines, defines)?;
+        let mut permutations = self.permutations.lock().unwrap();
+        if let Some(existing) = permutations.get(&defines) {
+            return Ok(Arc::clone(existing));
+        }
+
+        let shader = self.manager.create_fragment_shader(
+            &string_ref(self.debug_name.to_string() + &defines.get_debug_suffix()),
+            &self.main_function,
+            &(&defines.get_glsl_lines() + &self.other_functions),
+            (self.variables)(&defines),
+            self.libraries.clone(),
+        );
+        permutations.insert(defines, Arc::clone(&shader));
+        Ok(shader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::any::Any;
+
+    #[derive(Debug)]
+    struct DummyVertexShader {
+        debug_name: StringRef,
+        other_functions: StringRef,
+        variables: Vec<VertexShaderVariable>,
+    }
+
+    impl VertexShader for DummyVertexShader {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn get_debug_name(&self) -> &StringRef {
+            &self.debug_name
+        }
+
+        fn get_variables(&self) -> &Vec<VertexShaderVariable> {
+            &self.variables
+        }
+    }
+
+    #[derive(Debug)]
+    struct DummyShaderManager {
+        created: Mutex<u32>,
+    }
+
+    impl ShaderManager for DummyShaderManager {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn create_vertex_shader(
+            &self,
+            debug_name: &StringRef,
+            _main_function_body: &StringRef,
+            other_functions: &StringRef,
+            variables: Vec<VertexShaderVariable>,
+            _libraries: Vec<Arc<dyn ShaderLibrary>>,
+        ) -> Arc<dyn VertexShader> {
+            *self.created.lock().unwrap() += 1;
+            Arc::new(DummyVertexShader {
+                debug_name: debug_name.clone(),
+                other_functions: other_functions.clone(),
+                variables,
+            })
+        }
+
+        fn create_fragment_shader(
+            &self,
+            _debug_name: &StringRef,
+            _main_function: &StringRef,
+            _other_functions: &StringRef,
+            _variables: Vec<FragmentShaderVariable>,
+            _libraries: Vec<Arc<dyn ShaderLibrary>>,
+        ) -> Arc<dyn FragmentShader> {
+            unimplemented!()
+        }
+
+        fn create_compute_shader(
+            &self,
+            _debug_name: &StringRef,
+            _main_function_body: &StringRef,
+            _other_functions: &StringRef,
+            _variables: Vec<ExternalShaderVariable>,
+            _workgroup_size: WorkgroupSize,
+            _libraries: Vec<Arc<dyn ShaderLibrary>>,
+        ) -> Arc<dyn ComputeShader> {
+            unimplemented!()
+        }
+
+        fn create_library(
+            &self,
+            _debug_name: &StringRef,
+            _functions: &StringRef,
+        ) -> Arc<dyn ShaderLibrary> {
+            unimplemented!()
+        }
+    }
+
+    fn create_template(manager: Arc<DummyShaderManager>) -> VertexShaderTemplate {
+        let mut declared = ShaderDefines::new();
+        declared.set_bool(&str_ref("NORMAL_MAP"), false);
+        declared.set_int(&str_ref("NUM_BONES"), 0);
+        VertexShaderTemplate::new(
+            manager,
+            &str_ref("test"),
+            &str_ref("gl_Position = vec4(position, 1.0);"),
+            &str_ref(""),
+            |defines| {
+                let mut variables = vec![VertexShaderVariable::new(
+                    &str_ref("position"),
+                    DataType::new(FLOAT, VEC3),
+                    VertexShaderVariableType::VertexInput,
+                )];
+                if defines.is_enabled("NORMAL_MAP") {
+                    variables.push(VertexShaderVariable::new(
+                        &str_ref("tangent"),
+                        DataType::new(FLOAT, VEC3),
+                        VertexShaderVariableType::VertexInput,
+                    ));
+                }
+                variables
+            },
+            Vec::new(),
+            declared,
+        )
+    }
+
+    fn downcast(shader: &Arc<dyn VertexShader>) -> &DummyVertexShader {
+        shader.as_any().downcast_ref::<DummyVertexShader>().unwrap()
+    }
+
+    #[test]
+    fn test_permutation_cache() {
+        let manager = Arc::new(DummyShaderManager {
+            created: Mutex::new(0),
+        });
+        let template = create_template(Arc::clone(&manager));
+
+        let mut normal_map = ShaderDefines::new();
+        normal_map.set_bool(&str_ref("NORMAL_MAP"), true);
+
+        let mut explicit_default = ShaderDefines::new();
+        explicit_default.set_int(&str_ref("NUM_BONES"), 0);
+
+        let default1 = template.get_permutation(&ShaderDefines::new()).unwrap();
+        let default2 = template.get_permutation(&explicit_default).unwrap();
+        let with_normal_map = template.get_permutation(&normal_map).unwrap();
+
+        assert!(Arc::ptr_eq(&default1, &default2));
+        assert!(!Arc::ptr_eq(&default1, &with_normal_map));
+        assert_eq!(2, *manager.created.lock().unwrap());
+    }
+
+    #[test]
+    fn test_permutation_content() {
+        let manager = Arc::new(DummyShaderManager {
+            created: Mutex::new(0),
+        });
+        let template = create_template(manager);
+
+        let mut defines = ShaderDefines::new();
+        defines.set_int(&str_ref("NUM_BONES"), 4);
+        defines.set_bool(&str_ref("NORMAL_MAP"), true);
+        let shader = template.get_permutation(&defines).unwrap();
+
+        assert_eq!(2, shader.get_variables().len());
+        assert_eq!(
+            "#define NORMAL_MAP\n#define NUM_BONES 4\n",
+            downcast(&shader).other_functions.to_str()
+        );
+        assert_eq!(
+            "test[NORMAL_MAP,NUM_BONES=4]",
+            shader.get_debug_name().to_str()
+        );
+
+        let plain = template.get_permutation(&ShaderDefines::new()).unwrap();
+        assert_eq!(1, plain.get_variables().len());
+        assert_eq!(
+            "#define NUM_BONES 0\n",
+            downcast(&plain).other_functions.to_str()
+        );
+    }
+
+    #[test]
+    fn test_bad_defines() {
+        let manager = Arc::new(DummyShaderManager {
+            created: Mutex::new(0),
+        });
+        let template = create_template(manager);
+
+        let mut unknown = ShaderDefines::new();
+        unknown.set_bool(&str_ref("SKINNING"), true);
+        match template.get_permutation(&unknown) {
+            Err(ShaderDefineError::Unknown { name }) => assert_eq!(name, "SKINNING"),
+            _ => panic!("Expected an unknown define error"),
+        };
+
+        let mut wrong_kind = ShaderDefines::new();
+        wrong_kind.set_int(&str_ref("NORMAL_MAP"), 1);
+        match template.get_permutation(&wrong_kind) {
+            Err(ShaderDefineError::KindMismatch { name, .. }) => assert_eq!(name, "NORMAL_MAP"),
+            _ => panic!("Expected a kind mismatch error"),
+        };
+    }
+}
@@ -0,0 +1,589 @@
+use crate::*;
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// The files from which the code of a reloadable shader is read. The file at
+/// *main_function_path* should contain the body of the main function, and the
+/// (optional) file at *other_functions_path* should contain the other functions.
+/// See the *create_vertex_shader* method of *ShaderManager* for more information
+/// about these two parts of the shader code.
+#[derive(Clone, Debug)]
+pub struct ShaderSourceFiles {
+    pub main_function_path: PathBuf,
+    pub other_functions_path: Option<PathBuf>,
+}
+
+impl ShaderSourceFiles {
+    fn read(&self) -> io::Result<(String, String)> {
+        let main_function = fs::read_to_string(&self.main_function_path)?;
+        let other_functions = match &self.other_functions_path {
+            Some(path) => fs::read_to_string(path)?,
+            None => String::new(),
+        };
+        Ok((main_function, other_functions))
+    }
+}
+
+/// This error indicates that a reloadable shader (or shader pair) couldn't be
+/// (re)loaded. Whenever this happens, the previous version of the shader stays
+/// in use.
+#[derive(Debug)]
+pub enum ShaderReloadError {
+    /// One of the source files couldn't be read.
+    Io(io::Error),
+
+    /// The new shader code couldn't be compiled.
+    Compile(ShaderCompileError),
+
+    /// The new vertex shader couldn't be linked to the new fragment shader.
+    Link(Box<ShaderLinkError<ShaderNameLinkError>>),
+}
+
+impl Display for ShaderReloadError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Self::Io(error) => write!(f, "Failed to read shader file: {}", error),
+            Self::Compile(error) => error.fmt(f),
+            Self::Link(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for ShaderReloadError {}
+
+struct ReloadState<S: ?Sized> {
+    current: Arc<S>,
+    source: (String, String),
+    generation: u32,
+}
+
+/// A *VertexShader* whose code is read from files on disk, and that can be
+/// recreated whenever these files change.
+///
+/// Use the *poll* method regularly (for instance once per frame) to check if the
+/// files have changed. If they did, a new *VertexShader* will be created with the
+/// new code. If that fails, the previous *VertexShader* stays in use and the error
+/// is returned (so you can fix the shader file while the application is running).
+///
+/// The *variables* and *libraries* are not read from the files, so they stay the
+/// same during reloads.
+pub struct ReloadableVertexShader {
+    manager: Arc<dyn ShaderManager>,
+    debug_name: StringRef,
+    files: ShaderSourceFiles,
+    variables: Vec<VertexShaderVariable>,
+    libraries: Vec<Arc<dyn ShaderLibrary>>,
+
+    state: Mutex<ReloadState<dyn VertexShader>>,
+}
+
+impl ReloadableVertexShader {
+    /// Reads the given *files* and creates the first version of the vertex shader.
+    /// Unlike later reloads, this will fail if the files can't be read or the
+    /// shader can't be compiled (since there is no previous version to keep).
+    pub fn new(
+        manager: Arc<dyn ShaderManager>,
+        debug_name: &StringRef,
+        files: ShaderSourceFiles,
+        variables: Vec<VertexShaderVariable>,
+        libraries: Vec<Arc<dyn ShaderLibrary>>,
+    ) -> Result<Self, ShaderReloadError> {
+        let source = files.read().map_err(ShaderReloadError::Io)?;
+        let current = manager
+            .try_create_vertex_shader(
+                debug_name,
+                &string_ref(source.0.clone()),
+                &string_ref(source.1.clone()),
+                variables.clone(),
+                libraries.clone(),
+            )
+            .map_err(ShaderReloadError::Compile)?;
+        Ok(Self {
+            manager,
+            debug_name: debug_name.clone(),
+            files,
+            variables,
+            libraries,
+            state: Mutex::new(ReloadState {
+                current,
+                source,
+                generation: 0,
+            }),
+        })
+    }
+
+    /// Gets the most recent version of the vertex shader that was created
+    /// successfully.
+    pub fn get_current(&self) -> Arc<dyn VertexShader> {
+        Arc::clone(&self.state.lock().unwrap().current)
+    }
+
+    /// Gets the number of times this vertex shader has been reloaded
+    /// successfully. This can be used to find out whether objects that were
+    /// created from the previous version (like *GraphicsPipeline*s) need to be
+    /// recreated.
+    pub fn get_generation(&self) -> u32 {
+        self.state.lock().unwrap().generation
+    }
+
+    /// Reads the source files and recreates the vertex shader if their content
+    /// changed since the last successful (re)load. Returns *Ok(true)* if the
+    /// shader was recreated, *Ok(false)* if nothing changed, and an error if the
+    /// files couldn't be read or the new code couldn't be compiled. In the last
+    /// case, the previous version will stay in use.
+    pub fn poll(&self) -> Result<bool, ShaderReloadError> {
+        let source = self.files.read().map_err(ShaderReloadError::Io)?;
+        let mut state = self.state.lock().unwrap();
+        if state.source == source {
+            return Ok(false);
+        }
+
+        let new_shader = self
+            .manager
+            .try_create_vertex_shader(
+                &self.debug_name,
+                &string_ref(source.0.clone()),
+                &string_ref(source.1.clone()),
+                self.variables.clone(),
+                self.libraries.clone(),
+            )
+            .map_err(ShaderReloadError::Compile)?;
+        state.current = new_shader;
+        state.source = source;
+        state.generation += 1;
+        Ok(true)
+    }
+}
+
+/// The fragment shader counterpart of *ReloadableVertexShader*. See the
+/// documentation of *ReloadableVertexShader* for more information.
+pub struct ReloadableFragmentShader {
+    manager: Arc<dyn ShaderManager>,
+    debug_name: StringRef,
+    files: ShaderSourceFiles,
+    variables: Vec<FragmentShaderVariable>,
+    libraries: Vec<Arc<dyn ShaderLibrary>>,
+
+    state: Mutex<ReloadState<dyn FragmentShader>>,
+}
+
+impl ReloadableFragmentShader {
+    /// Reads the given *files* and creates the first version of the fragment
+    /// shader. See the *new* function of *ReloadableVertexShader*.
+    pub fn new(
+        manager: Arc<dyn ShaderManager>,
+        debug_name: &StringRef,
+        files: ShaderSourceFiles,
+        variables: Vec<FragmentShaderVariable>,
+        libraries: Vec<Arc<dyn ShaderLibrary>>,
+    ) -> Result<Self, ShaderReloadError> {
+        let source = files.read().map_err(ShaderReloadError::Io)?;
+        let current = manager
+            .try_create_fragment_shader(
+                debug_name,
+                &string_ref(source.0.clone()),
+                &string_ref(source.1.clone()),
+                variables.clone(),
+                libraries.clone(),
+            )
+            .map_err(ShaderReloadError::Compile)?;
+        Ok(Self {
+            manager,
+            debug_name: debug_name.clone(),
+            files,
+            variables,
+            libraries,
+            state: Mutex::new(ReloadState {
+                current,
+                source,
+                generation: 0,
+            }),
+        })
+    }
+
+    /// Gets the most recent version of the fragment shader that was created
+    /// successfully.
+    pub fn get_current(&self) -> Arc<dyn FragmentShader> {
+        Arc::clone(&self.state.lock().unwrap().current)
+    }
+
+    /// Gets the number of times this fragment shader has been reloaded
+    /// successfully.
+    pub fn get_generation(&self) -> u32 {
+        self.state.lock().unwrap().generation
+    }
+
+    /// Reads the source files and recreates the fragment shader if their content
+    /// changed. See the *poll* method of *ReloadableVertexShader*.
+    pub fn poll(&self) -> Result<bool, ShaderReloadError> {
+        let source = self.files.read().map_err(ShaderReloadError::Io)?;
+        let mut state = self.state.lock().unwrap();
+        if state.source == source {
+            return Ok(false);
+        }
+
+        let new_shader = self
+            .manager
+            .try_create_fragment_shader(
+                &self.debug_name,
+                &string_ref(source.0.clone()),
+                &string_ref(source.1.clone()),
+                self.variables.clone(),
+                self.libraries.clone(),
+            )
+            .map_err(ShaderReloadError::Compile)?;
+        state.current = new_shader;
+        state.source = source;
+        state.generation += 1;
+        Ok(true)
+    }
+}
+
+struct PairState {
+    current: Arc<ShaderPair>,
+    vertex_generation: u32,
+    fragment_generation: u32,
+    generation: u32,
+}
+
+/// A *ShaderPair* of a *ReloadableVertexShader* and a *ReloadableFragmentShader*
+/// that is linked again whenever one of these shaders is reloaded.
+///
+/// The shaders are linked with the *link_by_attribute_names* function of
+/// *ShaderPair*. If the reloaded shaders can't be linked, the previous
+/// *ShaderPair* stays in use.
+///
+/// Note that *GraphicsPipeline*s are *not* recreated automatically: use the
+/// *get_generation* method to find out when pipelines need to be recreated
+/// using the new *ShaderPair*.
+pub struct ReloadableShaderPair {
+    vertex_shader: Arc<ReloadableVertexShader>,
+    fragment_shader: Arc<ReloadableFragmentShader>,
+
+    state: Mutex<PairState>,
+}
+
+impl ReloadableShaderPair {
+    /// Links the current versions of the given shaders. This fails if they can't
+    /// be linked.
+    pub fn new(
+        vertex_shader: Arc<ReloadableVertexShader>,
+        fragment_shader: Arc<ReloadableFragmentShader>,
+    ) -> Result<Self, ShaderReloadError> {
+        let vertex_generation = vertex_shader.get_generation();
+        let fragment_generation = fragment_shader.get_generation();
+        let current = ShaderPair::link_by_attribute_names(
+            &vertex_shader.get_current(),
+            &fragment_shader.get_current(),
+        )
+        .map_err(|error| ShaderReloadError::Link(Box::new(error)))?;
+        Ok(Self {
+            vertex_shader,
+            fragment_shader,
+            state: Mutex::new(PairState {
+                current: Arc::new(current),
+                vertex_generation,
+                fragment_generation,
+                generation: 0,
+            }),
+        })
+    }
+
+    /// Gets the most recent *ShaderPair* that was linked successfully.
+    pub fn get_current(&self) -> Arc<ShaderPair> {
+        Arc::clone(&self.state.lock().unwrap().current)
+    }
+
+    /// Gets the number of times this shader pair has been linked again
+    /// successfully.
+    pub fn get_generation(&self) -> u32 {
+        self.state.lock().unwrap().generation
+    }
+
+    /// Polls both shaders (see the *poll* method of *ReloadableVertexShader*) and
+    /// links them again if at least 1 of them was reloaded (possibly during an
+    /// earlier *poll* of another *ReloadableShaderPair* that shares a shader).
+    ///
+    /// Returns *Ok(true)* if the pair was linked again, *Ok(false)* if nothing
+    /// changed, and an error if one of the shaders couldn't be reloaded or the
+    /// new shaders couldn't be linked. In the latter case, the previous
+    /// *ShaderPair* stays in use, and every subsequent *poll* will try to link
+    /// the shaders again (and return the link error) until they can be linked.
+    pub fn poll(&self) -> Result<bool, ShaderReloadError> {
+        let vertex_result = self.vertex_shader.poll();
+        let fragment_result = self.fragment_shader.poll();
+
+        let mut state = self.state.lock().unwrap();
+        let vertex_generation = self.vertex_shader.get_generation();
+        let fragment_generation = self.fragment_shader.get_generation();
+        let changed = vertex_generation != state.vertex_generation
+            || fragment_generation != state.fragment_generation;
+
+        let link_result = if changed {
+            match ShaderPair::link_by_attribute_names(
+                &self.vertex_shader.get_current(),
+                &self.fragment_shader.get_current(),
+            ) {
+                Ok(new_pair) => {
+                    state.vertex_generation = vertex_generation;
+                    state.fragment_generation = fragment_generation;
+                    state.current = Arc::new(new_pair);
+                    state.generation += 1;
+                    Ok(true)
+                }
+                Err(link_error) => Err(ShaderReloadError::Link(Box::new(link_error))),
+            }
+        } else {
+            Ok(false)
+        };
+
+        vertex_result?;
+        fragment_result?;
+        link_result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::any::Any;
+    use std::path::Path;
+
+    #[derive(Debug)]
+    struct DummyVertexShader {
+        debug_name: StringRef,
+        variables: Vec<VertexShaderVariable>,
+    }
+
+    impl VertexShader for DummyVertexShader {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn get_debug_name(&self) -> &StringRef {
+            &self.debug_name
+        }
+
+        fn get_variables(&self) -> &Vec<VertexShaderVariable> {
+            &self.variables
+        }
+    }
+
+    #[derive(Debug)]
+    struct DummyFragmentShader {
+        debug_name: StringRef,
+        variables: Vec<FragmentShaderVariable>,
+    }
+
+    impl FragmentShader for DummyFragmentShader {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn get_debug_name(&self) -> &StringRef {
+            &self.debug_name
+        }
+
+        fn get_variables(&self) -> &Vec<FragmentShaderVariable> {
+            &self.variables
+        }
+    }
+
+    /// Considers every shader with "error" in its main function as invalid
+    #[derive(Debug)]
+    struct DummyShaderManager {}
+
+    impl ShaderManager for DummyShaderManager {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn create_vertex_shader(
+            &self,
+            _debug_name: &StringRef,
+            _main_function_body: &StringRef,
+            _other_functions: &StringRef,
+            _variables: Vec<VertexShaderVariable>,
+            _libraries: Vec<Arc<dyn ShaderLibrary>>,
+        ) -> Arc<dyn VertexShader> {
+            unimplemented!()
+        }
+
+        fn create_fragment_shader(
+            &self,
+            _debug_name: &StringRef,
+            _main_function: &StringRef,
+            _other_functions: &StringRef,
+            _variables: Vec<FragmentShaderVariable>,
+            _libraries: Vec<Arc<dyn ShaderLibrary>>,
+        ) -> Arc<dyn FragmentShader> {
+            unimplemented!()
+        }
+
+        fn try_create_vertex_shader(
+            &self,
+            debug_name: &StringRef,
+            main_function_body: &StringRef,
+            _other_functions: &StringRef,
+            variables: Vec<VertexShaderVariable>,
+            _libraries: Vec<Arc<dyn ShaderLibrary>>,
+        ) -> Result<Arc<dyn VertexShader>, ShaderCompileError> {
+            if main_function_body.to_str().contains("error") {
+                return Err(ShaderCompileError::new(debug_name, &str_ref("error")));
+            }
+            Ok(Arc::new(DummyVertexShader {
+                debug_name: debug_name.clone(),
+                variables,
+            }))
+        }
+
+        fn try_create_fragment_shader(
+            &self,
+            debug_name: &StringRef,
+            main_function: &StringRef,
+            _other_functions: &StringRef,
+            variables: Vec<FragmentShaderVariable>,
+            _libraries: Vec<Arc<dyn ShaderLibrary>>,
+        ) -> Result<Arc<dyn FragmentShader>, ShaderCompileError> {
+            if main_function.to_str().contains("error") {
+                return Err(ShaderCompileError::new(debug_name, &str_ref("error")));
+            }
+            Ok(Arc::new(DummyFragmentShader {
+                debug_name: debug_name.clone(),
+                variables,
+            }))
+        }
+
+        fn create_compute_shader(
+            &self,
+            _debug_name: &StringRef,
+            _main_function_body: &StringRef,
+            _other_functions: &StringRef,
+            _variables: Vec<ExternalShaderVariable>,
+            _workgroup_size: WorkgroupSize,
+            _libraries: Vec<Arc<dyn ShaderLibrary>>,
+        ) -> Arc<dyn ComputeShader> {
+            unimplemented!()
+        }
+
+        fn create_library(
+            &self,
+            _debug_name: &StringRef,
+            _functions: &StringRef,
+        ) -> Arc<dyn ShaderLibrary> {
+            unimplemented!()
+        }
+    }
+
+    fn create_test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("griphin_reload_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn source_files(path: &Path) -> ShaderSourceFiles {
+        ShaderSourceFiles {
+            main_function_path: path.to_path_buf(),
+            other_functions_path: None,
+        }
+    }
+
+    #[test]
+    fn test_reload_vertex_shader() {
+        let dir = create_test_dir("vertex");
+        let path = dir.join("test.vert");
+        fs::write(&path, "gl_Position = vec4(0.0);").unwrap();
+
+        let shader = ReloadableVertexShader::new(
+            Arc::new(DummyShaderManager {}),
+            &str_ref("test"),
+            source_files(&path),
+            Vec::new(),
+            Vec::new(),
+        )
+        .unwrap();
+        let original = shader.get_current();
+
+        assert!(!shader.poll().unwrap());
+        assert!(Arc::ptr_eq(&original, &shader.get_current()));
+
+        fs::write(&path, "gl_Position = vec4(1.0);").unwrap();
+        assert!(shader.poll().unwrap());
+        assert_eq!(1, shader.get_generation());
+        let reloaded = shader.get_current();
+        assert!(!Arc::ptr_eq(&original, &reloaded));
+
+        // A broken version must keep the last working version alive
+        fs::write(&path, "error").unwrap();
+        match shader.poll() {
+            Err(ShaderReloadError::Compile(_)) => {}
+            _ => panic!("Expected a compile error"),
+        };
+        assert_eq!(1, shader.get_generation());
+        assert!(Arc::ptr_eq(&reloaded, &shader.get_current()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reload_shader_pair() {
+        let dir = create_test_dir("pair");
+        let vertex_path = dir.join("test.vert");
+        let fragment_path = dir.join("test.frag");
+        fs::write(&vertex_path, "gl_Position = vec4(0.0);").unwrap();
+        fs::write(&fragment_path, "outColor = vec4(1.0);").unwrap();
+
+        let manager: Arc<dyn ShaderManager> = Arc::new(DummyShaderManager {});
+        let vertex_shader = Arc::new(
+            ReloadableVertexShader::new(
+                Arc::clone(&manager),
+                &str_ref("vertex"),
+                source_files(&vertex_path),
+                vec![VertexShaderVariable::new(
+                    &str_ref("color"),
+                    DataType::new(FLOAT, VEC4),
+                    VertexShaderVariableType::SmoothFragmentOutput,
+                )],
+                Vec::new(),
+            )
+            .unwrap(),
+        );
+        let fragment_shader = Arc::new(
+            ReloadableFragmentShader::new(
+                manager,
+                &str_ref("fragment"),
+                source_files(&fragment_path),
+                vec![FragmentShaderVariable::new(
+                    &str_ref("color"),
+                    DataType::new(FLOAT, VEC4),
+                    FragmentShaderVariableType::SmoothVertexInput,
+                )],
+                Vec::new(),
+            )
+            .unwrap(),
+        );
+        let pair = ReloadableShaderPair::new(vertex_shader, fragment_shader).unwrap();
+        let original = pair.get_current();
+
+        assert!(!pair.poll().unwrap());
+
+        fs::write(&fragment_path, "outColor = vec4(0.5);").unwrap();
+        assert!(pair.poll().unwrap());
+        assert_eq!(1, pair.get_generation());
+        let relinked = pair.get_current();
+        assert!(!Arc::ptr_eq(&original, &relinked));
+
+        fs::write(&vertex_path, "error").unwrap();
+        assert!(pair.poll().is_err());
+        assert!(Arc::ptr_eq(&relinked, &pair.get_current()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
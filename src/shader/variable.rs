@@ -82,15 +82,42 @@ pub enum ExternalShaderVariableType {
 
     /// This variable is an input texture that is stored on a *ColorGrid* in the
     /// *RenderFlow*. Any pixel of the color grid can be accessed freely. Which
-    /// color grid to use must be specified when creating the render flow.
+    /// color grid to use must be specified when creating the render flow. To read
+    /// a color grid that was written during a previous render flow, use an
+    /// *External* or *SampledGrid* *DrawingNodeInputSource*.
     ColorGridInput,
 
     /// This variable is an input texture that stores depth & stencil values
     /// rather than colors. It reads its data from a *DepthStencilGrid* in the
     /// same *RenderFlow*. Any depth-stencil value can be accessed freely (
     /// although I don't see many use cases for this). Which depth stencil grid
-    /// to use must be specified when creating the render flow.
+    /// to use must be specified when creating the render flow. Like with
+    /// *ColorGridInput*, it can also read a grid that was written during a
+    /// previous render flow (for instance a shadow map).
     DepthStencilGridInput,
+
+    /// This variable is a buffer on the GPU that can be both read and written by
+    /// the shader. The *DataType* of the variable is the type of the elements of
+    /// the buffer, and the number of elements is not fixed. The elements are laid
+    /// out according to the *std430* rules. Storage buffers are mostly useful for
+    /// large amounts of structured data (like skinning matrices or light lists)
+    /// and for compute shaders, whose results must be stored somewhere.
+    ///
+    /// Values for this variable are *StorageBuffer*s, which can be created with
+    /// the *Gateway*.
+    StorageBuffer,
+
+    /// This variable is a small constant that can be changed for every draw,
+    /// without the overhead of a uniform buffer (a push constant, if you are
+    /// familiar with Vulkan). This is typically used for the model matrix or the
+    /// material index of each draw.
+    ///
+    /// All push constants of a pipeline are laid out according to the *std430*
+    /// rules, in the order of its external variables (see *PushConstantLayout*).
+    /// Their total size must not exceed the *get_max_push_constant_size* of the
+    /// *Instance*. Values can be set with the *set_push_constant* method of
+    /// *DrawCommandRecorder*.
+    PushConstant,
 }
 
 /// Represents a variable of a vertex shader. This struct is a simple tuple of name,
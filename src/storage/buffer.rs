@@ -0,0 +1,35 @@
+use crate::*;
+
+/// Represents a buffer in GPU memory that shaders can both read and write. It can be bound to
+/// shader variables of type *StorageBuffer*. The buffer consists of a number of elements that all
+/// have the same *DataType*, and these elements are laid out according to the *std430* rules.
+///
+/// To obtain a *StorageBuffer*, either create a *StorageBufferStore* and transfer it through the
+/// *Gateway*, or use the *create_storage_buffer* method of the *Gateway* when the content will be
+/// written by the GPU.
+pub trait StorageBuffer: Send + Sync {
+    /// Gets the *DataType* of the elements of this *StorageBuffer*. This should match the
+    /// *DataType* of the shader variables it is bound to.
+    fn get_element_type(&self) -> DataType;
+
+    /// Gets the number of elements in this *StorageBuffer*.
+    fn get_num_elements(&self) -> usize;
+
+    /// Gets the size of this *StorageBuffer*, in bytes. This is the number of elements multiplied
+    /// by the *std430* array stride of the element type.
+    fn get_size(&self) -> usize {
+        self.get_num_elements()
+            * self
+                .get_element_type()
+                .get_shape()
+                .get_std430_array_stride()
+    }
+
+    /// Checks if this *StorageBuffer* is ready to be used right away. See the *is_ready* method
+    /// of *VertexBuffer* for more information.
+    fn is_ready(&self) -> bool;
+
+    /// Blocks the current thread until this *StorageBuffer* is ready to be used. See the
+    /// *await_ready* method of *VertexBuffer* for more information.
+    fn await_ready(&self);
+}
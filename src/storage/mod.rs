@@ -0,0 +1,5 @@
+mod buffer;
+mod store;
+
+pub use buffer::*;
+pub use store::*;
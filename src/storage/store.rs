@@ -0,0 +1,235 @@
+use crate::*;
+use cgmath::*;
+
+/// Represents a byte buffer (or more accurately *Vec\<u8\>*) that is filled with the elements of
+/// a storage buffer, and ready to be sent through the *Gateway*.
+///
+/// All elements of the store have the same *DataType*, and they are laid out according to the
+/// *std430* rules: every element occupies *get_std430_array_stride()* bytes of its *DataShape*
+/// and the columns of matrices are padded to their alignment. The *put* methods of this struct
+/// take care of this layout, so users don't need to worry about it.
+///
+/// Every element starts with only zero bytes, so elements that are never *put* will be zero.
+pub struct StorageBufferStore {
+    element_type: DataType,
+    num_elements: usize,
+    raw_buffer: Vec<u8>,
+}
+
+impl StorageBufferStore {
+    /// Constructs a new *StorageBufferStore* that can hold *num_elements* elements of the given
+    /// *element_type*. All elements will initially be zero.
+    pub fn new(element_type: DataType, num_elements: usize) -> Self {
+        let stride = element_type.get_shape().get_std430_array_stride();
+        Self {
+            element_type,
+            num_elements,
+            raw_buffer: vec![0; stride * num_elements],
+        }
+    }
+
+    /// Gets the *DataType* of the elements of this store.
+    pub fn get_element_type(&self) -> DataType {
+        self.element_type
+    }
+
+    /// Gets the number of elements this store can hold.
+    pub fn get_num_elements(&self) -> usize {
+        self.num_elements
+    }
+
+    /// Gets a reference to the raw byte buffer of this *StorageBufferStore*. This method is
+    /// intended to be used by Griphin implementations, but users are free to use it as well.
+    pub fn get_raw_buffer(&self) -> &Vec<u8> {
+        &self.raw_buffer
+    }
+
+    fn get_offset(&self, index: usize, kind: DataKind, shape: DataShape) -> usize {
+        if self.element_type != DataType::new(kind, shape) {
+            panic!(
+                "This store has element type {:?}, but a value of type {:?} was put",
+                self.element_type,
+                DataType::new(kind, shape)
+            );
+        }
+        if index >= self.num_elements {
+            panic!(
+                "Element index {} is out of bounds for a store with {} elements",
+                index, self.num_elements
+            );
+        }
+        index * shape.get_std430_array_stride()
+    }
+
+    fn put_bytes_at(&mut self, offset: usize, bytes: [u8; 4]) {
+        self.raw_buffer[offset..offset + 4].copy_from_slice(&bytes);
+    }
+
+    fn put_column_at(&mut self, offset: usize, column: &[f32]) {
+        for (row, value) in column.iter().enumerate() {
+            self.put_bytes_at(offset + 4 * row, value.to_ne_bytes());
+        }
+    }
+
+    /// Sets the element at *index* to *value*
+    pub fn put_int(&mut self, index: usize, value: i32) {
+        let offset = self.get_offset(index, INT, SINGLE);
+        self.put_bytes_at(offset, value.to_ne_bytes());
+    }
+
+    /// Sets the element at *index* to *value*
+    pub fn put_float(&mut self, index: usize, value: f32) {
+        let offset = self.get_offset(index, FLOAT, SINGLE);
+        self.put_bytes_at(offset, value.to_ne_bytes());
+    }
+
+    /// Sets the element at *index* to *value*
+    pub fn put_bool(&mut self, index: usize, value: bool) {
+        let offset = self.get_offset(index, BOOL, SINGLE);
+        self.put_bytes_at(offset, (value as i32).to_ne_bytes());
+    }
+
+    /// Sets the element at *index* to *values*
+    pub fn put_vec2i(&mut self, index: usize, values: Vector2<i32>) {
+        let offset = self.get_offset(index, INT, VEC2);
+        for component in 0..2 {
+            self.put_bytes_at(offset + 4 * component, values[component].to_ne_bytes());
+        }
+    }
+
+    /// Sets the element at *index* to *values*
+    pub fn put_vec2f(&mut self, index: usize, values: Vector2<f32>) {
+        let offset = self.get_offset(index, FLOAT, VEC2);
+        let column: [f32; 2] = values.into();
+        self.put_column_at(offset, &column);
+    }
+
+    /// Sets the element at *index* to *values*
+    pub fn put_vec3i(&mut self, index: usize, values: Vector3<i32>) {
+        let offset = self.get_offset(index, INT, VEC3);
+        for component in 0..3 {
+            self.put_bytes_at(offset + 4 * component, values[component].to_ne_bytes());
+        }
+    }
+
+    /// Sets the element at *index* to *values*
+    pub fn put_vec3f(&mut self, index: usize, values: Vector3<f32>) {
+        let offset = self.get_offset(index, FLOAT, VEC3);
+        let column: [f32; 3] = values.into();
+        self.put_column_at(offset, &column);
+    }
+
+    /// Sets the element at *index* to *values*
+    pub fn put_vec4i(&mut self, index: usize, values: Vector4<i32>) {
+        let offset = self.get_offset(index, INT, VEC4);
+        for component in 0..4 {
+            self.put_bytes_at(offset + 4 * component, values[component].to_ne_bytes());
+        }
+    }
+
+    /// Sets the element at *index* to *values*
+    pub fn put_vec4f(&mut self, index: usize, values: Vector4<f32>) {
+        let offset = self.get_offset(index, FLOAT, VEC4);
+        let column: [f32; 4] = values.into();
+        self.put_column_at(offset, &column);
+    }
+
+    /// Sets the element at *index* to *matrix*
+    pub fn put_mat2f(&mut self, index: usize, matrix: Matrix2<f32>) {
+        let offset = self.get_offset(index, FLOAT, MAT2);
+        let column_stride = MAT2.get_std430_alignment();
+        for column_index in 0..2 {
+            let column: [f32; 2] = matrix[column_index].into();
+            self.put_column_at(offset + column_index * column_stride, &column);
+        }
+    }
+
+    /// Sets the element at *index* to *matrix*
+    pub fn put_mat3f(&mut self, index: usize, matrix: Matrix3<f32>) {
+        let offset = self.get_offset(index, FLOAT, MAT3);
+        let column_stride = MAT3.get_std430_alignment();
+        for column_index in 0..3 {
+            let column: [f32; 3] = matrix[column_index].into();
+            self.put_column_at(offset + column_index * column_stride, &column);
+        }
+    }
+
+    /// Sets the element at *index* to *matrix*
+    pub fn put_mat4f(&mut self, index: usize, matrix: Matrix4<f32>) {
+        let offset = self.get_offset(index, FLOAT, MAT4);
+        let column_stride = MAT4.get_std430_alignment();
+        for column_index in 0..4 {
+            let column: [f32; 4] = matrix[column_index].into();
+            self.put_column_at(offset + column_index * column_stride, &column);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::convert::TryInto;
+
+    fn get_float_at(store: &StorageBufferStore, offset: usize) -> f32 {
+        f32::from_ne_bytes(
+            store.get_raw_buffer()[offset..offset + 4]
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_std430_sizes() {
+        assert_eq!(4, SINGLE.get_std430_array_stride());
+        assert_eq!(8, VEC2.get_std430_array_stride());
+        assert_eq!(12, VEC3.get_std430_size());
+        assert_eq!(16, VEC3.get_std430_array_stride());
+        assert_eq!(16, VEC4.get_std430_array_stride());
+        assert_eq!(16, MAT2.get_std430_array_stride());
+        assert_eq!(48, MAT3.get_std430_array_stride());
+        assert_eq!(64, MAT4.get_std430_array_stride());
+    }
+
+    #[test]
+    fn test_vec3_padding() {
+        let mut store = StorageBufferStore::new(DataType::new(FLOAT, VEC3), 2);
+        assert_eq!(32, store.get_raw_buffer().len());
+
+        store.put_vec3f(1, Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(0.0, get_float_at(&store, 0));
+        assert_eq!(0.0, get_float_at(&store, 12));
+        assert_eq!(1.0, get_float_at(&store, 16));
+        assert_eq!(2.0, get_float_at(&store, 20));
+        assert_eq!(3.0, get_float_at(&store, 24));
+        assert_eq!(0.0, get_float_at(&store, 28));
+    }
+
+    #[test]
+    fn test_mat3_columns() {
+        let mut store = StorageBufferStore::new(DataType::new(FLOAT, MAT3), 1);
+        store.put_mat3f(0, Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0));
+
+        // Every column is padded to 16 bytes
+        assert_eq!(1.0, get_float_at(&store, 0));
+        assert_eq!(3.0, get_float_at(&store, 8));
+        assert_eq!(4.0, get_float_at(&store, 16));
+        assert_eq!(7.0, get_float_at(&store, 32));
+        assert_eq!(9.0, get_float_at(&store, 40));
+        assert_eq!(0.0, get_float_at(&store, 44));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wrong_type() {
+        let mut store = StorageBufferStore::new(DataType::new(FLOAT, VEC4), 3);
+        store.put_vec3f(0, Vector3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_bounds() {
+        let mut store = StorageBufferStore::new(DataType::new(INT, SINGLE), 3);
+        store.put_int(3, 5);
+    }
+}
@@ -0,0 +1,439 @@
+//! Dummy implementations of the Griphin traits, which are shared by the unit
+//! tests of this crate.
+
+use crate::*;
+
+use std::any::Any;
+use std::sync::{Arc, Condvar, Mutex};
+
+pub(crate) struct DummyPipeline {
+    pub(crate) name: StringRef,
+    pub(crate) variables: Vec<ExternalShaderVariable>,
+    pub(crate) outputs: Vec<FragmentShaderVariable>,
+    pub(crate) state: PipelineState,
+    pub(crate) topology: PrimitiveTopology,
+}
+
+impl GraphicsPipeline for DummyPipeline {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn get_external_variables(&self) -> &Vec<ExternalShaderVariable> {
+        &self.variables
+    }
+
+    fn get_output_variables(&self) -> &Vec<FragmentShaderVariable> {
+        &self.outputs
+    }
+
+    fn get_state(&self) -> &PipelineState {
+        &self.state
+    }
+
+    fn get_topology(&self) -> PrimitiveTopology {
+        self.topology
+    }
+
+    fn get_debug_name(&self) -> &StringRef {
+        &self.name
+    }
+}
+
+/// Creates a *DummyPipeline* without output variables and with the given state
+/// and topology.
+pub(crate) fn create_pipeline_with_state(
+    variables: Vec<ExternalShaderVariable>,
+    state: &PipelineStateBuilder,
+    topology: PrimitiveTopology,
+) -> Arc<dyn GraphicsPipeline> {
+    Arc::new(DummyPipeline {
+        name: str_ref("dummy"),
+        variables,
+        outputs: Vec::new(),
+        state: PipelineState::resolve(state, topology),
+        topology,
+    })
+}
+
+/// Creates a *DummyPipeline* with the default state and the *Triangles*
+/// topology.
+pub(crate) fn create_pipeline(variables: Vec<ExternalShaderVariable>) -> Arc<dyn GraphicsPipeline> {
+    create_pipeline_with_state(
+        variables,
+        &PipelineStateBuilder::new(),
+        PrimitiveTopology::Triangles,
+    )
+}
+
+/// Creates a *DummyPipeline* without variables and with the given sample count.
+pub(crate) fn create_multisampled_pipeline(sample_count: SampleCount) -> Arc<dyn GraphicsPipeline> {
+    let mut state = PipelineStateBuilder::new();
+    state.sample_count = sample_count;
+    create_pipeline_with_state(Vec::new(), &state, PrimitiveTopology::Triangles)
+}
+
+/// Creates a *DummyPipeline* without variables and with the given debug name.
+pub(crate) fn create_named_pipeline(name: &'static str) -> Arc<dyn GraphicsPipeline> {
+    Arc::new(DummyPipeline {
+        name: str_ref(name),
+        variables: Vec::new(),
+        outputs: Vec::new(),
+        state: PipelineState::resolve(&PipelineStateBuilder::new(), PrimitiveTopology::Triangles),
+        topology: PrimitiveTopology::Triangles,
+    })
+}
+
+/// Creates a *DummyPipeline* without external variables and with the given
+/// output variables.
+pub(crate) fn create_pipeline_with_outputs(
+    outputs: Vec<FragmentShaderVariable>,
+) -> Arc<dyn GraphicsPipeline> {
+    Arc::new(DummyPipeline {
+        name: str_ref("dummy"),
+        variables: Vec::new(),
+        outputs,
+        state: PipelineState::resolve(&PipelineStateBuilder::new(), PrimitiveTopology::Triangles),
+        topology: PrimitiveTopology::Triangles,
+    })
+}
+
+/// Creates an *ExternalShaderVariable* with the given properties.
+pub(crate) fn variable(
+    name: &'static str,
+    data_type: DataType,
+    variable_type: ExternalShaderVariableType,
+) -> ExternalShaderVariable {
+    ExternalShaderVariable::new(&str_ref(name), data_type, variable_type)
+}
+
+/// Creates a *FragmentShaderVariable* with the given properties.
+pub(crate) fn fragment_variable(
+    name: &'static str,
+    data_type: DataType,
+    variable_type: FragmentShaderVariableType,
+) -> FragmentShaderVariable {
+    FragmentShaderVariable::new(&str_ref(name), data_type, variable_type)
+}
+
+/// Creates a *DrawingNodeBuilder* with the given pipeline, where the inputs and
+/// outputs are paired with their shader variable names.
+pub(crate) fn create_drawing_node(
+    pipeline: Arc<dyn GraphicsPipeline>,
+    inputs: Vec<(DrawingNodeInputSource, &'static str)>,
+    outputs: Vec<(DrawingNodeOutputDestination, &'static str)>,
+) -> DrawingNodeBuilder {
+    DrawingNodeBuilder {
+        pipeline,
+        inputs: inputs
+            .into_iter()
+            .map(|(source, name)| DrawingNodeInput {
+                source,
+                shader_variable_name: str_ref(name),
+            })
+            .collect(),
+        outputs: outputs
+            .into_iter()
+            .map(|(destination, name)| DrawingNodeOutput {
+                destination,
+                shader_variable_name: str_ref(name),
+            })
+            .collect(),
+        viewport: None,
+        scissor: None,
+    }
+}
+
+pub(crate) struct DummyVertexBuffer {
+    pub(crate) usage: VertexBufferUsage,
+    pub(crate) num_vertices: usize,
+}
+
+impl VertexBuffer for DummyVertexBuffer {
+    fn get_usage(&self) -> VertexBufferUsage {
+        self.usage
+    }
+
+    fn get_num_vertices(&self) -> usize {
+        self.num_vertices
+    }
+
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    fn await_ready(&self) {}
+}
+
+/// Creates a *DummyVertexBuffer* with the *WildCard* usage.
+pub(crate) fn create_vertex_buffer(num_vertices: usize) -> Arc<dyn VertexBuffer> {
+    Arc::new(DummyVertexBuffer {
+        usage: VertexBufferUsage::WildCard,
+        num_vertices,
+    })
+}
+
+pub(crate) struct DummyIndexBuffer {
+    pub(crate) num_indices: usize,
+}
+
+impl IndexBuffer for DummyIndexBuffer {
+    fn get_num_indices(&self) -> usize {
+        self.num_indices
+    }
+
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    fn await_ready(&self) {}
+}
+
+pub(crate) struct DummyStorageBuffer {
+    pub(crate) element_type: DataType,
+    pub(crate) num_elements: usize,
+}
+
+impl StorageBuffer for DummyStorageBuffer {
+    fn get_element_type(&self) -> DataType {
+        self.element_type
+    }
+
+    fn get_num_elements(&self) -> usize {
+        self.num_elements
+    }
+
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    fn await_ready(&self) {}
+}
+
+/// A 16x16 texture with the *RGBA8Srgb* format.
+pub(crate) struct DummyTexture {}
+
+impl Texture for DummyTexture {
+    fn get_width(&self) -> u32 {
+        16
+    }
+
+    fn get_height(&self) -> u32 {
+        16
+    }
+
+    fn get_format(&self) -> ColorFormat {
+        ColorFormat::RGBA8Srgb
+    }
+
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    fn await_ready(&self) {}
+}
+
+#[derive(Debug)]
+pub(crate) struct DummyVertexShader {
+    pub(crate) variables: Vec<VertexShaderVariable>,
+}
+
+impl VertexShader for DummyVertexShader {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn get_debug_name(&self) -> &StringRef {
+        static NAME: StringRef = StringRef::Static("dummy");
+        &NAME
+    }
+
+    fn get_variables(&self) -> &Vec<VertexShaderVariable> {
+        &self.variables
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct DummyFragmentShader {
+    pub(crate) variables: Vec<FragmentShaderVariable>,
+}
+
+impl FragmentShader for DummyFragmentShader {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn get_debug_name(&self) -> &StringRef {
+        static NAME: StringRef = StringRef::Static("dummy");
+        &NAME
+    }
+
+    fn get_variables(&self) -> &Vec<FragmentShaderVariable> {
+        &self.variables
+    }
+}
+
+/// An *AbstractGridGroup* whose grids are described by an
+/// *AbstractGridGroupBuilder*. Its pipelines are *DummyPipeline*s, and it
+/// can't create concrete grid groups or render flows.
+pub(crate) struct DummyGroup {
+    pub(crate) builder: AbstractGridGroupBuilder,
+    pub(crate) ids: GridGroupIDs,
+}
+
+impl DummyGroup {
+    pub(crate) fn new(builder: AbstractGridGroupBuilder) -> Self {
+        let ids = GridIDAllocator::new().allocate(&builder);
+        Self { builder, ids }
+    }
+
+    /// Gets the abstract ids of the color grids, in order.
+    pub(crate) fn get_color_ids(&self) -> Vec<AbstractGridID> {
+        self.ids.colors.iter().map(|id| id.get_abstract()).collect()
+    }
+
+    fn get_color_grid(&self, id: AbstractGridID) -> Option<&AbstractColorGridBuilder> {
+        let index = self
+            .ids
+            .colors
+            .iter()
+            .position(|c| c.get_abstract() == id)?;
+        Some(&self.builder.color_grids[index])
+    }
+
+    fn get_depth_stencil_grid(&self, id: AbstractGridID) -> &AbstractDepthStencilGridBuilder {
+        let index = self
+            .ids
+            .depth_stencils
+            .iter()
+            .position(|d| d.get_abstract() == id)
+            .expect("The grid doesn't belong to this group");
+        &self.builder.depth_stencil_grids[index]
+    }
+}
+
+impl AbstractGridGroup for DummyGroup {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn create_concrete(&self, _width: u32, _height: u32) -> Arc<dyn GridGroup> {
+        unimplemented!()
+    }
+
+    fn create_graphics_pipeline(
+        &self,
+        shaders: &ShaderPair,
+        topology: PrimitiveTopology,
+        state: &PipelineStateBuilder,
+    ) -> Arc<dyn GraphicsPipeline> {
+        Arc::new(DummyPipeline {
+            name: str_ref("dummy"),
+            variables: shaders.get_external_variables().clone(),
+            outputs: shaders.get_output_variables().clone(),
+            state: PipelineState::resolve(state, topology),
+            topology,
+        })
+    }
+
+    fn get_color_format(&self, id: ColorGridID) -> ColorFormat {
+        self.get_color_grid(id.get_abstract()).unwrap().format
+    }
+
+    fn get_sample_count(&self, id: AbstractGridID) -> SampleCount {
+        match self.get_color_grid(id) {
+            Some(grid) => grid.sample_count,
+            None => self.get_depth_stencil_grid(id).sample_count,
+        }
+    }
+
+    fn get_grid_size(&self, id: AbstractGridID) -> GridSize {
+        match self.get_color_grid(id) {
+            Some(grid) => grid.size,
+            None => self.get_depth_stencil_grid(id).size,
+        }
+    }
+
+    fn has_shader_read_purpose(&self, id: AbstractGridID) -> bool {
+        match self.get_color_grid(id) {
+            Some(grid) => grid.purpose == ColorPurpose::ShaderRead,
+            None => self.get_depth_stencil_grid(id).purpose == DepthStencilPurpose::ShaderRead,
+        }
+    }
+
+    fn is_transient(&self, id: AbstractGridID) -> bool {
+        match self.get_color_grid(id) {
+            Some(grid) => grid.is_transient(),
+            None => self.get_depth_stencil_grid(id).is_transient(),
+        }
+    }
+
+    fn create_render_flow_builder(&self) -> RenderFlowBuilder {
+        RenderFlowBuilder::new(0)
+    }
+
+    fn create_render_flow(&self, _builder: RenderFlowBuilder) -> Arc<dyn RenderFlow> {
+        unimplemented!()
+    }
+}
+
+/// Creates an *AbstractColorGridBuilder* with the given properties and the
+/// *FULL* size.
+pub(crate) fn color_grid(
+    start_operation: ColorStartOperation,
+    purpose: ColorPurpose,
+    format: ColorFormat,
+    sample_count: SampleCount,
+) -> AbstractColorGridBuilder {
+    AbstractColorGridBuilder {
+        start_operation,
+        purpose,
+        format,
+        sample_count,
+        size: GridSize::FULL,
+    }
+}
+
+/// An *OcclusionQuery* whose result is 'computed' by another thread, like a
+/// GPU would do.
+pub(crate) struct DummyQuery {
+    result: Mutex<Option<u64>>,
+    condition: Condvar,
+}
+
+impl DummyQuery {
+    /// Makes the result of this query available.
+    pub(crate) fn finish(&self, samples_passed: u64) {
+        *self.result.lock().unwrap() = Some(samples_passed);
+        self.condition.notify_all();
+    }
+}
+
+impl OcclusionQuery for DummyQuery {
+    fn get_kind(&self) -> OcclusionQueryKind {
+        OcclusionQueryKind::SamplesPassed
+    }
+
+    fn is_ready(&self) -> bool {
+        self.result.lock().unwrap().is_some()
+    }
+
+    fn await_ready(&self) {
+        let mut result = self.result.lock().unwrap();
+        while result.is_none() {
+            result = self.condition.wait(result).unwrap();
+        }
+    }
+
+    fn get_samples_passed(&self) -> Option<u64> {
+        *self.result.lock().unwrap()
+    }
+}
+
+/// Creates a *DummyQuery* without a result.
+pub(crate) fn create_query() -> Arc<DummyQuery> {
+    Arc::new(DummyQuery {
+        result: Mutex::new(None),
+        condition: Condvar::new(),
+    })
+}
@@ -0,0 +1,27 @@
+use crate::*;
+
+/// Represents an image in GPU memory that shaders can sample. It can be bound to shader variables
+/// of type *TextureInput*. To obtain a *Texture*, use the *transfer_texture* method of the
+/// *Gateway*.
+///
+/// Unlike the grids of a *GridGroup*, textures are not drawn on: their content is transferred from
+/// the CPU once, and then only read by shaders.
+pub trait Texture: Send + Sync {
+
+    /// Gets the width of this *Texture*, in pixels.
+    fn get_width(&self) -> u32;
+
+    /// Gets the height of this *Texture*, in pixels.
+    fn get_height(&self) -> u32;
+
+    /// Gets the *ColorFormat* of the pixels of this *Texture*.
+    fn get_format(&self) -> ColorFormat;
+
+    /// Checks if this *Texture* is ready to be used right away. See the *is_ready* method of
+    /// *VertexBuffer* for more information.
+    fn is_ready(&self) -> bool;
+
+    /// Blocks the current thread until this *Texture* is ready to be used. See the *await_ready*
+    /// method of *VertexBuffer* for more information.
+    fn await_ready(&self);
+}
@@ -1,4 +1,7 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::ops::Add;
 use std::sync::Arc;
 
@@ -41,6 +44,30 @@ impl PartialEq<&str> for StringRef {
     }
 }
 
+impl Hash for StringRef {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_str().hash(state)
+    }
+}
+
+impl PartialOrd for StringRef {
+    fn partial_cmp(&self, other: &StringRef) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for StringRef {
+    fn cmp(&self, other: &StringRef) -> Ordering {
+        self.to_str().cmp(other.to_str())
+    }
+}
+
+impl Borrow<str> for StringRef {
+    fn borrow(&self) -> &str {
+        self.to_str()
+    }
+}
+
 impl Clone for StringRef {
     fn clone(&self) -> StringRef {
         match self {
@@ -2,7 +2,7 @@ use crate::*;
 
 /// Represents a list/buffer of vertices in GPU memory. To obtain a *VertexBuffer*, create a
 /// *VertexStore* and transfer it through the *Gateway*.
-pub trait VertexBuffer {
+pub trait VertexBuffer: Send + Sync {
 
     /// Gets the *VertexBufferUsage* of this *VertexBuffer*. This is the same usage that was passed
     /// as parameter when this vertex buffer was created.
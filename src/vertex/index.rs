@@ -0,0 +1,19 @@
+/// Represents a list/buffer of vertex indices in GPU memory. Every index is a *u32* that points to
+/// a vertex in the *VertexBuffer* it is drawn with. To obtain an *IndexBuffer*, use the
+/// *transfer_indices* method of the *Gateway*.
+///
+/// Index buffers should be drawn with vertex buffers whose *VertexBufferUsage* is *Indices* or
+/// *WildCard*.
+pub trait IndexBuffer: Send + Sync {
+
+    /// Gets the number of indices in this *IndexBuffer*.
+    fn get_num_indices(&self) -> usize;
+
+    /// Checks if this *IndexBuffer* is ready to be used for drawing right away. See the *is_ready*
+    /// method of *VertexBuffer* for more information.
+    fn is_ready(&self) -> bool;
+
+    /// Blocks the current thread until this *IndexBuffer* is ready to be drawn. See the
+    /// *await_ready* method of *VertexBuffer* for more information.
+    fn await_ready(&self);
+}
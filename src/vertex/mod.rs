@@ -3,12 +3,14 @@ mod description;
 mod store;
 mod usage;
 mod buffer;
+mod index;
 
 pub use attribute::*;
 pub use description::*;
 pub use store::*;
 pub use usage::*;
 pub use buffer::*;
+pub use index::*;
 
 /// Structs implementing this trait represent vertices on the CPU side. They should have attributes
 /// like position and texture coordinates that are meant to be sent to the graphics card, and
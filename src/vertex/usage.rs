@@ -2,7 +2,7 @@ use crate::PrimitiveTopology;
 
 /// This enum is used to indicate what a *VertexBuffer* is going to be used for. (What will be its
 /// *PrimitiveTopology*? Will it be used in combination with an *IndexBuffer*?)
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum VertexBufferUsage {
 
     /// The vertex buffer will be drawn without an associated index buffer. Thus, the *topology*